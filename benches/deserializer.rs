@@ -0,0 +1,86 @@
+#![allow(clippy::needless_return)]
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use light_ron::deserial::{RonDeserializer, RonEvent};
+
+/// Builds a list of `count` named structs, which makes the deserializer push back several tokens per value
+/// while telling structs and tuples apart.
+fn player_list(count: usize) -> String {
+    let mut src = String::from("[\n");
+    for i in 0..count {
+        src.push_str(&format!(
+            "    Player(name: \"Player{i}\", pos: ({i}.0, 0.5, -{i}.25), hp: {i}, alive: true, class: Warrior),\n"
+        ));
+    }
+    src.push(']');
+    return src;
+}
+
+/// Builds `depth` nested lists with a struct at the bottom.
+fn deep_nesting(depth: usize) -> String {
+    let mut src = "[".repeat(depth);
+    src.push_str("Leaf(value: 1)");
+    src.push_str(&"]".repeat(depth));
+    return src;
+}
+
+/// Builds a flat list of `count` integers.
+fn long_list(count: usize) -> String {
+    let mut src = String::from("[");
+    for i in 0..count {
+        src.push_str(&format!("{i}, "));
+    }
+    src.push(']');
+    return src;
+}
+
+/// Builds a map of `count` entries whose values are `len`-byte strings.
+fn big_strings(count: usize, len: usize) -> String {
+    let value = "lorem ipsum ".repeat(len / 12 + 1);
+    let mut src = String::from("{\n");
+    for i in 0..count {
+        src.push_str(&format!("    \"key{i}\": \"{}\",\n", &value[..len]));
+    }
+    src.push('}');
+    return src;
+}
+
+fn drain(src: &str) -> usize {
+    let mut parser = RonDeserializer::new(src);
+    let mut count = 0;
+    while parser.next_event().unwrap() != RonEvent::Eof {
+        count += 1;
+    }
+    return count;
+}
+
+fn large_documents(c: &mut Criterion) {
+    let mut group = c.benchmark_group("large_documents");
+    for count in [100, 1_000, 10_000] {
+        let src = player_list(count);
+        group.throughput(Throughput::Bytes(src.len() as u64));
+        group.bench_with_input(BenchmarkId::new("player_list", count), &src, |b, src| {
+            b.iter(|| drain(black_box(src)))
+        });
+    }
+    group.finish();
+}
+
+fn synthetic_documents(c: &mut Criterion) {
+    let mut group = c.benchmark_group("synthetic_documents");
+    let documents = [
+        ("deep_nesting", deep_nesting(2_000)),
+        ("long_list", long_list(100_000)),
+        ("big_strings", big_strings(1_000, 4_096)),
+    ];
+    for (name, src) in &documents {
+        group.throughput(Throughput::Bytes(src.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), src, |b, src| {
+            b.iter(|| drain(black_box(src)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, large_documents, synthetic_documents);
+criterion_main!(benches);
@@ -0,0 +1,153 @@
+use proc_macro2::Ident;
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::{Attribute, Error, Field, Fields, LitStr, Path, Result, Type};
+
+/// Attributes of the struct or enum being derived.
+pub struct ContainerAttrs {
+    pub rename: Option<String>,
+    pub deny_unknown_fields: bool,
+}
+
+impl ContainerAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut this = Self { rename: None, deny_unknown_fields: false };
+        for_each_meta(attrs, |meta| {
+            if meta.path.is_ident("rename") {
+                this.rename = Some(string_value(&meta)?);
+            } else if meta.path.is_ident("deny_unknown_fields") {
+                this.deny_unknown_fields = true;
+            } else {
+                return Err(meta.error("Unknown ron attribute for a struct or enum"));
+            }
+            return Ok(());
+        })?;
+        return Ok(this);
+    }
+}
+
+/// Attributes of an enum variant.
+pub struct VariantAttrs {
+    pub rename: Option<String>,
+    pub aliases: Vec<String>,
+}
+
+impl VariantAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut this = Self { rename: None, aliases: Vec::new() };
+        for_each_meta(attrs, |meta| {
+            if meta.path.is_ident("rename") {
+                this.rename = Some(string_value(&meta)?);
+            } else if meta.path.is_ident("alias") {
+                this.aliases.push(string_value(&meta)?);
+            } else {
+                return Err(meta.error("Unknown ron attribute for a variant"));
+            }
+            return Ok(());
+        })?;
+        return Ok(this);
+    }
+}
+
+/// Attributes of a named field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    pub rename: Option<String>,
+    pub aliases: Vec<String>,
+    /// `Some(None)` for `Default::default()`, or `Some(path)` for a function.
+    pub default: Option<Option<Path>>,
+    pub skip: bool,
+    pub skip_serializing_if: Option<Path>,
+    pub flatten: bool,
+    /// A module with `serialize` and `deserialize` functions used instead of the field type's impls.
+    pub with: Option<Path>,
+}
+
+impl FieldAttrs {
+    pub fn parse(attrs: &[Attribute]) -> Result<Self> {
+        let mut this = Self::default();
+        for_each_meta(attrs, |meta| {
+            if meta.path.is_ident("rename") {
+                this.rename = Some(string_value(&meta)?);
+            } else if meta.path.is_ident("alias") {
+                this.aliases.push(string_value(&meta)?);
+            } else if meta.path.is_ident("default") {
+                this.default = Some(if meta.input.peek(syn::Token![=]) { Some(path_value(&meta)?) } else { None });
+            } else if meta.path.is_ident("skip") {
+                this.skip = true;
+            } else if meta.path.is_ident("skip_serializing_if") {
+                this.skip_serializing_if = Some(path_value(&meta)?);
+            } else if meta.path.is_ident("flatten") {
+                this.flatten = true;
+            } else if meta.path.is_ident("with") {
+                this.with = Some(path_value(&meta)?);
+            } else {
+                return Err(meta.error("Unknown ron attribute for a field"));
+            }
+            return Ok(());
+        })?;
+        return Ok(this);
+    }
+}
+
+/// A named field with its attributes.
+pub struct NamedField<'f> {
+    pub ident: &'f Ident,
+    pub ty: &'f Type,
+    /// The name it's written with.
+    pub name: String,
+    pub attrs: FieldAttrs,
+}
+
+impl NamedField<'_> {
+    /// Every name the field is read with.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        return std::iter::once(self.name.as_str()).chain(self.attrs.aliases.iter().map(String::as_str));
+    }
+}
+
+/// Parses the attributes of the fields of a struct or variant. Unnamed fields can't have any.
+pub fn named_fields(fields: &Fields) -> Result<Vec<NamedField<'_>>> {
+    let mut named = Vec::new();
+    for field in fields {
+        let Some(ident) = &field.ident else {
+            reject_attrs(field)?;
+            continue;
+        };
+        let attrs = FieldAttrs::parse(&field.attrs)?;
+        if attrs.flatten && (attrs.rename.is_some() || !attrs.aliases.is_empty() || attrs.skip || attrs.skip_serializing_if.is_some()) {
+            return Err(Error::new_spanned(field, "A flattened field can't be renamed or skipped"));
+        }
+        if attrs.flatten && attrs.with.is_some() {
+            return Err(Error::new_spanned(field, "A flattened field is read and written as fields, so it can't use `with`"));
+        }
+        if attrs.flatten && attrs.default.is_some() {
+            return Err(Error::new_spanned(field, "A flattened field is built from its own fields, so it can't have a default"));
+        }
+        let name = attrs.rename.clone().unwrap_or_else(|| ident.unraw().to_string());
+        named.push(NamedField { ident, ty: &field.ty, name, attrs });
+    }
+    return Ok(named);
+}
+
+fn reject_attrs(field: &Field) -> Result<()> {
+    return match field.attrs.iter().find(|x| x.path().is_ident("ron")) {
+        Some(attr) => Err(Error::new_spanned(attr, "ron attributes are only supported on named fields")),
+        None => Ok(()),
+    };
+}
+
+fn for_each_meta(attrs: &[Attribute], mut f: impl FnMut(ParseNestedMeta) -> Result<()>) -> Result<()> {
+    for attr in attrs.iter().filter(|x| x.path().is_ident("ron")) {
+        attr.parse_nested_meta(&mut f)?;
+    }
+    return Ok(());
+}
+
+fn string_value(meta: &ParseNestedMeta) -> Result<String> {
+    return Ok(meta.value()?.parse::<LitStr>()?.value());
+}
+
+fn path_value(meta: &ParseNestedMeta) -> Result<Path> {
+    return meta.value()?.parse::<LitStr>()?.parse();
+}
@@ -0,0 +1,281 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Fields, GenericParam, Generics, Lifetime, LifetimeParam, Result};
+
+use crate::attr::{named_fields, ContainerAttrs, NamedField, VariantAttrs};
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let attrs = ContainerAttrs::parse(&input.attrs)?;
+    let ident = &input.ident;
+    let name = attrs.rename.unwrap_or_else(|| ident.to_string());
+    let deny_unknown = attrs.deny_unknown_fields;
+    let (generics, lt) = de_generics(&input.generics);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    let (body, fields_impl) = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => {
+                let fields = named_fields(&data.fields)?;
+                let body = quote! {
+                    let __fields = <Self as ::light_ron::deserial::DeserializeFields<#lt>>::fields();
+                    let __reader = ::light_ron::deserial::StructReader::with_name(de, #name, &__fields)?
+                        .optional(&__fields)
+                        .deny_unknown_fields(#deny_unknown);
+                    return <Self as ::light_ron::deserial::DeserializeFields<#lt>>::read_all(__reader);
+                };
+                let partial_ty = partial_type(&fields, &lt);
+                let fields_expr = fields_expr(&fields, &lt);
+                let partial_init = partial_init(&fields, &lt);
+                let read_field = read_field(&fields, &lt);
+                let finish = finish(&fields, &lt, quote!(Self));
+                let fields_impl = quote! {
+                    impl #impl_generics ::light_ron::deserial::DeserializeFields<#lt> for #ident #ty_generics #where_clause {
+                        type Partial = #partial_ty;
+
+                        fn fields() -> ::std::borrow::Cow<'static, [&'static str]> {
+                            return #fields_expr;
+                        }
+
+                        fn partial() -> Self::Partial {
+                            return #partial_init;
+                        }
+
+                        fn read_field(
+                            __partial: &mut Self::Partial,
+                            __field: &'static str,
+                            __reader: &mut ::light_ron::deserial::StructReader<'_, #lt>,
+                        ) -> ::std::result::Result<bool, ::light_ron::deserial::RonError> {
+                            return ::std::result::Result::Ok(#read_field);
+                        }
+
+                        fn finish(__partial: Self::Partial, __missing: &mut ::std::vec::Vec<::std::string::String>) -> ::std::option::Option<Self> {
+                            return #finish;
+                        }
+                    }
+                };
+                (body, fields_impl)
+            },
+            Fields::Unnamed(_) | Fields::Unit => {
+                named_fields(&data.fields)?;
+                let elements = data.fields.iter().map(|_| quote!(::light_ron::deserial::RonDeserialize::deserialize(de)?));
+                let value = match data.fields {
+                    Fields::Unit => quote!(Self),
+                    _ => quote!(Self(#(#elements),*)),
+                };
+                let body = quote! {
+                    return ::light_ron::deserial::read_tuple_struct(de, #name, |de| ::std::result::Result::Ok(#value));
+                };
+                (body, TokenStream::new())
+            },
+        },
+        Data::Enum(data) => {
+            let mut names = Vec::new();
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let attrs = VariantAttrs::parse(&variant.attrs)?;
+                let start = names.len();
+                names.push(attrs.rename.unwrap_or_else(|| variant.ident.to_string()));
+                names.extend(attrs.aliases);
+                let indices = start..names.len();
+                let body = variant_body(&variant.ident, &variant.fields, &lt, deny_unknown)?;
+                arms.push(quote!(#(#indices)|* => #body,));
+            }
+            let body = quote! {
+                let (__i, __variant) = ::light_ron::deserial::read_enum(de, &[#(#names),*])?;
+                return match __i {
+                    #(#arms)*
+                    _ => ::std::unreachable!(),
+                };
+            };
+            (body, TokenStream::new())
+        },
+        Data::Union(_) => return Err(Error::new_spanned(input, "RonDeserialize can't be derived for unions")),
+    };
+
+    return Ok(quote! {
+        impl #impl_generics ::light_ron::deserial::RonDeserialize<#lt> for #ident #ty_generics #where_clause {
+            fn deserialize(de: &mut ::light_ron::deserial::RonDeserializer<#lt>) -> ::std::result::Result<Self, ::light_ron::deserial::RonError> {
+                #body
+            }
+        }
+
+        #fields_impl
+    });
+}
+
+/// Reads the data of a variant from `__variant`, evaluating to a `Result<Self, RonError>`.
+fn variant_body(ident: &syn::Ident, fields: &Fields, lt: &Lifetime, deny_unknown: bool) -> Result<TokenStream> {
+    return Ok(match fields {
+        Fields::Unit => quote!(__variant.unit().map(|_| Self::#ident)),
+        Fields::Unnamed(_) => {
+            named_fields(fields)?;
+            let bindings: Vec<_> = (0..fields.len()).map(|i| format_ident!("__f{i}")).collect();
+            let tys = fields.iter().map(|x| &x.ty);
+            quote! {{
+                let (#(#bindings,)*) = __variant.tuple::<(#(#tys,)*)>()?;
+                ::std::result::Result::Ok(Self::#ident(#(#bindings),*))
+            }}
+        },
+        Fields::Named(_) => {
+            let fields = named_fields(fields)?;
+            let fields_expr = fields_expr(&fields, lt);
+            let partial_init = partial_init(&fields, lt);
+            let read_field = read_field(&fields, lt);
+            let finish = finish(&fields, lt, quote!(Self::#ident));
+            quote! {{
+                let __fields: ::std::borrow::Cow<'static, [&'static str]> = #fields_expr;
+                let mut __reader = __variant.fields(&__fields)?.optional(&__fields).deny_unknown_fields(#deny_unknown);
+                let __reader = &mut __reader;
+                let mut __partial = #partial_init;
+                while let ::std::option::Option::Some(__field) = __reader.next_field()? {
+                    if !(#read_field) {
+                        __reader.skip()?;
+                    }
+                }
+                let mut __missing = ::std::vec::Vec::new();
+                let __value = {
+                    let __missing = &mut __missing;
+                    #finish
+                };
+                match __value {
+                    ::std::option::Option::Some(x) => ::std::result::Result::Ok(x),
+                    ::std::option::Option::None => ::std::result::Result::Err(
+                        __reader.deserializer().error(::light_ron::deserial::ErrorKind::MissingFields(__missing)),
+                    ),
+                }
+            }}
+        },
+    });
+}
+
+/// The generics of the impls, with a lifetime for the source text if the type doesn't borrow from it already, and
+/// each type parameter bound by `RonDeserialize`.
+fn de_generics(generics: &Generics) -> (Generics, Lifetime) {
+    let mut generics = generics.clone();
+    let lt = match generics.lifetimes().next() {
+        Some(x) => x.lifetime.clone(),
+        None => {
+            let lt = Lifetime::new("'de", Span::call_site());
+            generics.params.insert(0, GenericParam::Lifetime(LifetimeParam::new(lt.clone())));
+            lt
+        },
+    };
+    let params: Vec<_> = generics.type_params().map(|x| x.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.push(syn::parse_quote!(#param: ::light_ron::deserial::RonDeserialize<#lt>));
+    }
+    return (generics, lt);
+}
+
+/// Expression for the names of every field that can be read.
+fn fields_expr(fields: &[NamedField], lt: &Lifetime) -> TokenStream {
+    let names = fields.iter().filter(|x| !x.attrs.skip && !x.attrs.flatten).flat_map(NamedField::names);
+    let flattened: Vec<_> = fields.iter().filter(|x| x.attrs.flatten).map(|x| x.ty).collect();
+    if flattened.is_empty() {
+        return quote!(::std::borrow::Cow::Borrowed(&[#(#names),*]));
+    }
+    return quote! {{
+        let mut names = ::std::vec![#(#names),*];
+        #(names.extend_from_slice(&<#flattened as ::light_ron::deserial::DeserializeFields<#lt>>::fields());)*
+        ::std::borrow::Cow::Owned(names)
+    }};
+}
+
+/// Fields kept in the partial value, with their position in it.
+fn kept<'f, 'a>(fields: &'f [NamedField<'a>]) -> impl Iterator<Item = (syn::Index, &'f NamedField<'a>)> {
+    return fields.iter().filter(|x| !x.attrs.skip).enumerate().map(|(i, x)| (syn::Index::from(i), x));
+}
+
+/// A tuple with an `Option` for each field, or the partial value of flattened fields.
+fn partial_type(fields: &[NamedField], lt: &Lifetime) -> TokenStream {
+    let tys = kept(fields).map(|(_, x)| {
+        let ty = x.ty;
+        if x.attrs.flatten {
+            return quote!(<#ty as ::light_ron::deserial::DeserializeFields<#lt>>::Partial);
+        }
+        return quote!(::std::option::Option<#ty>);
+    });
+    return quote!((#(#tys,)*));
+}
+
+fn partial_init(fields: &[NamedField], lt: &Lifetime) -> TokenStream {
+    let values = kept(fields).map(|(_, x)| {
+        let ty = x.ty;
+        if x.attrs.flatten {
+            return quote!(<#ty as ::light_ron::deserial::DeserializeFields<#lt>>::partial());
+        }
+        return quote!(::std::option::Option::None);
+    });
+    return quote!((#(#values,)*));
+}
+
+/// Expression that reads `__field` from `__reader` into `__partial`, evaluating to whether the field was known.
+fn read_field(fields: &[NamedField], lt: &Lifetime) -> TokenStream {
+    let arms = kept(fields).filter(|(_, x)| !x.attrs.flatten).map(|(i, x)| {
+        let names = x.names();
+        let read = match &x.attrs.with {
+            Some(path) => quote!(#path::deserialize(__reader.deserializer())?),
+            None => quote!(__reader.read()?),
+        };
+        return quote! {
+            #(#names)|* => {
+                __partial.#i = ::std::option::Option::Some(#read);
+                true
+            },
+        };
+    });
+    let flattened = kept(fields).filter(|(_, x)| x.attrs.flatten).map(|(i, x)| {
+        let ty = x.ty;
+        return quote!(<#ty as ::light_ron::deserial::DeserializeFields<#lt>>::read_field(&mut __partial.#i, __field, __reader)?);
+    });
+    return quote! {
+        match __field {
+            #(#arms)*
+            _ => #(#flattened ||)* false,
+        }
+    };
+}
+
+/// Expression that builds `ctor` from `__partial`, adding the required fields that are missing to `__missing`.
+fn finish(fields: &[NamedField], lt: &Lifetime, ctor: TokenStream) -> TokenStream {
+    let mut partial = kept(fields).map(|(i, _)| i);
+    let bindings: Vec<_> = (0..fields.len()).map(|i| format_ident!("__f{i}")).collect();
+    let values = fields.iter().map(|x| {
+        let ty = x.ty;
+        let default = match &x.attrs.default {
+            Some(Some(path)) => quote!(#path()),
+            _ => quote!(::std::default::Default::default()),
+        };
+        if x.attrs.skip {
+            return quote!(::std::option::Option::Some(#default));
+        }
+        let i = partial.next().unwrap();
+        if x.attrs.flatten {
+            return quote!(<#ty as ::light_ron::deserial::DeserializeFields<#lt>>::finish(__partial.#i, __missing));
+        }
+        // Fields that can be left out when writing are always optional when reading, so the output reads back.
+        if x.attrs.default.is_some() || x.attrs.skip_serializing_if.is_some() {
+            return quote!(::std::option::Option::Some(__partial.#i.unwrap_or_else(|| #default)));
+        }
+        let name = &x.name;
+        return quote! {
+            match __partial.#i {
+                ::std::option::Option::Some(x) => ::std::option::Option::Some(x),
+                ::std::option::Option::None => {
+                    __missing.push(::std::string::String::from(#name));
+                    ::std::option::Option::None
+                },
+            }
+        };
+    });
+    let idents = fields.iter().map(|x| x.ident);
+    return quote! {{
+        #(let #bindings = #values;)*
+        match (#(#bindings,)*) {
+            (#(::std::option::Option::Some(#bindings),)*) => ::std::option::Option::Some(#ctor { #(#idents: #bindings),* }),
+            _ => ::std::option::Option::None,
+        }
+    }};
+}
@@ -0,0 +1,48 @@
+//! Derive macros for `light-ron`'s `RonSerialize` and `RonDeserialize`. Use them through the `derive` feature of
+//! `light-ron` rather than depending on this crate directly.
+
+#![forbid(unsafe_code)]
+#![allow(clippy::needless_return)]
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod attr;
+mod de;
+mod ser;
+
+/// Implements `RonSerialize`, and `SerializeFields` for structs with named fields.
+///
+/// Structs are written with their name, tuple structs as named tuples, and unit structs as `Name()`. Enum variants
+/// are written as identifiers, named tuples or named structs. See [`RonDeserialize`](derive@RonDeserialize) for the
+/// attributes.
+#[proc_macro_derive(RonSerialize, attributes(ron))]
+pub fn derive_serialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    return ser::expand(&input).unwrap_or_else(syn::Error::into_compile_error).into();
+}
+
+/// Implements `RonDeserialize`, and `DeserializeFields` for structs with named fields.
+///
+/// Fields that the type doesn't have are skipped, and all the required fields that are missing are reported in a single
+/// error. The `#[ron(..)]` attributes are:
+/// - On structs and enums:
+///   - `rename = "Name"` to change the name written before structs and tuples.
+///   - `deny_unknown_fields` to fail with the name and location of the first field the type, or the variant with
+///     named fields, doesn't have. Fields of flattened structs are known.
+/// - On variants, `rename = "Name"`, and `alias = "Old"` to also accept another name. `alias` can be repeated.
+/// - On named fields:
+///   - `rename = "maxHP"` to change the name of the field.
+///   - `alias = "hp"` to also accept another name when reading. It can be repeated.
+///   - `default` to use `Default::default()` when the field is missing, or `default = "path"` to call a function.
+///   - `skip` to never read nor write the field, always using its default.
+///   - `skip_serializing_if = "path"` to leave the field out when a function given a reference to it returns `true`.
+///     When reading, a missing field then gets its default like with `default`, which can still pick the function.
+///   - `flatten` to read and write the fields of another derived struct as if they were fields of this one.
+///   - `with = "module"` to read and write the field with the `deserialize` and `serialize` functions of a module
+///     instead of its type's impls, like `with = "light_ron::codec::base64"` for a `Vec<u8>`.
+#[proc_macro_derive(RonDeserialize, attributes(ron))]
+pub fn derive_deserialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    return de::expand(&input).unwrap_or_else(syn::Error::into_compile_error).into();
+}
@@ -0,0 +1,137 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Fields, Generics, Result};
+
+use crate::attr::{named_fields, ContainerAttrs, NamedField, VariantAttrs};
+
+pub fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let attrs = ContainerAttrs::parse(&input.attrs)?;
+    let ident = &input.ident;
+    let name = attrs.rename.unwrap_or_else(|| ident.to_string());
+    let generics = ser_generics(&input.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let (body, fields_impl) = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(_) => {
+                let fields = named_fields(&data.fields)?;
+                let writes = write_fields(&fields, |x| quote!(&self.#x));
+                let body = quote! {
+                    ser.write_event(::light_ron::deserial::RonEvent::StructStart { name: ::std::option::Option::Some(#name) })?;
+                    ::light_ron::serial::SerializeFields::serialize_fields(self, ser)?;
+                    return ser.write_event(::light_ron::deserial::RonEvent::StructEnd { name: ::std::option::Option::Some(#name) });
+                };
+                let fields_impl = quote! {
+                    impl #impl_generics ::light_ron::serial::SerializeFields for #ident #ty_generics #where_clause {
+                        fn serialize_fields<W: ::std::fmt::Write>(&self, ser: &mut ::light_ron::serial::RonSerializer<W>) -> ::std::fmt::Result {
+                            #writes
+                            return ::std::result::Result::Ok(());
+                        }
+                    }
+                };
+                (body, fields_impl)
+            },
+            Fields::Unnamed(_) | Fields::Unit => {
+                named_fields(&data.fields)?;
+                let indices = (0..data.fields.len()).map(syn::Index::from);
+                let body = write_tuple(&name, indices.map(|i| quote!(&self.#i)));
+                (quote!(return #body;), TokenStream::new())
+            },
+        },
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                let attrs = VariantAttrs::parse(&variant.attrs)?;
+                let name = attrs.rename.unwrap_or_else(|| variant.ident.to_string());
+                let variant_ident = &variant.ident;
+                let bindings: Vec<_> = (0..variant.fields.len()).map(|i| format_ident!("__f{i}")).collect();
+                arms.push(match &variant.fields {
+                    Fields::Unit => quote! {
+                        Self::#variant_ident => ser.write_event(::light_ron::deserial::RonEvent::Primitive(
+                            ::light_ron::deserial::RonPrimitive::Enum(#name),
+                        )),
+                    },
+                    Fields::Unnamed(_) => {
+                        named_fields(&variant.fields)?;
+                        let body = write_tuple(&name, bindings.iter().map(|x| quote!(#x)));
+                        quote!(Self::#variant_ident(#(#bindings),*) => #body,)
+                    },
+                    Fields::Named(_) => {
+                        let fields = named_fields(&variant.fields)?;
+                        let idents = fields.iter().map(|x| x.ident);
+                        let writes = write_fields(&fields, |x| {
+                            let i = fields.iter().position(|y| y.ident == x).unwrap();
+                            let binding = &bindings[i];
+                            return quote!(#binding);
+                        });
+                        quote! {
+                            Self::#variant_ident { #(#idents: #bindings),* } => {
+                                ser.write_event(::light_ron::deserial::RonEvent::StructStart { name: ::std::option::Option::Some(#name) })?;
+                                #writes
+                                ser.write_event(::light_ron::deserial::RonEvent::StructEnd { name: ::std::option::Option::Some(#name) })
+                            },
+                        }
+                    },
+                });
+            }
+            // Matching on a reference to an empty enum isn't exhaustive.
+            let value = if arms.is_empty() { quote!(*self) } else { quote!(self) };
+            (quote!(return match #value { #(#arms)* };), TokenStream::new())
+        },
+        Data::Union(_) => return Err(Error::new_spanned(input, "RonSerialize can't be derived for unions")),
+    };
+
+    return Ok(quote! {
+        impl #impl_generics ::light_ron::serial::RonSerialize for #ident #ty_generics #where_clause {
+            fn serialize<W: ::std::fmt::Write>(&self, ser: &mut ::light_ron::serial::RonSerializer<W>) -> ::std::fmt::Result {
+                #body
+            }
+        }
+
+        #fields_impl
+    });
+}
+
+/// The generics of the impls, with each type parameter bound by `RonSerialize`.
+fn ser_generics(generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    let params: Vec<_> = generics.type_params().map(|x| x.ident.clone()).collect();
+    let where_clause = generics.make_where_clause();
+    for param in params {
+        where_clause.predicates.push(syn::parse_quote!(#param: ::light_ron::serial::RonSerialize));
+    }
+    return generics;
+}
+
+/// Expression that writes a tuple named `name` with the given references to its elements.
+fn write_tuple(name: &str, elements: impl Iterator<Item = TokenStream>) -> TokenStream {
+    return quote! {{
+        ser.write_event(::light_ron::deserial::RonEvent::TupleStart { name: ::std::option::Option::Some(#name) })?;
+        #(::light_ron::serial::RonSerialize::serialize(#elements, ser)?;)*
+        ser.write_event(::light_ron::deserial::RonEvent::TupleEnd { name: ::std::option::Option::Some(#name) })
+    }};
+}
+
+/// Statements that write each field, given the reference to its value from `access`.
+fn write_fields(fields: &[NamedField], access: impl Fn(&syn::Ident) -> TokenStream) -> TokenStream {
+    let writes = fields.iter().filter(|x| !x.attrs.skip).map(|x| {
+        let value = access(x.ident);
+        if x.attrs.flatten {
+            return quote!(::light_ron::serial::SerializeFields::serialize_fields(#value, ser)?;);
+        }
+        let name = &x.name;
+        let serialize = match &x.attrs.with {
+            Some(path) => quote!(#path::serialize),
+            None => quote!(::light_ron::serial::RonSerialize::serialize),
+        };
+        let write = quote! {
+            ser.write_event(::light_ron::deserial::RonEvent::NamedField(#name))?;
+            #serialize(#value, ser)?;
+        };
+        return match &x.attrs.skip_serializing_if {
+            Some(path) => quote!(if !#path(#value) { #write }),
+            None => write,
+        };
+    });
+    return quote!(#(#writes)*);
+}
@@ -0,0 +1,20 @@
+//! Reads a RON document from stdin and writes it back to stdout with the default formatting.
+//!
+//! `cargo run --example reformat < file.ron`
+
+use std::io::Read;
+
+use light_ron::deserial::RonDeserializer;
+use light_ron::serial::{PrettyConfig, RonSerializer};
+
+fn main() {
+    let mut src = String::new();
+    std::io::stdin().read_to_string(&mut src).expect("Couldn't read stdin!");
+
+    let mut de = RonDeserializer::new(&src);
+    let mut ser = RonSerializer::new(String::new(), PrettyConfig::default());
+    match light_ron::transcode(&mut de, &mut ser) {
+        Ok(()) => println!("{}", ser.into_inner()),
+        Err(err) => eprintln!("{err}"),
+    }
+}
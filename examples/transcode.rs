@@ -0,0 +1,26 @@
+//! Reads a RON document from stdin and writes it to stdout as JSON, or the other way around with `--from-json`.
+//!
+//! `cargo run --example transcode --features transcode < file.ron`
+
+use std::io::Read;
+
+use light_ron::serial::PrettyConfig;
+
+fn main() {
+    let mut src = String::new();
+    std::io::stdin().read_to_string(&mut src).expect("Couldn't read stdin!");
+
+    if std::env::args().any(|x| x == "--from-json") {
+        let mut de = serde_json::Deserializer::from_str(&src);
+        match light_ron::serde::transcode_from(&mut de, PrettyConfig::default()) {
+            Ok(ron) => println!("{ron}"),
+            Err(err) => eprintln!("{err}"),
+        }
+    } else {
+        let mut ser = serde_json::Serializer::pretty(std::io::stdout());
+        match light_ron::serde::transcode_to(&src, &mut ser) {
+            Ok(()) => println!(),
+            Err(err) => eprintln!("{err}"),
+        }
+    }
+}
@@ -0,0 +1,18 @@
+//! Checks that reading the events of any document ends with `Eof` or an error, without panicking. Run with
+//! `cargo fuzz run deserializer`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use light_ron::deserial::{RonDeserializer, RonEvent};
+
+fuzz_target!(|src: &str| {
+    let mut de = RonDeserializer::new(src);
+    loop {
+        match de.next_event() {
+            Ok(RonEvent::Eof) | Err(_) => break,
+            Ok(_) => {},
+        }
+    }
+    let _ = de.position();
+});
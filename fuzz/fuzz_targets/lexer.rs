@@ -0,0 +1,19 @@
+//! Checks that the lexer never panics and always makes progress. Run with `cargo fuzz run lexer`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use light_ron::deserial::lexer::Lexer;
+
+fuzz_target!(|src: &str| {
+    let mut lexer = Lexer::new(src);
+    // Every call moves past at least one byte, errors included.
+    for _ in 0..=src.len() {
+        match lexer.next_token() {
+            Ok(Some((_, span))) => assert_eq!(lexer.text(span).len(), span.end - span.start),
+            Ok(None) => return,
+            Err(_) => {},
+        }
+    }
+    panic!("The lexer didn't reach the end of the source");
+});
@@ -0,0 +1,128 @@
+//! Embedding RON files in a program, parsed while it's built.
+//!
+//! This is a separate crate from `light-ron` because it uses `light-ron` to parse the files, so depend on both.
+
+#![forbid(unsafe_code)]
+#![allow(clippy::needless_return)]
+
+use std::path::PathBuf;
+
+use light_ron::value::RonValue;
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, Error, LitStr, Result, Token, Type};
+
+struct Input {
+    path: LitStr,
+    ty: Option<Type>,
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let path = input.parse()?;
+        let ty = match input.parse::<Option<Token![as]>>()? {
+            Some(_) => Some(input.parse()?),
+            None => None,
+        };
+        return Ok(Self { path, ty });
+    }
+}
+
+/// Embeds a RON file, with its path relative to the `Cargo.toml` of the crate using the macro. Files that can't be
+/// read or parsed fail the build, with the location of the error in the file.
+///
+/// `include_ron!("file.ron")` is an expression that builds the file's `RonValue` without parsing anything when the
+/// program runs, like to keep it in a static:
+/// ```ignore
+/// static DEFAULTS: LazyLock<RonValue> = LazyLock::new(|| include_ron!("assets/defaults.ron"));
+/// ```
+///
+/// `include_ron!("file.ron" as Type)` decodes the file into a `Type` that implements `RonDeserialize<'static>`, so it
+/// can borrow strings from the embedded text. Only the syntax is checked while building, so it panics when it runs if
+/// the file doesn't match the type.
+/// ```ignore
+/// let config = include_ron!("config.ron" as Config);
+/// ```
+#[proc_macro]
+pub fn include_ron(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as Input);
+    return expand(&input).unwrap_or_else(Error::into_compile_error).into();
+}
+
+fn expand(input: &Input) -> Result<TokenStream2> {
+    let path = input.path.value();
+    let dir = std::env::var("CARGO_MANIFEST_DIR").map_err(|_| Error::new(input.path.span(), "CARGO_MANIFEST_DIR isn't set"))?;
+    let full = PathBuf::from(dir).join(&path);
+    let src = std::fs::read_to_string(&full).map_err(|err| Error::new(input.path.span(), format!("Failed to read '{}': {err}", full.display())))?;
+    let value: RonValue = light_ron::from_str(&src).map_err(|err| Error::new(input.path.span(), format!("Failed to parse '{path}': {err}")))?;
+
+    // Including the file makes the crate rebuild when it changes.
+    let full = full.to_string_lossy();
+    return match &input.ty {
+        Some(ty) => Ok(quote! {
+            ::light_ron::from_str::<#ty>(::core::include_str!(#full)).unwrap_or_else(|err| ::core::panic!("Failed to decode '{}': {}", #path, err))
+        }),
+        None => {
+            let value = value_tokens(&value);
+            Ok(quote! {{
+                const _: &str = ::core::include_str!(#full);
+                #value
+            }})
+        },
+    };
+}
+
+/// An expression that builds `value`.
+fn value_tokens(value: &RonValue) -> TokenStream2 {
+    let name_tokens = |name: &Option<String>| match name {
+        Some(x) => quote!(::core::option::Option::Some(::std::string::String::from(#x))),
+        None => quote!(::core::option::Option::None),
+    };
+    return match value {
+        RonValue::None => quote!(::light_ron::value::RonValue::None),
+        RonValue::Some(x) => {
+            let x = value_tokens(x);
+            quote!(::light_ron::value::RonValue::Some(::std::boxed::Box::new(#x)))
+        },
+        RonValue::Int(x) => quote!(::light_ron::value::RonValue::Int(#x)),
+        // From the bits, so infinities and NaN come out the same.
+        RonValue::Float(x) => {
+            let bits = x.to_bits();
+            quote!(::light_ron::value::RonValue::Float(f64::from_bits(#bits)))
+        },
+        RonValue::Bool(x) => quote!(::light_ron::value::RonValue::Bool(#x)),
+        RonValue::Char(x) => quote!(::light_ron::value::RonValue::Char(#x)),
+        RonValue::Str(x) => quote!(::light_ron::value::RonValue::Str(::std::string::String::from(#x))),
+        RonValue::Enum(x) => quote!(::light_ron::value::RonValue::Enum(::std::string::String::from(#x))),
+        RonValue::Bytes(x) => {
+            let bytes = Literal::byte_string(x);
+            quote!(::light_ron::value::RonValue::Bytes(::std::vec::Vec::from(#bytes.as_slice())))
+        },
+        RonValue::List(values) => {
+            let values = values.iter().map(value_tokens);
+            quote!(::light_ron::value::RonValue::List(::std::vec![#(#values),*]))
+        },
+        RonValue::Map(entries) => {
+            let entries = entries.iter().map(|(k, v)| {
+                let (k, v) = (value_tokens(k), value_tokens(v));
+                quote!((#k, #v))
+            });
+            quote!(::light_ron::value::RonValue::Map(::std::vec![#(#entries),*]))
+        },
+        RonValue::Tuple { name, values } => {
+            let name = name_tokens(name);
+            let values = values.iter().map(value_tokens);
+            quote!(::light_ron::value::RonValue::Tuple { name: #name, values: ::std::vec![#(#values),*] })
+        },
+        RonValue::Struct { name, fields } => {
+            let name = name_tokens(name);
+            let fields = fields.iter().map(|(k, v)| {
+                let v = value_tokens(v);
+                quote!((::std::string::String::from(#k), #v))
+            });
+            quote!(::light_ron::value::RonValue::Struct { name: #name, fields: ::std::vec![#(#fields),*] })
+        },
+    };
+}
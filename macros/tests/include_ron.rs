@@ -0,0 +1,30 @@
+//! Checks the code generated by `include_ron!` against parsing the same files when the tests run.
+
+#![allow(clippy::needless_return)]
+
+use light_ron::deserial::RonDeserialize;
+use light_ron::value::RonValue;
+use light_ron_macros::include_ron;
+
+#[derive(Debug, PartialEq, RonDeserialize)]
+struct Config<'a> {
+    title: String,
+    name: &'a str,
+    resolution: (u32, u32),
+    players: Vec<Option<String>>,
+}
+
+#[test]
+fn value_test() {
+    let value = include_ron!("tests/data/config.ron");
+    let expected: RonValue = light_ron::from_str(include_str!("data/config.ron")).unwrap();
+    assert_eq!(value, expected);
+    assert_eq!(value["icon"], RonValue::Bytes(b"\x89PNG".to_vec()));
+}
+
+#[test]
+fn typed_test() {
+    let config: Config<'static> = include_ron!("tests/data/config.ron" as Config);
+    assert_eq!(config, Config { title: String::from("Crabs \"&\" pirates"), name: "crabs", resolution: (1920, 1080), players: vec![Some(String::from("a")), None] });
+    assert_eq!(include_ron!("tests/data/config.ron" as RonValue)["key"], RonValue::Char('k'));
+}
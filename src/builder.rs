@@ -0,0 +1,172 @@
+//! Building documents in code, without formatting strings by hand.
+//!
+//! Builders can be turned into [`RonValue`]s, or written straight into a serializer since they implement
+//! [`RonSerialize`].
+//! ```
+//! # use light_ron::builder::Ron;
+//! let player = Ron::struct_("Player")
+//!     .field("name", "x")
+//!     .field("pos", Ron::tuple([0.0, 0.0]))
+//!     .field("powers", Ron::list([Ron::ident("Fire"), Ron::named_tuple("Cast", [3])]))
+//!     .field("factions", Ron::map().entry("pirates", -100).entry("crabs", 30))
+//!     .field("pet", None::<&str>);
+//! assert_eq!(light_ron::to_string(&player).unwrap(), r#"Player(
+//!     name: "x",
+//!     pos: (0.0, 0.0),
+//!     powers: [
+//!         Fire,
+//!         Cast(3),
+//!     ],
+//!     factions: {
+//!         "pirates": -100,
+//!         "crabs": 30,
+//!     },
+//!     pet: None,
+//! )"#);
+//! ```
+
+use std::fmt::{self, Write};
+
+use crate::deserial::RonEvent;
+use crate::serial::{RonSerialize, RonSerializer};
+use crate::value::RonValue;
+
+/// Entry point of the builders.
+pub struct Ron;
+
+impl Ron {
+    /// A struct written with `name` before its fields.
+    pub fn struct_(name: impl Into<String>) -> StructBuilder {
+        return StructBuilder { name: Some(name.into()), fields: Vec::new() };
+    }
+
+    /// A struct without a name, like `(a: 1)`.
+    pub fn unnamed_struct() -> StructBuilder {
+        return StructBuilder { name: None, fields: Vec::new() };
+    }
+
+    pub fn map() -> MapBuilder {
+        return MapBuilder { entries: Vec::new() };
+    }
+
+    pub fn tuple<T: Into<RonValue>>(values: impl IntoIterator<Item = T>) -> RonValue {
+        return RonValue::Tuple { name: None, values: values.into_iter().map(Into::into).collect() };
+    }
+
+    /// A tuple written with `name` before it, like an enum variant with data.
+    pub fn named_tuple<T: Into<RonValue>>(name: impl Into<String>, values: impl IntoIterator<Item = T>) -> RonValue {
+        return RonValue::Tuple { name: Some(name.into()), values: values.into_iter().map(Into::into).collect() };
+    }
+
+    pub fn list<T: Into<RonValue>>(values: impl IntoIterator<Item = T>) -> RonValue {
+        return RonValue::List(values.into_iter().map(Into::into).collect());
+    }
+
+    /// A bare identifier, like an enum variant without data.
+    pub fn ident(name: impl Into<String>) -> RonValue {
+        return RonValue::Enum(name.into());
+    }
+
+    pub fn some(value: impl Into<RonValue>) -> RonValue {
+        return RonValue::Some(Box::new(value.into()));
+    }
+
+    pub fn none() -> RonValue {
+        return RonValue::None;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructBuilder {
+    name: Option<String>,
+    fields: Vec<(String, RonValue)>,
+}
+
+impl StructBuilder {
+    /// Adds a field after the ones already added.
+    pub fn field(mut self, name: impl Into<String>, value: impl Into<RonValue>) -> Self {
+        self.fields.push((name.into(), value.into()));
+        return self;
+    }
+
+    pub fn build(self) -> RonValue {
+        return RonValue::Struct { name: self.name, fields: self.fields };
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapBuilder {
+    entries: Vec<(RonValue, RonValue)>,
+}
+
+impl MapBuilder {
+    /// Adds an entry after the ones already added.
+    pub fn entry(mut self, key: impl Into<RonValue>, value: impl Into<RonValue>) -> Self {
+        self.entries.push((key.into(), value.into()));
+        return self;
+    }
+
+    pub fn build(self) -> RonValue {
+        return RonValue::Map(self.entries);
+    }
+}
+
+impl From<StructBuilder> for RonValue {
+    fn from(x: StructBuilder) -> Self {
+        return x.build();
+    }
+}
+
+impl From<MapBuilder> for RonValue {
+    fn from(x: MapBuilder) -> Self {
+        return x.build();
+    }
+}
+
+impl RonSerialize for StructBuilder {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        ser.write_event(RonEvent::StructStart { name: self.name.as_deref() })?;
+        for (name, x) in &self.fields {
+            ser.write_event(RonEvent::NamedField(name))?;
+            x.serialize(ser)?;
+        }
+        return ser.write_event(RonEvent::StructEnd { name: self.name.as_deref() });
+    }
+}
+
+impl RonSerialize for MapBuilder {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        ser.write_event(RonEvent::MapStart)?;
+        for (k, v) in &self.entries {
+            k.serialize(ser)?;
+            v.serialize(ser)?;
+        }
+        return ser.write_event(RonEvent::MapEnd);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_test() {
+        let value = Ron::unnamed_struct().field("a", 1).field("b", Ron::some(Ron::map().entry(1, "x"))).build();
+        assert_eq!(value, RonValue::Struct {
+            name: None,
+            fields: vec![
+                (String::from("a"), RonValue::Int(1)),
+                (String::from("b"), RonValue::Some(Box::new(RonValue::Map(vec![(RonValue::Int(1), RonValue::Str(String::from("x")))])))),
+            ],
+        });
+    }
+
+    #[test]
+    fn serialize_test() {
+        let builder = Ron::struct_("Named").field("a", Ron::named_tuple("Rect", [1, 2])).field("b", Ron::none());
+        let value: RonValue = builder.clone().into();
+        assert_eq!(crate::to_string(&builder).unwrap(), crate::to_string(&value).unwrap());
+        assert_eq!(crate::from_str::<RonValue>(&crate::to_string(&builder).unwrap()), Ok(value));
+    }
+}
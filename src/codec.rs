@@ -0,0 +1,202 @@
+//! Binary data stored as base64 or hex strings, which is how most RON files hold it.
+//!
+//! The wrappers can be used as field types, and the `read_*` and `write_*` functions in hand written impls. Derived
+//! impls can keep `Vec<u8>` fields with `#[ron(with = "light_ron::codec::base64")]` or `light_ron::codec::hex`. Reading
+//! also accepts byte strings, like `b"\x01"`, so files can move to them without breaking.
+//! ```
+//! # use light_ron::codec::{Base64, Hex};
+//! let (icon, hash): (Base64, Hex) = light_ron::from_str(r#"("aGk=", "00ff")"#).unwrap();
+//! assert_eq!((icon.0.as_slice(), hash.0.as_slice()), (b"hi".as_slice(), [0x00, 0xff].as_slice()));
+//! assert_eq!(light_ron::to_string(&(icon, hash)).unwrap(), r#"("aGk=", "00ff")"#);
+//! ```
+
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+
+use crate::deserial::{unexpected, ErrorKind, RonDeserialize, RonDeserializer, RonError, RonEvent, RonPrimitive};
+use crate::serial::{RonSerialize, RonSerializer};
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Bytes written as a base64 string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Base64(pub Vec<u8>);
+
+/// Bytes written as a string of hex digits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Hex(pub Vec<u8>);
+
+/// Encodes `bytes` in standard base64, with padding.
+pub fn encode_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    return text;
+}
+
+/// Decodes base64 with or without padding, in the standard or the URL safe alphabet. Whitespace isn't accepted.
+pub fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim_end_matches('=');
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let (mut n, mut bits) = (0u32, 0);
+    for c in text.bytes() {
+        let digit = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+        n = n << 6 | digit as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((n >> bits) as u8);
+        }
+    }
+    // A single digit left over can't hold a whole byte.
+    if bits >= 6 {
+        return None;
+    }
+    return Some(bytes);
+}
+
+/// Encodes `bytes` as lowercase hex digits.
+pub fn encode_hex(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len() * 2);
+    for x in bytes {
+        write!(text, "{x:02x}").unwrap();
+    }
+    return text;
+}
+
+/// Decodes hex digits in either case.
+pub fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    return (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect();
+}
+
+/// Reads a base64 string, or a byte string.
+pub fn read_base64(de: &mut RonDeserializer) -> Result<Vec<u8>, RonError> {
+    return read_encoded(de, "base64", decode_base64);
+}
+
+/// Reads a string of hex digits, or a byte string.
+pub fn read_hex(de: &mut RonDeserializer) -> Result<Vec<u8>, RonError> {
+    return read_encoded(de, "hex", decode_hex);
+}
+
+pub fn write_base64<W: Write>(bytes: &[u8], ser: &mut RonSerializer<W>) -> fmt::Result {
+    return ser.write_event(RonEvent::Primitive(RonPrimitive::Str(Cow::Owned(encode_base64(bytes)))));
+}
+
+pub fn write_hex<W: Write>(bytes: &[u8], ser: &mut RonSerializer<W>) -> fmt::Result {
+    return ser.write_event(RonEvent::Primitive(RonPrimitive::Str(Cow::Owned(encode_hex(bytes)))));
+}
+
+/// Functions for `#[ron(with = "light_ron::codec::base64")]`, to write a field of bytes as a base64 string.
+pub mod base64 {
+    use super::*;
+
+    pub fn serialize<W: Write>(bytes: &[u8], ser: &mut RonSerializer<W>) -> fmt::Result {
+        return write_base64(bytes, ser);
+    }
+
+    pub fn deserialize(de: &mut RonDeserializer) -> Result<Vec<u8>, RonError> {
+        return read_base64(de);
+    }
+}
+
+/// Functions for `#[ron(with = "light_ron::codec::hex")]`, to write a field of bytes as a string of hex digits.
+pub mod hex {
+    use super::*;
+
+    pub fn serialize<W: Write>(bytes: &[u8], ser: &mut RonSerializer<W>) -> fmt::Result {
+        return write_hex(bytes, ser);
+    }
+
+    pub fn deserialize(de: &mut RonDeserializer) -> Result<Vec<u8>, RonError> {
+        return read_hex(de);
+    }
+}
+
+fn read_encoded(de: &mut RonDeserializer, encoding: &str, decode: fn(&str) -> Option<Vec<u8>>) -> Result<Vec<u8>, RonError> {
+    return match de.next_non_comment()? {
+        RonEvent::Primitive(RonPrimitive::Str(x)) => decode(&x).ok_or_else(|| de.error(ErrorKind::InvalidValue(format!("Invalid {encoding} string")))),
+        RonEvent::Primitive(RonPrimitive::Bytes(x)) => Ok(x.into_owned()),
+        x => Err(unexpected(de, &format!("a {encoding} string"), x)),
+    };
+}
+
+impl<'a> RonDeserialize<'a> for Base64 {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        return read_base64(de).map(Self);
+    }
+}
+
+impl RonSerialize for Base64 {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return write_base64(&self.0, ser);
+    }
+}
+
+impl<'a> RonDeserialize<'a> for Hex {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        return read_hex(de).map(Self);
+    }
+}
+
+impl RonSerialize for Hex {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return write_hex(&self.0, ser);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64_test() {
+        let cases: [(&[u8], &str); 5] = [(b"", ""), (b"f", "Zg=="), (b"fo", "Zm8="), (b"foo", "Zm9v"), (&[0xfb, 0xff, 0x00, 0x01], "+/8AAQ==")];
+        for (bytes, text) in cases {
+            assert_eq!(encode_base64(bytes), text);
+            assert_eq!(decode_base64(text).as_deref(), Some(bytes));
+        }
+        assert_eq!(decode_base64("-_8AAQ"), Some(vec![0xfb, 0xff, 0x00, 0x01]));
+        assert_eq!(decode_base64("Zm9vY"), None);
+        assert_eq!(decode_base64("Zm 9v"), None);
+    }
+
+    #[test]
+    fn hex_test() {
+        assert_eq!(encode_hex(&[0x00, 0xab, 0x10]), "00ab10");
+        assert_eq!(decode_hex("00AB10"), Some(vec![0x00, 0xab, 0x10]));
+        assert_eq!(decode_hex(""), Some(vec![]));
+        assert_eq!(decode_hex("abc"), None);
+        assert_eq!(decode_hex("zz"), None);
+        assert_eq!(decode_hex("éa"), None);
+    }
+
+    #[test]
+    fn read_test() {
+        assert_eq!(crate::from_str::<Vec<Base64>>(r#"["Zm9v", b"\x01"]"#), Ok(vec![Base64(b"foo".to_vec()), Base64(vec![1])]));
+
+        let err = crate::from_str::<Hex>("\"0g\"").unwrap_err();
+        assert_eq!(err.message(), "Invalid hex string");
+        let err = crate::from_str::<Hex>("[0]").unwrap_err();
+        assert_eq!(err.message(), "Expected a hex string, found a list");
+    }
+}
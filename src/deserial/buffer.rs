@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use super::{DeserializerOptions, ErrorKind, RonDeserialize, RonDeserializer, RonError, RonEvent, Span};
+
+/// The events of a whole document, read once and replayed any number of times, like to decode the same document into
+/// several types without parsing it again.
+///
+/// Replays are [`RonDeserializer`]s, so anything that reads from one works the same, errors point at the same places,
+/// and borrowed strings still come from the source.
+/// ```
+/// # use light_ron::deserial::{RonDeserialize, RonEventBuffer};
+/// # use light_ron::value::RonValue;
+/// let buffer = RonEventBuffer::new("(3, \"three\")").unwrap();
+/// let pair: (u8, &str) = buffer.decode().unwrap();
+/// let value: RonValue = buffer.decode().unwrap();
+/// assert_eq!(pair, (3, "three"));
+/// assert_eq!(value[1].as_str(), Some("three"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RonEventBuffer<'a> {
+    src: &'a str,
+    options: DeserializerOptions,
+    /// Every event up to the first `Eof`, included.
+    events: Arc<[(RonEvent<'a>, Span)]>,
+}
+
+impl<'a> RonEventBuffer<'a> {
+    /// Reads all of `src`.
+    pub fn new(src: &'a str) -> Result<Self, RonError> {
+        return Self::record(RonDeserializer::new(src));
+    }
+
+    /// Reads every event left in `de`. Replays use the options of `de`.
+    pub fn record(mut de: RonDeserializer<'a>) -> Result<Self, RonError> {
+        let mut events = Vec::new();
+        loop {
+            let event = de.next_event()?;
+            let end = event == RonEvent::Eof;
+            events.push((event, de.last_span()));
+            if end {
+                break;
+            }
+        }
+        return Ok(Self { src: de.src(), options: de.options().clone(), events: events.into() });
+    }
+
+    /// A deserializer that returns the recorded events from the start.
+    pub fn replay(&self) -> RonDeserializer<'a> {
+        return RonDeserializer::replaying(self.src, self.options.clone(), self.events.clone());
+    }
+
+    /// Decodes a `T` from a replay, failing if anything follows its value.
+    pub fn decode<T: RonDeserialize<'a>>(&self) -> Result<T, RonError> {
+        let mut de = self.replay();
+        let value = T::deserialize(&mut de)?;
+        return match de.next_event()? {
+            RonEvent::Eof => Ok(value),
+            _ => Err(de.error(ErrorKind::TrailingCharacters)),
+        };
+    }
+
+    /// The recorded events, ending with `Eof`.
+    pub fn events(&self) -> impl Iterator<Item = &RonEvent<'a>> {
+        return self.events.iter().map(|x| &x.0);
+    }
+
+    pub fn src(&self) -> &'a str {
+        return self.src;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deserial::SyntaxOptions;
+
+    #[test]
+    fn replay_test() {
+        let buffer = RonEventBuffer::new("Point(x: 1, y: 2)").unwrap();
+        assert_eq!(buffer.events().count(), 7);
+        let mut de = buffer.replay();
+        assert_eq!(de.next_event().unwrap(), RonEvent::StructStart { name: Some("Point") });
+        assert_eq!(de.last_span(), Span::new(0, 6));
+        assert_eq!(de.next_event().unwrap(), RonEvent::NamedField("x"));
+        assert_eq!(de.next_raw_value().unwrap(), "1");
+        assert_eq!(de.next_event().unwrap(), RonEvent::NamedField("y"));
+
+        // Replays are independent of each other.
+        let mut de = buffer.replay();
+        de.skip_value().unwrap();
+        assert_eq!(de.next_event().unwrap(), RonEvent::Eof);
+        assert_eq!(de.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn decode_test() {
+        let buffer = RonEventBuffer::new("[(1, \"a\"), (2, \"b\\n\")]").unwrap();
+        let pairs: Vec<(i32, String)> = buffer.decode().unwrap();
+        assert_eq!(pairs, [(1, String::from("a")), (2, String::from("b\n"))]);
+        let values: Vec<crate::value::RonValue> = buffer.decode().unwrap();
+        assert_eq!(values[1][1].as_str(), Some("b\n"));
+
+        let err = buffer.decode::<Vec<(i32, i32)>>().unwrap_err();
+        assert_eq!(err.span(), Some(Span::new(5, 8)));
+        assert!(buffer.decode::<i32>().is_err());
+    }
+
+    #[test]
+    fn options_test() {
+        let options = DeserializerOptions { syntax: SyntaxOptions::lenient(), ..Default::default() };
+        let buffer = RonEventBuffer::record(RonDeserializer::with_options("a: 1", options)).unwrap();
+        assert!(buffer.replay().options().syntax.implicit_top_struct);
+        assert!(RonEventBuffer::new("[1, 2").is_err());
+    }
+}
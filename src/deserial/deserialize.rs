@@ -0,0 +1,414 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::hash::{BuildHasher, Hash};
+
+use smallvec::SmallVec;
+
+use super::{ErrorKind, RonDeserializer, RonError, RonEvent, RonPrimitive};
+
+/// A type that can be read from the events of a [`RonDeserializer`].
+///
+/// Implementations consume exactly the events of one value, so they can be composed to decode bigger types.
+pub trait RonDeserialize<'a>: Sized {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError>;
+}
+
+/// Reads a tuple named `name`, like `Vec2(1, 2)`, checking the name as
+/// [`struct_names`](super::DeserializerOptions::struct_names) says. `read` gets the deserializer after the start of the
+/// tuple and reads its elements.
+/// ```
+/// # use light_ron::deserial::{read_tuple_struct, RonDeserialize, RonDeserializer};
+/// let mut de = RonDeserializer::new("Vec2(1, 2)");
+/// let value = read_tuple_struct(&mut de, "Vec2", |de| Ok((i32::deserialize(de)?, i32::deserialize(de)?)));
+/// assert_eq!(value, Ok((1, 2)));
+/// ```
+pub fn read_tuple_struct<'a, T>(de: &mut RonDeserializer<'a>, name: &str, read: impl FnOnce(&mut RonDeserializer<'a>) -> Result<T, RonError>) -> Result<T, RonError> {
+    match de.next_non_comment()? {
+        RonEvent::TupleStart { name: found } => de.check_struct_name(found, name)?,
+        x => return Err(unexpected(de, &format!("tuple {name}"), x)),
+    }
+    let value = read(de)?;
+    return match de.next_non_comment()? {
+        RonEvent::TupleEnd { .. } => Ok(value),
+        x => Err(unexpected(de, &format!("the end of tuple {name}"), x)),
+    };
+}
+
+/// Builds the error returned when an implementation gets an event it can't handle. It points at the last event returned.
+/// Error events from recovery mode are returned as they are.
+pub(crate) fn unexpected(de: &RonDeserializer, expected: &str, got: RonEvent) -> RonError {
+    if let RonEvent::Error(err) = got {
+        return err;
+    }
+    return de.error(ErrorKind::InvalidType { expected: String::from(expected), found: describe(&got) });
+}
+
+/// Short human readable description of an event, for error messages.
+pub(crate) fn describe(event: &RonEvent) -> String {
+    return match *event {
+        RonEvent::OptionalSomeValue => String::from("'Some'"),
+        RonEvent::Primitive(RonPrimitive::NoneOptValue) => String::from("'None'"),
+        RonEvent::Primitive(RonPrimitive::Int(x)) => format!("integer {x}"),
+        RonEvent::Primitive(RonPrimitive::Float(x)) => format!("float {x}"),
+        RonEvent::Primitive(RonPrimitive::TypedInt(x, suffix)) => format!("integer {x}{suffix}"),
+        RonEvent::Primitive(RonPrimitive::TypedFloat(x, suffix)) => format!("float {x}{suffix}"),
+        RonEvent::Primitive(RonPrimitive::Bool(x)) => format!("bool {x}"),
+        RonEvent::Primitive(RonPrimitive::Char(x)) => format!("char {x:?}"),
+        RonEvent::Primitive(RonPrimitive::Str(ref x)) => format!("string {x:?}"),
+        RonEvent::Primitive(RonPrimitive::Enum(x)) => format!("identifier {x}"),
+        RonEvent::Primitive(RonPrimitive::Bytes(ref x)) => format!("byte string of {} bytes", x.len()),
+        RonEvent::StructStart { name: Some(x) } => format!("struct {x}"),
+        RonEvent::StructStart { name: None } => String::from("a struct"),
+        RonEvent::NamedField(x) => format!("field {x}"),
+        RonEvent::StructEnd { .. } => String::from("the end of a struct"),
+        RonEvent::TupleStart { name: Some(x) } => format!("tuple {x}"),
+        RonEvent::TupleStart { name: None } => String::from("a tuple"),
+        RonEvent::TupleEnd { .. } => String::from("the end of a tuple"),
+        RonEvent::MapStart => String::from("a map"),
+        RonEvent::MapEnd => String::from("the end of a map"),
+        RonEvent::ListStart => String::from("a list"),
+        RonEvent::ListEnd => String::from("the end of a list"),
+        RonEvent::Comment(_) => String::from("a comment"),
+        RonEvent::Error(_) => String::from("an error"),
+        RonEvent::DocumentEnd => String::from("the end of a top level value"),
+        RonEvent::Eof => String::from("the end of the document"),
+    };
+}
+
+/// Consumes the next event if it's `end`, returning whether it did so.
+fn next_is_end(de: &mut RonDeserializer, end: RonEvent) -> Result<bool, RonError> {
+    if *de.peek_non_comment()? == end {
+        de.next_non_comment()?;
+        return Ok(true);
+    }
+    return Ok(false);
+}
+
+macro_rules! impl_number {
+    ($($ty:ty: $accessor:ident, $expected:literal),*) => {$(
+        impl<'a> RonDeserialize<'a> for $ty {
+            fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+                return match de.next_non_comment()? {
+                    RonEvent::Primitive(x) => x.$accessor().map_err(|x| de.error(x)),
+                    x => Err(unexpected(de, $expected, x)),
+                };
+            }
+        }
+    )*};
+}
+
+impl_number!(
+    i8: as_i8, "an integer", i16: as_i16, "an integer", i32: as_i32, "an integer", i64: as_i64, "an integer",
+    i128: as_i128, "an integer", isize: as_isize, "an integer", u8: as_u8, "an integer", u16: as_u16, "an integer",
+    u32: as_u32, "an integer", u64: as_u64, "an integer", u128: as_u128, "an integer", usize: as_usize, "an integer",
+    f32: as_f32_checked, "a number", f64: as_f64, "a number"
+);
+
+impl<'a> RonDeserialize<'a> for bool {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        return match de.next_non_comment()? {
+            RonEvent::Primitive(RonPrimitive::Bool(x)) => Ok(x),
+            x => Err(unexpected(de, "a bool", x)),
+        };
+    }
+}
+
+impl<'a> RonDeserialize<'a> for char {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        return match de.next_non_comment()? {
+            RonEvent::Primitive(RonPrimitive::Char(x)) => Ok(x),
+            x => Err(unexpected(de, "a char", x)),
+        };
+    }
+}
+
+/// Strings with escapes can't be borrowed from the source, so they fail to decode as `&str`. `String` and `Cow<str>`
+/// accept them.
+impl<'a> RonDeserialize<'a> for &'a str {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        return match Cow::<str>::deserialize(de)? {
+            Cow::Borrowed(x) => Ok(x),
+            Cow::Owned(_) => Err(de.error(ErrorKind::InvalidValue(String::from("A string with escapes can't be borrowed")))),
+        };
+    }
+}
+
+impl<'a> RonDeserialize<'a> for String {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        return Cow::<str>::deserialize(de).map(Cow::into_owned);
+    }
+}
+
+impl<'a> RonDeserialize<'a> for Cow<'a, str> {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        return match de.next_non_comment()? {
+            RonEvent::Primitive(RonPrimitive::Str(x)) => Ok(x),
+            x => Err(unexpected(de, "a string", x)),
+        };
+    }
+}
+
+impl<'a, T: RonDeserialize<'a>> RonDeserialize<'a> for Box<T> {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        return T::deserialize(de).map(Box::new);
+    }
+}
+
+impl<'a, T: RonDeserialize<'a>> RonDeserialize<'a> for Option<T> {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        return match de.next_non_comment()? {
+            RonEvent::Primitive(RonPrimitive::NoneOptValue) => Ok(None),
+            RonEvent::OptionalSomeValue => T::deserialize(de).map(Some),
+            x => Err(unexpected(de, "an option", x)),
+        };
+    }
+}
+
+/// Reads the elements of a list into `push`, which lets every collection share it.
+fn read_list<'a, T: RonDeserialize<'a>>(de: &mut RonDeserializer<'a>, mut push: impl FnMut(T)) -> Result<(), RonError> {
+    match de.next_non_comment()? {
+        RonEvent::ListStart => {},
+        x => return Err(unexpected(de, "a list", x)),
+    }
+    while !next_is_end(de, RonEvent::ListEnd)? {
+        push(T::deserialize(de)?);
+    }
+    return Ok(());
+}
+
+/// Reads the key value pairs of a map into `insert`, which lets every map type share it.
+fn read_map<'a, K: RonDeserialize<'a>, V: RonDeserialize<'a>>(de: &mut RonDeserializer<'a>, mut insert: impl FnMut(K, V)) -> Result<(), RonError> {
+    match de.next_non_comment()? {
+        RonEvent::MapStart => {},
+        x => return Err(unexpected(de, "a map", x)),
+    }
+    while !next_is_end(de, RonEvent::MapEnd)? {
+        let key = K::deserialize(de)?;
+        let value = V::deserialize(de)?;
+        insert(key, value);
+    }
+    return Ok(());
+}
+
+impl<'a, T: RonDeserialize<'a>> RonDeserialize<'a> for Vec<T> {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        let mut vec = Vec::new();
+        read_list(de, |x| vec.push(x))?;
+        return Ok(vec);
+    }
+}
+
+/// Arrays are read from tuples, like the `ron` crate writes them, but lists are accepted too.
+impl<'a, T: RonDeserialize<'a>, const N: usize> RonDeserialize<'a> for [T; N] {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        // Kept inline, so reading an array doesn't allocate unless it has too many elements.
+        let mut vec = SmallVec::<[T; N]>::new();
+        match de.peek_non_comment()? {
+            RonEvent::ListStart => read_list(de, |x| vec.push(x))?,
+            RonEvent::TupleStart { .. } => {
+                de.next_non_comment()?;
+                while !matches!(de.peek_non_comment()?, RonEvent::TupleEnd { .. }) {
+                    vec.push(T::deserialize(de)?);
+                }
+                de.next_non_comment()?;
+            },
+            _ => {
+                let x = de.next_non_comment()?;
+                return Err(unexpected(de, "an array", x));
+            },
+        }
+
+        let len = vec.len();
+        return vec.into_inner().map_err(|_| de.error(ErrorKind::InvalidValue(format!("Expected an array of length {N}, got {len} elements"))));
+    }
+}
+
+impl<'a, T: RonDeserialize<'a> + Eq + Hash, S: BuildHasher + Default> RonDeserialize<'a> for HashSet<T, S> {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        let mut set = HashSet::default();
+        read_list(de, |x| { set.insert(x); })?;
+        return Ok(set);
+    }
+}
+
+impl<'a, T: RonDeserialize<'a> + Ord> RonDeserialize<'a> for BTreeSet<T> {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        let mut set = BTreeSet::new();
+        read_list(de, |x| { set.insert(x); })?;
+        return Ok(set);
+    }
+}
+
+impl<'a, K: RonDeserialize<'a> + Eq + Hash, V: RonDeserialize<'a>, S: BuildHasher + Default> RonDeserialize<'a> for HashMap<K, V, S> {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        let mut map = HashMap::default();
+        read_map(de, |k, v| { map.insert(k, v); })?;
+        return Ok(map);
+    }
+}
+
+impl<'a, K: RonDeserialize<'a> + Ord, V: RonDeserialize<'a>> RonDeserialize<'a> for BTreeMap<K, V> {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        let mut map = BTreeMap::new();
+        read_map(de, |k, v| { map.insert(k, v); })?;
+        return Ok(map);
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($len:literal => ($($name:ident),*))*) => {$(
+        impl<'a, $($name: RonDeserialize<'a>),*> RonDeserialize<'a> for ($($name,)*) {
+            fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+                match de.next_non_comment()? {
+                    RonEvent::TupleStart { .. } => {},
+                    x => return Err(unexpected(de, concat!("a tuple of ", $len, " elements"), x)),
+                }
+                let value = ($($name::deserialize(de)?,)*);
+                return match de.next_non_comment()? {
+                    RonEvent::TupleEnd { .. } => Ok(value),
+                    x => Err(unexpected(de, concat!("the end of a tuple of ", $len, " elements"), x)),
+                };
+            }
+        }
+    )*};
+}
+
+impl_tuple! {
+    0 => ()
+    1 => (T0)
+    2 => (T0, T1)
+    3 => (T0, T1, T2)
+    4 => (T0, T1, T2, T3)
+    5 => (T0, T1, T2, T3, T4)
+    6 => (T0, T1, T2, T3, T4, T5)
+    7 => (T0, T1, T2, T3, T4, T5, T6)
+    8 => (T0, T1, T2, T3, T4, T5, T6, T7)
+    9 => (T0, T1, T2, T3, T4, T5, T6, T7, T8)
+    10 => (T0, T1, T2, T3, T4, T5, T6, T7, T8, T9)
+    11 => (T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10)
+    12 => (T0, T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deserial::DeserializerOptions;
+
+    #[test]
+    fn int_test() {
+        assert_eq!(u8::deserialize(&mut RonDeserializer::new("255")), Ok(255));
+        assert!(u8::deserialize(&mut RonDeserializer::new("256")).is_err());
+        assert!(u32::deserialize(&mut RonDeserializer::new("-1")).is_err());
+        assert_eq!(i64::deserialize(&mut RonDeserializer::new("-69420")), Ok(-69420));
+    }
+
+    #[test]
+    fn wide_int_test() {
+        assert_eq!(u64::deserialize(&mut RonDeserializer::new("18446744073709551615")), Ok(u64::MAX));
+        let err = u64::deserialize(&mut RonDeserializer::new("18446744073709551616")).unwrap_err();
+        assert_eq!(err.message(), "18446744073709551616 doesn't fit in a u64");
+        assert!(i64::deserialize(&mut RonDeserializer::new("9223372036854775808")).is_err());
+        assert_eq!(i128::deserialize(&mut RonDeserializer::new("-170141183460469231731687303715884105728")), Ok(i128::MIN));
+        assert_eq!(u128::deserialize(&mut RonDeserializer::new("18446744073709551616u128")), Ok(1 << 64));
+        assert!(u128::deserialize(&mut RonDeserializer::new("-1u128")).is_err());
+        assert!(f64::deserialize(&mut RonDeserializer::new("18446744073709551615u64")).is_err());
+    }
+
+    #[test]
+    fn float_test() {
+        assert_eq!(f64::deserialize(&mut RonDeserializer::new("0.5")), Ok(0.5));
+        assert_eq!(f32::deserialize(&mut RonDeserializer::new("2")), Ok(2.0));
+        let err = f32::deserialize(&mut RonDeserializer::new("1000000000000000000000000000000000000000.0")).unwrap_err();
+        assert!(err.message().ends_with("doesn't fit in a f32"));
+    }
+
+    #[test]
+    fn str_test() {
+        assert_eq!(<&str>::deserialize(&mut RonDeserializer::new("\"abc\"")), Ok("abc"));
+        assert_eq!(String::deserialize(&mut RonDeserializer::new("\"abc\"")), Ok(String::from("abc")));
+        assert!(String::deserialize(&mut RonDeserializer::new("abc")).is_err());
+    }
+
+    #[test]
+    fn bool_char_test() {
+        assert_eq!(bool::deserialize(&mut RonDeserializer::new("true")), Ok(true));
+        assert_eq!(char::deserialize(&mut RonDeserializer::new("'x'")), Ok('x'));
+    }
+
+    #[test]
+    fn option_test() {
+        assert_eq!(Option::<i32>::deserialize(&mut RonDeserializer::new("None")), Ok(None));
+        assert_eq!(Option::<i32>::deserialize(&mut RonDeserializer::new("Some(5)")), Ok(Some(5)));
+        assert_eq!(Option::<Option<i32>>::deserialize(&mut RonDeserializer::new("Some(None)")), Ok(Some(None)));
+        assert!(Option::<i32>::deserialize(&mut RonDeserializer::new("5")).is_err());
+    }
+
+    #[test]
+    fn vec_test() {
+        assert_eq!(Vec::<i32>::deserialize(&mut RonDeserializer::new("[1, 2, 3,]")), Ok(vec![1, 2, 3]));
+        assert_eq!(Vec::<i32>::deserialize(&mut RonDeserializer::new("[]")), Ok(vec![]));
+        assert_eq!(Vec::<Vec<u8>>::deserialize(&mut RonDeserializer::new("[[1], [], [2, 3]]")), Ok(vec![vec![1], vec![], vec![2, 3]]));
+        assert!(Vec::<i32>::deserialize(&mut RonDeserializer::new("[1, true]")).is_err());
+    }
+
+    #[test]
+    fn array_test() {
+        assert_eq!(<[f32; 3]>::deserialize(&mut RonDeserializer::new("(1.0, 2.0, 3.0)")), Ok([1.0, 2.0, 3.0]));
+        assert_eq!(<[f32; 2]>::deserialize(&mut RonDeserializer::new("[1.0, 2.0]")), Ok([1.0, 2.0]));
+        assert!(<[f32; 2]>::deserialize(&mut RonDeserializer::new("[1.0, 2.0, 3.0]")).is_err());
+    }
+
+    #[test]
+    fn tuple_test() {
+        assert_eq!(<()>::deserialize(&mut RonDeserializer::new("()")), Ok(()));
+        assert_eq!(<(i32, &str, bool)>::deserialize(&mut RonDeserializer::new(r#"(1, "a", true)"#)), Ok((1, "a", true)));
+        assert!(<(i32, i32)>::deserialize(&mut RonDeserializer::new("(1, 2, 3)")).is_err());
+        assert!(<(i32, i32)>::deserialize(&mut RonDeserializer::new("(1)")).is_err());
+    }
+
+    #[test]
+    fn map_test() {
+        let src = r#"{ "pirates": -100, "crabs": 30 }"#;
+        let map = HashMap::<String, i32>::deserialize(&mut RonDeserializer::new(src)).unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["pirates"], -100);
+
+        let map = BTreeMap::<&str, i32>::deserialize(&mut RonDeserializer::new(src)).unwrap();
+        assert_eq!(map.into_iter().collect::<Vec<_>>(), vec![("crabs", 30), ("pirates", -100)]);
+    }
+
+    #[test]
+    fn set_test() {
+        let set = HashSet::<i32>::deserialize(&mut RonDeserializer::new("[1, 2, 2]")).unwrap();
+        assert_eq!(set.len(), 2);
+        let set = BTreeSet::<i32>::deserialize(&mut RonDeserializer::new("[3, 1]")).unwrap();
+        assert_eq!(set.into_iter().collect::<Vec<_>>(), vec![1, 3]);
+    }
+
+    #[test]
+    fn box_cow_test() {
+        assert_eq!(Box::<i32>::deserialize(&mut RonDeserializer::new("1")), Ok(Box::new(1)));
+        assert_eq!(Cow::<str>::deserialize(&mut RonDeserializer::new("\"a\"")), Ok(Cow::Borrowed("a")));
+    }
+
+    #[test]
+    fn escaped_string_test() {
+        let src = r#""a\"b\\\n\u{e9}\x41""#;
+        assert_eq!(String::deserialize(&mut RonDeserializer::new(src)), Ok(String::from("a\"b\\\néA")));
+        assert!(matches!(Cow::<str>::deserialize(&mut RonDeserializer::new(src)), Ok(Cow::Owned(_))));
+        let err = <&str>::deserialize(&mut RonDeserializer::new(src)).unwrap_err();
+        assert_eq!(err.message(), "A string with escapes can't be borrowed");
+    }
+
+    #[test]
+    fn comments_test() {
+        let read = |src| RonDeserializer::with_options(src, DeserializerOptions { comments: true, ..Default::default() });
+        let src = "// Spawns\n[ /* a */ (1, Some(\"x\")), (2 /* b */, None) // c\n]";
+        assert_eq!(Vec::<(i32, Option<&str>)>::deserialize(&mut read(src)), Ok(vec![(1, Some("x")), (2, None)]));
+        let src = "{ // a\n\"a\": [1, /* b */ 2] }";
+        assert_eq!(BTreeMap::<String, Vec<u8>>::deserialize(&mut read(src)), Ok(BTreeMap::from([(String::from("a"), vec![1, 2])])));
+        let value = read_tuple_struct(&mut read("/* a */ Vec2( // b\n1, 2 /* c */)"), "Vec2", |de| Ok((i32::deserialize(de)?, i32::deserialize(de)?)));
+        assert_eq!(value, Ok((1, 2)));
+    }
+}
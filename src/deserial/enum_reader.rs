@@ -0,0 +1,161 @@
+use super::{unexpected, RonDeserialize, RonDeserializer, RonError, RonEvent, RonPrimitive, StructReader};
+
+/// The shape of the data of an enum variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariantKind {
+    /// No data, like `Fire`.
+    Unit,
+    /// Unnamed fields, like `Beam(2.0)`.
+    Tuple,
+    /// Named fields, like `Water(depth: 3)`.
+    Struct,
+}
+
+/// Reads the variant of the enum that comes next, which has to be one of `variants`, returning its index and a reader
+/// for its data. Nothing is consumed until the data is read.
+/// ```
+/// # use light_ron::deserial::{read_enum, RonDeserializer, RonError};
+/// #[derive(Debug, PartialEq)]
+/// enum Spell { Fire, Water { depth: i32 }, Beam(f32) }
+///
+/// fn read_spell(de: &mut RonDeserializer) -> Result<Spell, RonError> {
+///     let (i, variant) = read_enum(de, &["Fire", "Water", "Beam"])?;
+///     return match i {
+///         0 => variant.unit().map(|_| Spell::Fire),
+///         1 => {
+///             let mut reader = variant.fields(&["depth"])?;
+///             let mut depth = 0;
+///             while reader.next_field()?.is_some() {
+///                 depth = reader.read()?;
+///             }
+///             Ok(Spell::Water { depth })
+///         },
+///         _ => variant.newtype().map(Spell::Beam),
+///     };
+/// }
+///
+/// let mut de = RonDeserializer::new("[Fire, Water(depth: 3), Beam(2.0)]");
+/// de.next_event().unwrap();
+/// let spells = [read_spell(&mut de), read_spell(&mut de), read_spell(&mut de)];
+/// assert_eq!(spells, [Ok(Spell::Fire), Ok(Spell::Water { depth: 3 }), Ok(Spell::Beam(2.0))]);
+/// ```
+pub fn read_enum<'d, 'a>(de: &'d mut RonDeserializer<'a>, variants: &[&str]) -> Result<(usize, VariantReader<'d, 'a>), RonError> {
+    let (name, kind) = match *de.peek_non_comment()? {
+        RonEvent::Primitive(RonPrimitive::Enum(name)) => (name, VariantKind::Unit),
+        RonEvent::TupleStart { name: Some(name) } => (name, VariantKind::Tuple),
+        RonEvent::StructStart { name: Some(name) } => (name, VariantKind::Struct),
+        _ => ("", VariantKind::Unit),
+    };
+    let Some(i) = variants.iter().position(|x| *x == name) else {
+        let x = de.next_non_comment()?;
+        return Err(unexpected(de, &one_of(variants), x));
+    };
+    return Ok((i, VariantReader { de, name, kind }));
+}
+
+/// Reads the data of the variant found by [`read_enum`]. Every method checks that the variant has the shape it reads.
+pub struct VariantReader<'d, 'a> {
+    de: &'d mut RonDeserializer<'a>,
+    name: &'a str,
+    kind: VariantKind,
+}
+
+impl<'d, 'a> VariantReader<'d, 'a> {
+    pub fn name(&self) -> &'a str {
+        return self.name;
+    }
+
+    pub fn kind(&self) -> VariantKind {
+        return self.kind;
+    }
+
+    /// Reads a variant without data.
+    pub fn unit(self) -> Result<(), RonError> {
+        return match self.de.next_non_comment()? {
+            RonEvent::Primitive(RonPrimitive::Enum(_)) => Ok(()),
+            x => Err(unexpected(self.de, &format!("{} without data", self.name), x)),
+        };
+    }
+
+    /// Reads the data of a variant with unnamed fields as a tuple, like `(f32, i32)` for `Beam(2.0, 3)`.
+    pub fn tuple<T: RonDeserialize<'a>>(self) -> Result<T, RonError> {
+        if self.kind != VariantKind::Tuple {
+            let x = self.de.next_non_comment()?;
+            return Err(unexpected(self.de, &format!("{} with unnamed fields", self.name), x));
+        }
+        return T::deserialize(self.de);
+    }
+
+    /// Reads the data of a variant with a single unnamed field, like `Beam(2.0)`.
+    pub fn newtype<T: RonDeserialize<'a>>(self) -> Result<T, RonError> {
+        return self.tuple::<(T,)>().map(|x| x.0);
+    }
+
+    /// Starts reading a variant with named fields, which are checked against `fields`.
+    pub fn fields(self, fields: &'d [&'static str]) -> Result<StructReader<'d, 'a>, RonError> {
+        if self.kind != VariantKind::Struct {
+            let x = self.de.next_non_comment()?;
+            return Err(unexpected(self.de, &format!("{} with named fields", self.name), x));
+        }
+        return StructReader::new(self.de, fields);
+    }
+}
+
+/// Joins the names of the variants as "A", "A or B" or "A, B or C".
+fn one_of(variants: &[&str]) -> String {
+    return match variants {
+        [] => String::from("nothing"),
+        [x] => String::from(*x),
+        [rest @ .., last] => format!("{} or {last}", rest.join(", ")),
+    };
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deserial::Span;
+
+    const VARIANTS: &[&str] = &["Fire", "Water", "Beam"];
+
+    #[test]
+    fn read_test() {
+        let mut de = RonDeserializer::new("[Water(depth: 3), Beam(2.0, 1), Fire]");
+        de.next_event().unwrap();
+
+        let (i, variant) = read_enum(&mut de, VARIANTS).unwrap();
+        assert_eq!((i, variant.name(), variant.kind()), (1, "Water", VariantKind::Struct));
+        let mut reader = variant.fields(&["depth"]).unwrap();
+        assert_eq!(reader.next_field(), Ok(Some("depth")));
+        assert_eq!(reader.read::<i32>(), Ok(3));
+        assert_eq!(reader.next_field(), Ok(None));
+
+        let (i, variant) = read_enum(&mut de, VARIANTS).unwrap();
+        assert_eq!((i, variant.kind()), (2, VariantKind::Tuple));
+        assert_eq!(variant.tuple::<(f32, i32)>(), Ok((2.0, 1)));
+
+        let (i, variant) = read_enum(&mut de, VARIANTS).unwrap();
+        assert_eq!((i, variant.kind()), (0, VariantKind::Unit));
+        assert_eq!(variant.unit(), Ok(()));
+        assert_eq!(de.next_event(), Ok(RonEvent::ListEnd));
+    }
+
+    #[test]
+    fn error_test() {
+        let err = read_enum(&mut RonDeserializer::new("Ice(1)"), VARIANTS).err().unwrap();
+        assert_eq!(err.message(), "Expected Fire, Water or Beam, found tuple Ice");
+        assert_eq!(err.span(), Some(Span::new(0, 4)));
+        let err = read_enum(&mut RonDeserializer::new("(depth: 3)"), &["Water"]).err().unwrap();
+        assert_eq!(err.message(), "Expected Water, found a struct");
+
+        let mut de = RonDeserializer::new("Fire(1)");
+        let err = read_enum(&mut de, VARIANTS).unwrap().1.unit().unwrap_err();
+        assert_eq!(err.message(), "Expected Fire without data, found tuple Fire");
+        let mut de = RonDeserializer::new("Beam");
+        let err = read_enum(&mut de, VARIANTS).unwrap().1.newtype::<f32>().unwrap_err();
+        assert_eq!(err.message(), "Expected Beam with unnamed fields, found identifier Beam");
+        let mut de = RonDeserializer::new("Water(3)");
+        let err = read_enum(&mut de, VARIANTS).unwrap().1.fields(&["depth"]).err().unwrap();
+        assert_eq!(err.message(), "Expected Water with named fields, found tuple Water");
+    }
+}
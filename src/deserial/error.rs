@@ -0,0 +1,307 @@
+use std::fmt;
+
+use super::lexer::TokenKind;
+
+/// A range of bytes of the source document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        return Self { start, end };
+    }
+
+    /// The smallest span covering both `self` and `other`.
+    pub fn to(self, other: Span) -> Self {
+        return Self { start: self.start.min(other.start), end: self.end.max(other.end) };
+    }
+}
+
+/// What went wrong, without the location.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// A token that can't appear at this point of the document. The error's expected set lists the ones that could.
+    UnexpectedToken,
+
+    /// The document ended before the current value was complete.
+    UnexpectedEof,
+
+    /// A value was needed, but the found token can't start one.
+    ExpectedValue,
+
+    /// A string literal without its closing quote.
+    UnterminatedString,
+
+    /// A backslash in a string that doesn't start a known escape sequence. Holds the sequence.
+    InvalidEscape(String),
+
+    /// A char literal that doesn't hold exactly one char, like `''` or `'ab'`, or that isn't closed. Holds its text.
+    InvalidChar(String),
+
+    /// A number that can't be read, like `1.2.3` or `300u8`. Holds its text.
+    InvalidNumber(String),
+
+    /// A char that can't start any token, like `@`.
+    UnexpectedChar(char),
+
+    /// A block comment without its closing `*/`.
+    UnterminatedComment,
+
+    /// Something other than whitespace follows the value of the document.
+    TrailingCharacters,
+
+    /// A well formed value of a different type than the one being decoded, like a string where a number was needed.
+    InvalidType { expected: String, found: String },
+
+    /// A value of the right type that can't be accepted, like an integer out of range.
+    InvalidValue(String),
+
+    /// A struct field that isn't one of the declared ones.
+    UnknownField(String),
+
+    /// A struct field that appears more than once.
+    DuplicateField(String),
+
+    /// Required struct fields that didn't appear, all listed at once.
+    MissingFields(Vec<String>),
+
+    /// A struct whose name isn't the expected one, or that has none when names are required. See
+    /// [`StructNames`](super::StructNames).
+    StructName { expected: String, found: Option<String> },
+
+    /// A map key that already appeared in the same map. The error points at the repeated key, and `first` at the
+    /// original one.
+    DuplicateMapKey { key: String, first: Span },
+
+    /// An identifier used as an enum variant that doesn't start with an uppercase letter, when the syntax options
+    /// don't accept those.
+    LowercaseEnum(String),
+
+    /// Any other error.
+    Message(String),
+}
+
+/// The source line an error points to, kept so the error can be displayed on its own.
+#[derive(Debug, Clone, PartialEq)]
+struct Snippet {
+    line: usize,
+    column: usize,
+    text: String,
+    width: usize,
+}
+
+/// An error found while deserializing a RON document.
+///
+/// When the error has a location, its `Display` output renders the offending line with the span underlined:
+/// ```text
+/// Expected ',' or ')', found ']' at line 2, column 15
+///   |
+/// 2 |     pos: (1, 2],
+///   |               ^
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct RonError {
+    // Boxed so results stay small on the happy path.
+    inner: Box<Inner>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Inner {
+    kind: ErrorKind,
+    found: Option<TokenKind>,
+    expected: Vec<TokenKind>,
+    span: Option<Span>,
+    snippet: Option<Snippet>,
+}
+
+impl RonError {
+    /// An error with a message and no location.
+    pub fn new(message: impl Into<String>) -> Self {
+        return Self::from_inner(Inner { kind: ErrorKind::Message(message.into()), found: None, expected: Vec::new(), span: None, snippet: None });
+    }
+
+    /// An error located at `span` of `src`.
+    pub fn at(kind: ErrorKind, span: Span, src: &str) -> Self {
+        return Self::from_inner(Inner { kind, found: None, expected: Vec::new(), span: Some(span), snippet: Snippet::new(src, span) });
+    }
+
+    fn from_inner(inner: Inner) -> Self {
+        return Self { inner: Box::new(inner) };
+    }
+
+    pub(crate) fn with_tokens(mut self, found: Option<TokenKind>, expected: &[TokenKind]) -> Self {
+        self.inner.found = found;
+        self.inner.expected = expected.to_vec();
+        return self;
+    }
+
+    /// Moves the error from a part of `src` that starts at `offset` to the whole of `src`, for errors found while
+    /// reading a slice of the document on its own.
+    pub(crate) fn offset(mut self, offset: usize, src: &str) -> Self {
+        if let ErrorKind::DuplicateMapKey { first, .. } = &mut self.inner.kind {
+            *first = Span::new(first.start + offset, first.end + offset);
+        }
+        if let Some(span) = self.inner.span {
+            let span = Span::new(span.start + offset, span.end + offset);
+            self.inner.span = Some(span);
+            self.inner.snippet = Snippet::new(src, span);
+        }
+        return self;
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        return &self.inner.kind;
+    }
+
+    /// The offending token, if the error was caused by one. `None` for errors at the end of the document.
+    pub fn found(&self) -> Option<TokenKind> {
+        return self.inner.found;
+    }
+
+    /// The tokens that would have been accepted instead of the found one.
+    pub fn expected(&self) -> &[TokenKind] {
+        return &self.inner.expected;
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        return self.inner.span;
+    }
+
+    /// The 1-based line and column (in chars) the error starts at.
+    pub fn line_col(&self) -> Option<(usize, usize)> {
+        return self.inner.snippet.as_ref().map(|x| (x.line, x.column));
+    }
+
+    /// A single line description of the error, without the location.
+    pub fn message(&self) -> String {
+        let found = match self.inner.found {
+            Some(x) => x.to_string(),
+            None => String::from("the end of the document"),
+        };
+
+        return match &self.inner.kind {
+            ErrorKind::UnexpectedToken | ErrorKind::UnexpectedEof if self.inner.expected.is_empty() => match self.inner.found {
+                Some(x) => format!("Unexpected {x}"),
+                None => String::from("Unexpected end of the document"),
+            },
+            ErrorKind::UnexpectedToken | ErrorKind::UnexpectedEof => format!("Expected {}, found {found}", list(&self.inner.expected)),
+            ErrorKind::ExpectedValue => format!("Expected a value, found {found}"),
+            ErrorKind::UnterminatedString => String::from("Unterminated string"),
+            ErrorKind::InvalidChar(x) => format!("Invalid char literal {x}"),
+            ErrorKind::InvalidNumber(x) => format!("Invalid number {x}"),
+            ErrorKind::UnexpectedChar(x) => format!("Unexpected character {x:?}"),
+            ErrorKind::UnterminatedComment => String::from("Unterminated block comment"),
+            ErrorKind::InvalidEscape(x) => format!("Invalid escape sequence {x}"),
+            ErrorKind::TrailingCharacters => match self.inner.found {
+                Some(x) => format!("Expected the end of the document, found {x}"),
+                None => String::from("Expected the end of the document"),
+            },
+            ErrorKind::InvalidType { expected, found } => format!("Expected {expected}, found {found}"),
+            ErrorKind::UnknownField(x) => format!("Unknown field {x}"),
+            ErrorKind::DuplicateField(x) => format!("Duplicate field {x}"),
+            ErrorKind::MissingFields(x) if x.len() == 1 => format!("Missing field {}", x[0]),
+            ErrorKind::MissingFields(x) => format!("Missing fields {}", x.join(", ")),
+            ErrorKind::StructName { expected, found: Some(found) } => format!("Expected struct {expected}, found struct {found}"),
+            ErrorKind::StructName { expected, found: None } => format!("Expected struct {expected}, found a struct without a name"),
+            ErrorKind::DuplicateMapKey { key, .. } => format!("Duplicate map key {key}"),
+            ErrorKind::LowercaseEnum(x) => format!("Expected an enum variant starting with an uppercase letter, found {x}"),
+            ErrorKind::InvalidValue(x) | ErrorKind::Message(x) => x.clone(),
+        };
+    }
+}
+
+/// Joins the expected tokens as "a", "a or b" or "a, b or c".
+fn list(tokens: &[TokenKind]) -> String {
+    let mut text = String::new();
+    for (i, x) in tokens.iter().enumerate() {
+        if i > 0 {
+            text.push_str(if i == tokens.len() - 1 { " or " } else { ", " });
+        }
+        text.push_str(&x.to_string());
+    }
+    return text;
+}
+
+impl Snippet {
+    fn new(src: &str, span: Span) -> Option<Self> {
+        let start = span.start.min(src.len());
+        if !src.is_char_boundary(start) {
+            return None;
+        }
+
+        let line_start = src[..start].rfind('\n').map_or(0, |x| x + 1);
+        let line_end = src[start..].find('\n').map_or(src.len(), |x| start + x);
+        let text = src[line_start..line_end].trim_end_matches('\r');
+
+        let column = src[line_start..start].chars().count() + 1;
+        let end = span.end.clamp(start, line_start + text.len());
+        let width = src.get(start..end).map_or(0, |x| x.chars().count()).max(1);
+
+        return Some(Self { line: src[..start].matches('\n').count() + 1, column, text: String::from(text), width });
+    }
+}
+
+impl fmt::Display for Snippet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        // Tabs are kept so the carets line up with the text however the terminal renders them.
+        let padding: String = self.text.chars().take(self.column - 1).map(|c| if c == '\t' { '\t' } else { ' ' }).collect();
+
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.text)?;
+        return write!(f, "{gutter} | {padding}{}", "^".repeat(self.width));
+    }
+}
+
+impl fmt::Display for RonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message())?;
+        if let Some(snippet) = &self.inner.snippet {
+            write!(f, " at line {}, column {}\n{snippet}", snippet.line, snippet.column)?;
+        }
+        return Ok(());
+    }
+}
+
+impl std::error::Error for RonError {}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn message_test() {
+        let err = RonError::at(ErrorKind::UnexpectedToken, Span::new(0, 1), "]").with_tokens(Some(TokenKind::RBracket), &[TokenKind::Comma, TokenKind::RParen]);
+        assert_eq!(err.message(), "Expected ',' or ')', found ']'");
+
+        let err = RonError::at(ErrorKind::UnexpectedEof, Span::new(1, 1), "[").with_tokens(None, &[TokenKind::Comma, TokenKind::RBracket, TokenKind::Ident]);
+        assert_eq!(err.message(), "Expected ',', ']' or identifier, found the end of the document");
+
+        assert_eq!(RonError::new("Custom").to_string(), "Custom");
+    }
+
+    #[test]
+    fn snippet_test() {
+        let src = "Player(\n    pos: (1, 2],\n)";
+        let err = RonError::at(ErrorKind::UnexpectedToken, Span::new(22, 23), src).with_tokens(Some(TokenKind::RBracket), &[TokenKind::Comma, TokenKind::RParen]);
+        assert_eq!(err.line_col(), Some((2, 15)));
+        assert_eq!(err.to_string(), "Expected ',' or ')', found ']' at line 2, column 15\n  |\n2 |     pos: (1, 2],\n  |               ^");
+    }
+
+    #[test]
+    fn snippet_width_test() {
+        let src = "\t\"ñandú\" 1";
+        let err = RonError::at(ErrorKind::Message(String::from("Bad")), Span::new(1, 10), src);
+        assert_eq!(err.to_string(), "Bad at line 1, column 2\n  |\n1 | \t\"ñandú\" 1\n  | \t^^^^^^^");
+    }
+
+    #[test]
+    fn eof_snippet_test() {
+        let err = RonError::at(ErrorKind::UnexpectedEof, Span::new(3, 3), "[1,");
+        assert_eq!(err.to_string(), "Unexpected end of the document at line 1, column 4\n  |\n1 | [1,\n  |    ^");
+    }
+}
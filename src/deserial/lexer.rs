@@ -1,147 +1,729 @@
-use std::str::CharIndices;
+//! Token level access to RON source, for tools like syntax highlighters, formatters and language servers.
+//!
+//! ```
+//! use light_ron::deserial::lexer::{Lexer, TokenKind};
+//!
+//! let kinds: Vec<_> = Lexer::new("Some(1)").map(|x| x.unwrap().0.kind()).collect();
+//! assert_eq!(kinds, [TokenKind::SomeOptValue, TokenKind::LParen, TokenKind::Int, TokenKind::RParen]);
+//! ```
 
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+
+use super::error::{ErrorKind, RonError, Span};
+
+/// Splits RON source into tokens. Whitespace is skipped and never produces tokens, and so are comments unless the
+/// lexer was built with [`Lexer::with_comments`].
+///
+/// After an error the lexer has already moved past the offending text, so it can keep being used.
+#[derive(Clone)]
 pub struct Lexer<'a> {
     src: &'a str,
-    iter: CharIndices<'a>,
-    trailing: Option<(usize, char)>,
+    bytes: &'a [u8],
+    pos: usize,
+    comments: bool,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(src: &'a str) -> Self {
-        return Self { src, iter: src.char_indices(), trailing: None };
-    }
-
-    pub fn next_token(&mut self) -> Option<Token> {
-        self.ignore_whitespaces()?;
-
-        let (char_byte, char) = self.next_char()?;
-        return Some(match char {
-            '(' => Token::LParen,
-            ')' => Token::RParen,
-            '[' => Token::LBracket,
-            ']' => Token::RBracket,
-            '{' => Token::LCurly,
-            '}' => Token::RCurly,
-            ':' => Token::Colon,
-            ',' => Token::Comma,
-            '"' => self.read_string()?,
-            '\'' => Token::Char(self.read_char()?),
-            '0'..='9' | '-' => match self.read_number(char_byte)? {
-                Number::Int(x) => Token::Int(x),
-                Number::Float(x) => Token::Float(x),
+        return Self { src, bytes: src.as_bytes(), pos: 0, comments: false };
+    }
+
+    /// A lexer that returns comments as `Token::Comment` instead of skipping them.
+    pub fn with_comments(src: &'a str) -> Self {
+        return Self { comments: true, ..Self::new(src) };
+    }
+
+    /// Returns the next token and the span of its text, or `None` at the end of the source.
+    pub fn next_token(&mut self) -> Result<Option<(Token, Span)>, RonError> {
+        self.skip_whitespaces()?;
+
+        let start = self.pos;
+        let Some(&byte) = self.bytes.get(start) else {
+            return Ok(None);
+        };
+        if self.comments && self.at_comment() {
+            self.skip_comment()?;
+            return Ok(Some((Token::Comment(start, self.pos), Span::new(start, self.pos))));
+        }
+
+        self.pos += 1;
+        let token = match byte {
+            b'(' => Token::LParen,
+            b')' => Token::RParen,
+            b'[' => Token::LBracket,
+            b']' => Token::RBracket,
+            b'{' => Token::LCurly,
+            b'}' => Token::RCurly,
+            b':' => Token::Colon,
+            b',' => Token::Comma,
+            b'"' => self.read_string()?,
+            b'\'' => self.read_char(start)?,
+            b'b' if self.bytes.get(start + 1) == Some(&b'"') => {
+                self.pos += 1;
+                self.read_byte_string()?
+            },
+            b'0'..=b'9' | b'-' => self.read_number(start)?,
+            b'r' if self.bytes.get(start + 1) == Some(&b'#') => {
+                self.pos += 1;
+                let ident = self.read_raw_ident();
+                if ident.0 == ident.1 {
+                    return Err(RonError::at(ErrorKind::Message(String::from("Expected an identifier after 'r#'")), Span::new(start, self.pos), self.src));
+                }
+                Token::Ident(ident.0, ident.1)
             },
             _ => {
-                let ident = self.read_ident(char_byte)?;
+                self.pos = start;
+                let first = self.peek_char().unwrap_or_default();
+                if first != '_' && !unicode_ident::is_xid_start(first) {
+                    self.pos += first.len_utf8();
+                    return Err(RonError::at(ErrorKind::UnexpectedChar(first), Span::new(start, self.pos), self.src));
+                }
+                let ident = self.read_ident(start);
                 match &self.src[ident.0..ident.1] {
                     "false" => Token::Bool(false),
                     "true" => Token::Bool(true),
                     "Some" => Token::SomeOptValue,
                     "None" => Token::NoneOptValue,
-                    _ => Token::Ident(ident.0, ident.1), 
+                    "inf" => Token::Float(f64::INFINITY),
+                    "NaN" => Token::Float(f64::NAN),
+                    _ => Token::Ident(ident.0, ident.1),
                 }
             },
-        });
+        };
+        return Ok(Some((token, Span::new(start, self.pos))));
     }
 
+    pub fn src(&self) -> &'a str {
+        return self.src;
+    }
+
+    /// Source text between two byte offsets, like the ones carried by `Token::Ident` and `Token::Str`. Offsets that are
+    /// out of range or inside a char give an empty string.
     pub fn get_string(&self, start: usize, end: usize) -> &'a str {
-        return &self.src[start..end];
+        return self.src.get(start..end).unwrap_or("");
     }
 
-    fn ignore_whitespaces(&mut self) -> Option<()> {
-        let mut val = self.next_char()?;
-        while val.1.is_whitespace() {
-            val = self.iter.next()?;
+    /// Text of a string between two byte offsets, like the ones carried by `Token::Str`, with its escapes decoded. Only
+    /// allocates if there are escapes. Backslashes that don't start an escape are kept.
+    pub fn get_str(&self, start: usize, end: usize) -> Cow<'a, str> {
+        let text = self.get_string(start, end);
+        if !text.contains('\\') {
+            return Cow::Borrowed(text);
         }
 
-        self.trailing = Some(val);
-        return Some(());
+        let mut string = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(i) = rest.find('\\') {
+            string.push_str(&rest[..i]);
+            let (c, len) = char_escape(&rest[i + 1..]).unwrap_or(('\\', 0));
+            string.push(c);
+            rest = &rest[i + 1 + len..];
+        }
+        string.push_str(rest);
+        return Cow::Owned(string);
     }
 
-    fn next_char(&mut self) -> Option<(usize, char)> {
-        if let Some(trailing) = self.trailing {
-            let val = Some(trailing);
-            self.trailing = None;
-            return val;
+    /// Bytes of a byte string between two byte offsets, like the ones carried by `Token::Bytes`, with its escapes
+    /// decoded. Only allocates if there are escapes. Offsets that are out of range give no bytes, and backslashes that
+    /// don't start an escape are kept.
+    pub fn get_bytes(&self, start: usize, end: usize) -> Cow<'a, [u8]> {
+        let text = self.bytes.get(start..end).unwrap_or_default();
+        if !text.contains(&b'\\') {
+            return Cow::Borrowed(text);
+        }
+
+        let mut bytes = Vec::with_capacity(text.len());
+        let mut i = 0;
+        while i < text.len() {
+            if text[i] != b'\\' {
+                bytes.push(text[i]);
+                i += 1;
+                continue;
+            }
+            let (byte, len) = byte_escape(&text[i + 1..]).unwrap_or((b'\\', 0));
+            bytes.push(byte);
+            i += 1 + len;
         }
+        return Cow::Owned(bytes);
+    }
 
-        return self.iter.next();
+    /// Source text covered by a span.
+    pub fn text(&self, span: Span) -> &'a str {
+        return self.get_string(span.start, span.end);
     }
 
-    fn read_string(&mut self) -> Option<Token> {
-        let start = self.next_char()?;
-        if start.1 == '"' {
-            return Some(Token::Str(0, 0));
+    /// Skips whitespace bytes directly, only decoding a char when a non-ASCII byte is found.
+    /// Also skips comments, unless they are being returned as tokens, and a byte order mark at the start of the source.
+    fn skip_whitespaces(&mut self) -> Result<(), RonError> {
+        while let Some(&byte) = self.bytes.get(self.pos) {
+            match byte {
+                b' ' | b'\t' | b'\n' | b'\r' | 0x0B | 0x0C => self.pos += 1,
+                b'/' if !self.comments && self.at_comment() => self.skip_comment()?,
+                0x80.. => match self.peek_char() {
+                    Some(c) if c.is_whitespace() || (c == '\u{feff}' && self.pos == 0) => self.pos += c.len_utf8(),
+                    _ => return Ok(()),
+                },
+                _ => return Ok(()),
+            }
         }
+        return Ok(());
+    }
 
-        let mut val = self.next_char()?;
-        while val.1 != '"' {
-            val = self.next_char()?;
-        };
+    fn at_comment(&self) -> bool {
+        return matches!(self.bytes.get(self.pos..self.pos + 2), Some(b"//" | b"/*"));
+    }
+
+    /// Skips a `//` comment up to the end of its line, or a `/* */` comment, which can be nested.
+    fn skip_comment(&mut self) -> Result<(), RonError> {
+        let start = self.pos;
+        if self.bytes[start + 1] == b'/' {
+            let rest = &self.bytes[start..];
+            self.pos += rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+            return Ok(());
+        }
+
+        self.pos += 2;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.bytes.get(self.pos..self.pos + 2) {
+                Some(b"/*") => { depth += 1; self.pos += 2; },
+                Some(b"*/") => { depth -= 1; self.pos += 2; },
+                Some(_) => self.pos += 1,
+                None => {
+                    self.pos = self.bytes.len();
+                    return Err(RonError::at(ErrorKind::UnterminatedComment, Span::new(start, self.pos), self.src));
+                },
+            }
+        }
+        return Ok(());
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        return self.src[self.pos..].chars().next();
+    }
+
+    /// Reads a string after its opening quote, checking its escapes.
+    fn read_string(&mut self) -> Result<Token, RonError> {
+        let start = self.pos;
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    let Some((_, len)) = self.src.get(self.pos + 1..).and_then(char_escape) else {
+                        return Err(self.invalid_escape(start));
+                    };
+                    self.pos += 1 + len;
+                },
+                Some(_) => self.pos += 1,
+                None => return Err(RonError::at(ErrorKind::UnterminatedString, Span::new(start - 1, self.pos), self.src)),
+            }
+        }
+        self.pos += 1;
+        return Ok(Token::Str(start, self.pos - 1));
+    }
 
-        return Some(Token::Str(start.0, val.0));
+    /// Reads a byte string after its `b"`, checking its escapes.
+    fn read_byte_string(&mut self) -> Result<Token, RonError> {
+        let start = self.pos;
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    let Some((_, len)) = byte_escape(&self.bytes[self.pos + 1..]) else {
+                        return Err(self.invalid_escape(start));
+                    };
+                    self.pos += 1 + len;
+                },
+                Some(_) => self.pos += 1,
+                None => return Err(RonError::at(ErrorKind::UnterminatedString, Span::new(start - 2, self.pos), self.src)),
+            }
+        }
+        self.pos += 1;
+        return Ok(Token::Bytes(start, self.pos - 1));
+    }
+
+    /// Builds the error of the backslash at the lexer's position, skipping the rest of the string that starts at
+    /// `start` so the lexer keeps going after it.
+    fn invalid_escape(&mut self, start: usize) -> RonError {
+        let len = self.src[self.pos + 1..].chars().next().map_or(0, char::len_utf8);
+        let span = Span::new(self.pos, self.pos + 1 + len);
+        self.pos = start;
+        self.skip_string();
+        return RonError::at(ErrorKind::InvalidEscape(String::from(&self.src[span.start..span.end])), span, self.src);
     }
 
-    fn read_char(&mut self) -> Option<char> {
-        let start = self.next_char()?;
-        if start.1 == '\'' {
-            panic!("Char was empty!");
+    /// Moves past the closing quote of the string the lexer is in, or to the end of the source.
+    fn skip_string(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.pos) {
+            self.pos += if byte == b'\\' { 2 } else { 1 };
+            if byte == b'"' {
+                return;
+            }
         }
+        self.pos = self.bytes.len();
+    }
 
-        let end = self.next_char()?;
-        if end.1 != '\'' {
-            panic!("More than one char inside char!") // TODO: Implement char escapign
+    /// Reads a char literal after its opening quote, which holds one char or one escape sequence of the ones strings
+    /// have. On errors the lexer moves past the closing quote if it's on the same line.
+    fn read_char(&mut self, start_byte: usize) -> Result<Token, RonError> {
+        let c = match self.bytes.get(self.pos) {
+            Some(b'\\') => match char_escape(&self.src[self.pos + 1..]) {
+                Some((c, len)) => {
+                    self.pos += 1 + len;
+                    Some(c)
+                },
+                None => {
+                    let len = self.src[self.pos + 1..].chars().next().filter(|&c| c != '\n').map_or(0, char::len_utf8);
+                    let span = Span::new(self.pos, self.pos + 1 + len);
+                    self.skip_char(start_byte);
+                    return Err(RonError::at(ErrorKind::InvalidEscape(String::from(self.text(span))), span, self.src));
+                },
+            },
+            _ => self.peek_char().filter(|&c| c != '\'' && c != '\n').inspect(|c| self.pos += c.len_utf8()),
+        };
+        if let Some(c) = c.filter(|_| self.bytes.get(self.pos) == Some(&b'\'')) {
+            self.pos += 1;
+            return Ok(Token::Char(c));
         }
 
-        return Some(start.1);
+        self.skip_char(start_byte);
+        let span = Span::new(start_byte, self.pos);
+        return Err(RonError::at(ErrorKind::InvalidChar(String::from(self.text(span))), span, self.src));
     }
 
-    fn read_number(&mut self, start_byte: usize) -> Option<Number> {
+    /// Moves past the closing quote of the char literal at `start_byte` if it's on the same line, skipping escaped
+    /// quotes, or past the char after the opening quote otherwise.
+    fn skip_char(&mut self, start_byte: usize) {
+        self.pos = start_byte + 1;
+        let mut end = self.pos;
+        while let Some(c) = self.src[end..].chars().next().filter(|&c| c != '\n') {
+            end += c.len_utf8();
+            if c == '\'' {
+                self.pos = end;
+                return;
+            }
+            if c == '\\' {
+                end += self.src[end..].chars().next().filter(|&c| c != '\n').map_or(0, char::len_utf8);
+            }
+        }
+        self.pos += self.peek_char().filter(|&c| c != '\n').map_or(0, char::len_utf8);
+    }
+
+    /// Reads a number starting at `start_byte`, with an optional type suffix like `42u8` or `1.5f32`. Suffixed
+    /// numbers are checked to fit in their type.
+    fn read_number(&mut self, start_byte: usize) -> Result<Token, RonError> {
         // TODO: Add support for 0x, 0b and 0o.
 
-        let mut last_byte = self.src.len();
-        while let Some(val) = self.next_char() {
-            if val.1.is_numeric() || val.1 == '.' {
-                continue;
+        let rest = &self.bytes[self.pos..];
+        self.pos += rest.iter().position(|&b| !(b.is_ascii_digit() || b == b'.')).unwrap_or(rest.len());
+        let number_end = self.pos;
+        self.skip_while(unicode_ident::is_xid_continue);
+
+        let text = &self.src[start_byte..self.pos];
+        let invalid = || RonError::at(ErrorKind::InvalidNumber(String::from(text)), Span::new(start_byte, self.pos), self.src);
+        let number = &self.src[start_byte..number_end];
+        if number == "-" && &self.src[number_end..self.pos] == "inf" {
+            return Ok(Token::Float(f64::NEG_INFINITY));
+        }
+        if number_end == self.pos {
+            if let Ok(x) = number.parse::<i128>() {
+                return Ok(Token::Int(x));
             }
-            self.trailing = Some(val);
-            last_byte = val.0;
-            break;
+            return number.parse::<f64>().map(Token::Float).map_err(|_| invalid());
         }
 
-        let str = &self.src[start_byte..last_byte];
-        if let Ok(x) = str.trim().parse::<i64>() {
-            return Some(Number::Int(x));
+        let suffix = NumberSuffix::parse(&self.src[number_end..self.pos]).ok_or_else(invalid)?;
+        if suffix.is_float() {
+            let x = number.parse::<f64>().map_err(|_| invalid())?;
+            if suffix == NumberSuffix::F32 && x.is_finite() && (x as f32).is_infinite() {
+                return Err(invalid());
+            }
+            return Ok(Token::TypedFloat(x, suffix));
         }
+        return match number.parse::<i128>() {
+            Ok(x) if suffix.fits(x) => Ok(Token::TypedInt(x, suffix)),
+            _ => Err(invalid()),
+        };
+    }
 
-        if let Ok(x) = str.trim().parse::<f64>() {
-            return Some(Number::Float(x));
+    /// Reads an identifier starting at `start_byte`, whose first char was already checked.
+    fn read_ident(&mut self, start_byte: usize) -> (usize, usize) {
+        if let Some(first) = self.peek_char() {
+            self.pos += first.len_utf8();
         }
+        self.skip_while(unicode_ident::is_xid_continue);
+        return (start_byte, self.pos);
+    }
 
-        panic!("Invalid number (got \"{str}\")!");
+    /// Reads the rest of a raw identifier after its `r#`, which can also hold `.`, `+` and `-`.
+    fn read_raw_ident(&mut self) -> (usize, usize) {
+        let start = self.pos;
+        self.skip_while(is_raw_ident_char);
+        return (start, self.pos);
     }
 
-    fn read_ident(&mut self, start_byte: usize) -> Option<(usize, usize)> {
-        let mut last_byte = self.src.len();
-        while let Some(val) = self.next_char() {
-            if val.1.is_alphanumeric() || val.1 == '_' {
+    /// Skips chars while `f` accepts them, only decoding the non-ASCII ones.
+    fn skip_while(&mut self, f: fn(char) -> bool) {
+        while let Some(&byte) = self.bytes.get(self.pos) {
+            if byte.is_ascii() {
+                if !f(byte as char) {
+                    break;
+                }
+                self.pos += 1;
                 continue;
             }
-            self.trailing = Some(val);
-            last_byte = val.0;
-            break;
+
+            match self.peek_char() {
+                Some(c) if f(c) => self.pos += c.len_utf8(),
+                _ => break,
+            }
         }
-        return Some((start_byte, last_byte));
     }
 }
 
+/// Finds the `)` that closes a `Some` whose value ends at `end`, since it has no event. Returns the offset right after it.
+pub(crate) fn find_some_close(src: &str, end: usize) -> Result<usize, RonError> {
+    let mut lexer = Lexer::new(&src[end..]);
+    return match lexer.next_token() {
+        Ok(Some((Token::RParen, close))) => Ok(end + close.end),
+        Ok(Some((tok, span))) => {
+            let span = Span::new(end + span.start, end + span.end);
+            Err(RonError::at(ErrorKind::UnexpectedToken, span, src).with_tokens(Some(tok.kind()), &[TokenKind::RParen]))
+        },
+        Ok(None) => Err(RonError::at(ErrorKind::UnexpectedEof, Span::new(src.len(), src.len()), src).with_tokens(None, &[TokenKind::RParen])),
+        Err(err) => Err(err.offset(end, src)),
+    };
+}
+
+fn is_raw_ident_char(c: char) -> bool {
+    return unicode_ident::is_xid_continue(c) || matches!(c, '.' | '+' | '-');
+}
+
+/// Whether `text` can be written as a plain identifier, without `r#`. Keywords like `true`, `None` and `inf` can't.
+pub fn is_plain_ident(text: &str) -> bool {
+    let mut chars = text.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    return (first == '_' || unicode_ident::is_xid_start(first))
+        && chars.all(unicode_ident::is_xid_continue)
+        && !matches!(text, "true" | "false" | "Some" | "None" | "inf" | "NaN");
+}
+
+/// Whether `text` can be written as an identifier at all, with `r#` if it isn't a plain one.
+pub fn is_raw_ident(text: &str) -> bool {
+    return !text.is_empty() && text.chars().all(is_raw_ident_char);
+}
+
+/// A token with its value. `Ident`, `Str`, `Bytes` and `Comment` hold the byte offsets of their text. Strings and byte
+/// strings don't include their quotes, but comments include their delimiters.
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
 pub enum Token {
     LParen, RParen, LBracket, RBracket, LCurly, RCurly, Colon, Comma, 
-    Ident(usize, usize), Bool(bool), Float(f64), Int(i64), Char(char), Str(usize, usize), SomeOptValue, NoneOptValue,
+    Ident(usize, usize), Bool(bool), Float(f64), Int(i128), Char(char), Str(usize, usize), SomeOptValue, NoneOptValue,
+    Comment(usize, usize), Bytes(usize, usize),
+    /// A number with a type suffix, like `42u8` or `1f32`. Integers always fit in their type.
+    TypedInt(i128, NumberSuffix), TypedFloat(f64, NumberSuffix),
 }
 
-enum Number {
-    Int(i64), Float(f64),
+impl Token {
+    pub fn kind(&self) -> TokenKind {
+        return match self {
+            Token::LParen => TokenKind::LParen,
+            Token::RParen => TokenKind::RParen,
+            Token::LBracket => TokenKind::LBracket,
+            Token::RBracket => TokenKind::RBracket,
+            Token::LCurly => TokenKind::LCurly,
+            Token::RCurly => TokenKind::RCurly,
+            Token::Colon => TokenKind::Colon,
+            Token::Comma => TokenKind::Comma,
+            Token::Ident(_, _) => TokenKind::Ident,
+            Token::Bool(_) => TokenKind::Bool,
+            Token::Float(_) | Token::TypedFloat(..) => TokenKind::Float,
+            Token::Int(_) | Token::TypedInt(..) => TokenKind::Int,
+            Token::Char(_) => TokenKind::Char,
+            Token::Str(_, _) => TokenKind::Str,
+            Token::SomeOptValue => TokenKind::SomeOptValue,
+            Token::NoneOptValue => TokenKind::NoneOptValue,
+            Token::Comment(_, _) => TokenKind::Comment,
+            Token::Bytes(_, _) => TokenKind::Bytes,
+        };
+    }
+}
+
+/// The kind of a token, without its value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TokenKind {
+    LParen, RParen, LBracket, RBracket, LCurly, RCurly, Colon, Comma, 
+    Ident, Bool, Float, Int, Char, Str, SomeOptValue, NoneOptValue, Comment, Bytes,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(match self {
+            TokenKind::LParen => "'('",
+            TokenKind::RParen => "')'",
+            TokenKind::LBracket => "'['",
+            TokenKind::RBracket => "']'",
+            TokenKind::LCurly => "'{'",
+            TokenKind::RCurly => "'}'",
+            TokenKind::Colon => "':'",
+            TokenKind::Comma => "','",
+            TokenKind::Ident => "identifier",
+            TokenKind::Bool => "bool",
+            TokenKind::Float => "float",
+            TokenKind::Int => "integer",
+            TokenKind::Char => "char",
+            TokenKind::Str => "string",
+            TokenKind::SomeOptValue => "'Some'",
+            TokenKind::NoneOptValue => "'None'",
+            TokenKind::Comment => "comment",
+            TokenKind::Bytes => "byte string",
+        });
+    }
+}
+
+impl TokenKind {
+    /// Whether the token is one of `(`, `)`, `[`, `]`, `{` or `}`.
+    pub fn is_delimiter(&self) -> bool {
+        return matches!(self, TokenKind::LParen | TokenKind::RParen | TokenKind::LBracket | TokenKind::RBracket | TokenKind::LCurly | TokenKind::RCurly);
+    }
+
+    /// Whether the token is a literal value, including `Some` and `None`.
+    pub fn is_literal(&self) -> bool {
+        return matches!(self, TokenKind::Bool | TokenKind::Float | TokenKind::Int | TokenKind::Char | TokenKind::Str | TokenKind::Bytes | TokenKind::SomeOptValue | TokenKind::NoneOptValue);
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<(Token, Span), RonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        return self.next_token().transpose();
+    }
+}
+
+/// A change to the source of a [`TokenCache`]: the text in `span` is replaced by `text`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub span: Span,
+    pub text: String,
+}
+
+/// Which tokens of a [`TokenCache`] an edit replaced. Both ranges start at the same index, `old` is the one the
+/// replaced tokens had and `new` the one of the tokens that took their place. Tokens after them were only moved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relexed {
+    pub old: Range<usize>,
+    pub new: Range<usize>,
+}
+
+/// A token or a lexing error of a [`TokenCache`], with its span.
+pub type CachedToken = (Result<Token, ErrorKind>, Span);
+
+/// The tokens of a source that changes over time, like a file open in an editor. After each edit only the tokens
+/// around it are lexed again, until the new ones line up with the old ones.
+///
+/// Errors are kept in the list with the span they were found at, so highlighting can mark them. Edits after an error
+/// lex everything from that error on again, since what it is can depend on any text after it.
+/// ```
+/// # use light_ron::deserial::Span;
+/// # use light_ron::deserial::lexer::{TextEdit, Token, TokenCache};
+/// let mut cache = TokenCache::new("(hp: 10, mp: 5)");
+/// let relexed = cache.relex_range(TextEdit { span: Span::new(5, 7), text: String::from("200") });
+/// assert_eq!(cache.src(), "(hp: 200, mp: 5)");
+/// assert_eq!(cache.tokens()[relexed.new.end - 1], (Ok(Token::Int(200)), Span::new(5, 8)));
+/// assert_eq!(relexed.new.len(), relexed.old.len());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TokenCache {
+    src: String,
+    comments: bool,
+    tokens: Vec<CachedToken>,
+}
+
+impl TokenCache {
+    pub fn new(src: impl Into<String>) -> Self {
+        return Self::build(src.into(), false);
+    }
+
+    /// A cache that keeps comments as `Token::Comment`, like [`Lexer::with_comments`].
+    pub fn with_comments(src: impl Into<String>) -> Self {
+        return Self::build(src.into(), true);
+    }
+
+    fn build(src: String, comments: bool) -> Self {
+        let mut cache = Self { src, comments, tokens: Vec::new() };
+        cache.tokens = cache.lex_from(0, usize::MAX, 0, 0).0;
+        return cache;
+    }
+
+    pub fn src(&self) -> &str {
+        return &self.src;
+    }
+
+    /// The tokens and errors of the source in order, with their spans.
+    pub fn tokens(&self) -> &[CachedToken] {
+        return &self.tokens;
+    }
+
+    /// Applies `edit` to the source and lexes the tokens it touched again.
+    ///
+    /// # Panics
+    /// If the span of the edit is out of the source or doesn't fall on char boundaries.
+    pub fn relex_range(&mut self, edit: TextEdit) -> Relexed {
+        let TextEdit { span, text } = edit;
+        self.src.replace_range(span.start..span.end, &text);
+        let delta = text.len() as isize - (span.end - span.start) as isize;
+        let edit_end = span.start + text.len();
+
+        // Lexing a token looks at the char after it, so tokens ending right before the edit are lexed again too. An error
+        // can come from looking anywhere ahead, like an unclosed char, so nothing after the first one is kept.
+        let valid = self.tokens.iter().position(|x| x.0.is_err()).unwrap_or(self.tokens.len());
+        let kept = self.tokens[..valid].iter().rposition(|x| x.1.end < span.start);
+        let (first, restart) = kept.map_or((0, 0), |i| (i + 1, self.tokens[i].1.end));
+
+        // Tokens are matched by where they start, which for the ones after the edit moves by `delta`.
+        let old_after = first + self.tokens[first..].iter().position(|x| x.1.start >= span.end).unwrap_or(self.tokens.len() - first);
+        let (new_tokens, resync) = self.lex_from(restart, edit_end, old_after, delta);
+
+        let end = resync.unwrap_or(self.tokens.len());
+        let inserted = new_tokens.len();
+        self.tokens.splice(first..end, new_tokens);
+        for x in &mut self.tokens[first + inserted..] {
+            *x = shifted(x.clone(), delta);
+        }
+        return Relexed { old: first..end, new: first..first + inserted };
+    }
+
+    /// Lexes from `pos` until the end, or until a token starting at or after `resync_at` starts where one of the old
+    /// tokens from `old_from` on starts once moved by `delta`. Only tokens are compared, since the spans of errors
+    /// aren't where lexing restarts. From there on the old tokens are still right, since lexing only looks forward.
+    /// Returns the new tokens and the index of that old token.
+    fn lex_from(&self, pos: usize, resync_at: usize, old_from: usize, delta: isize) -> (Vec<CachedToken>, Option<usize>) {
+        let mut lexer = if self.comments { Lexer::with_comments(&self.src) } else { Lexer::new(&self.src) };
+        lexer.pos = pos;
+
+        let mut tokens = Vec::new();
+        let mut old = old_from;
+        loop {
+            let (token, span) = match lexer.next_token() {
+                Ok(Some((token, span))) => (Ok(token), span),
+                Ok(None) => return (tokens, None),
+                Err(err) => (Err(err.kind().clone()), err.span().unwrap_or(Span::new(lexer.pos, lexer.pos))),
+            };
+            if token.is_ok() && span.start >= resync_at {
+                while old < self.tokens.len() && (self.tokens[old].1.start as isize + delta) < span.start as isize {
+                    old += 1;
+                }
+                let mut same = self.tokens[old..].iter().take_while(|x| x.1.start as isize + delta == span.start as isize);
+                if let Some(i) = same.position(|x| x.0.is_ok()) {
+                    return (tokens, Some(old + i));
+                }
+            }
+            tokens.push((token, span));
+        }
+    }
+}
+
+/// Moves a cached token and its span by `delta` bytes.
+fn shifted((token, span): CachedToken, delta: isize) -> CachedToken {
+    let by = |x: usize| x.wrapping_add_signed(delta);
+    let token = match token {
+        Ok(Token::Ident(a, b)) => Ok(Token::Ident(by(a), by(b))),
+        Ok(Token::Str(a, b)) => Ok(Token::Str(by(a), by(b))),
+        Ok(Token::Comment(a, b)) => Ok(Token::Comment(by(a), by(b))),
+        Ok(Token::Bytes(a, b)) => Ok(Token::Bytes(by(a), by(b))),
+        x => x,
+    };
+    return (token, Span::new(by(span.start), by(span.end)));
+}
+
+/// Decodes the escape sequence that follows a `\` in a byte string, returning the byte and the length of the sequence.
+fn byte_escape(rest: &[u8]) -> Option<(u8, usize)> {
+    return match rest.first()? {
+        b'\\' => Some((b'\\', 1)),
+        b'"' => Some((b'"', 1)),
+        b'\'' => Some((b'\'', 1)),
+        b'n' => Some((b'\n', 1)),
+        b'r' => Some((b'\r', 1)),
+        b't' => Some((b'\t', 1)),
+        b'0' => Some((0, 1)),
+        b'x' => {
+            let hex = std::str::from_utf8(rest.get(1..3)?).ok()?;
+            Some((u8::from_str_radix(hex, 16).ok()?, 3))
+        },
+        _ => None,
+    };
+}
+
+/// Decodes the escape sequence at the start of `rest`, which follows a backslash in a string. Returns the char and
+/// how many bytes the sequence took. Besides the escapes of byte strings, there are unicode escapes like `\u{e9}`,
+/// and `\x` only goes up to `\x7f`.
+fn char_escape(rest: &str) -> Option<(char, usize)> {
+    if let Some(hex) = rest.strip_prefix("u{") {
+        let len = hex.find('}')?;
+        if len == 0 || len > 6 || !hex[..len].bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let code = u32::from_str_radix(&hex[..len], 16).ok()?;
+        return Some((char::from_u32(code)?, len + 3));
+    }
+    return byte_escape(rest.as_bytes()).filter(|x| x.0 < 0x80).map(|(byte, len)| (byte as char, len));
+}
+
+/// The Rust style type suffix of a number, like the `u8` of `42u8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NumberSuffix {
+    I8, I16, I32, I64, I128, Isize, U8, U16, U32, U64, U128, Usize, F32, F64,
+}
+
+macro_rules! suffixes {
+    (ints: $($int:ident: $int_ty:ty),*; floats: $($float:ident: $float_ty:ty),*) => {
+        impl NumberSuffix {
+            pub fn parse(text: &str) -> Option<Self> {
+                return match text {
+                    $(stringify!($int_ty) => Some(NumberSuffix::$int),)*
+                    $(stringify!($float_ty) => Some(NumberSuffix::$float),)*
+                    _ => None,
+                };
+            }
+
+            pub fn as_str(self) -> &'static str {
+                return match self {
+                    $(NumberSuffix::$int => stringify!($int_ty),)*
+                    $(NumberSuffix::$float => stringify!($float_ty),)*
+                };
+            }
+
+            /// Whether `x` is in the range of the suffix's type. Always true for floats.
+            pub fn fits(self, x: i128) -> bool {
+                return match self {
+                    $(NumberSuffix::$int => <$int_ty>::try_from(x).is_ok(),)*
+                    $(NumberSuffix::$float => true,)*
+                };
+            }
+
+            pub fn is_float(self) -> bool {
+                return matches!(self, $(NumberSuffix::$float)|*);
+            }
+        }
+    };
+}
+
+suffixes!(
+    ints: I8: i8, I16: i16, I32: i32, I64: i64, I128: i128, Isize: isize, U8: u8, U16: u16, U32: u32, U64: u64, U128: u128, Usize: usize;
+    floats: F32: f32, F64: f64
+);
+
+impl fmt::Display for NumberSuffix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(self.as_str());
+    }
 }
 
 
@@ -149,35 +731,412 @@ enum Number {
 mod tests {
     use super::*;
 
+    fn next(lexer: &mut Lexer) -> Option<Token> {
+        return lexer.next_token().unwrap().map(|x| x.0);
+    }
+
     #[test]
     fn test_next_token() {
         let src = r#" ( ) [ ] { } : , true Test false Some None 123.456 "text" 69420 -69420 'a'"#;
         let mut lexer = Lexer::new(src);
-        assert_eq!(lexer.next_token(), Some(Token::LParen));
-        assert_eq!(lexer.next_token(), Some(Token::RParen));
-        assert_eq!(lexer.next_token(), Some(Token::LBracket));
-        assert_eq!(lexer.next_token(), Some(Token::RBracket));
-        assert_eq!(lexer.next_token(), Some(Token::LCurly));
-        assert_eq!(lexer.next_token(), Some(Token::RCurly));
-        assert_eq!(lexer.next_token(), Some(Token::Colon));
-        assert_eq!(lexer.next_token(), Some(Token::Comma));
-        assert_eq!(lexer.next_token(), Some(Token::Bool(true)));
-        assert_eq!(lexer.next_token(), Some(Token::Ident(22, 26)));
-        assert_eq!(lexer.next_token(), Some(Token::Bool(false)));
-        assert_eq!(lexer.next_token(), Some(Token::SomeOptValue));
-        assert_eq!(lexer.next_token(), Some(Token::NoneOptValue));
-        assert_eq!(lexer.next_token(), Some(Token::Float(123.456)));
-        assert_eq!(lexer.next_token(), Some(Token::Str(52, 56)));
-        assert_eq!(lexer.next_token(), Some(Token::Int(69420)));
-        assert_eq!(lexer.next_token(), Some(Token::Int(-69420)));
-        assert_eq!(lexer.next_token(), Some(Token::Char('a')));
-        assert_eq!(lexer.next_token(), None);
+        assert_eq!(next(&mut lexer), Some(Token::LParen));
+        assert_eq!(next(&mut lexer), Some(Token::RParen));
+        assert_eq!(next(&mut lexer), Some(Token::LBracket));
+        assert_eq!(next(&mut lexer), Some(Token::RBracket));
+        assert_eq!(next(&mut lexer), Some(Token::LCurly));
+        assert_eq!(next(&mut lexer), Some(Token::RCurly));
+        assert_eq!(next(&mut lexer), Some(Token::Colon));
+        assert_eq!(next(&mut lexer), Some(Token::Comma));
+        assert_eq!(next(&mut lexer), Some(Token::Bool(true)));
+        assert_eq!(next(&mut lexer), Some(Token::Ident(22, 26)));
+        assert_eq!(next(&mut lexer), Some(Token::Bool(false)));
+        assert_eq!(next(&mut lexer), Some(Token::SomeOptValue));
+        assert_eq!(next(&mut lexer), Some(Token::NoneOptValue));
+        assert_eq!(next(&mut lexer), Some(Token::Float(123.456)));
+        assert_eq!(next(&mut lexer), Some(Token::Str(52, 56)));
+        assert_eq!(next(&mut lexer), Some(Token::Int(69420)));
+        assert_eq!(next(&mut lexer), Some(Token::Int(-69420)));
+        assert_eq!(next(&mut lexer), Some(Token::Char('a')));
+        assert_eq!(next(&mut lexer), None);
     }
 
     #[test]
     fn none() {
         let mut lexer = Lexer::new("None");
-        assert_eq!(lexer.next_token(), Some(Token::NoneOptValue));
-        assert_eq!(lexer.next_token(), None);
+        assert_eq!(next(&mut lexer), Some(Token::NoneOptValue));
+        assert_eq!(next(&mut lexer), None);
+    }
+
+    #[test]
+    fn non_ascii() {
+        let src = "\u{3000}Größe\u{2003}\"ñandú\" 'é'";
+        let mut lexer = Lexer::new(src);
+        assert_eq!(next(&mut lexer), Some(Token::Ident(3, 10)));
+        assert_eq!(lexer.get_string(3, 10), "Größe");
+        assert_eq!(next(&mut lexer), Some(Token::Str(14, 21)));
+        assert_eq!(lexer.get_string(14, 21), "ñandú");
+        assert_eq!(next(&mut lexer), Some(Token::Char('é')));
+        assert_eq!(next(&mut lexer), None);
+    }
+
+    #[test]
+    fn blank_sources() {
+        for src in ["", " \t\r\n\u{0B}\u{0C}", "\u{3000}\u{2003}", "// a", "/* a /* b */ */\n// c\n", "\u{feff}", "\u{feff} // a"] {
+            assert_eq!(Lexer::new(src).next_token(), Ok(None), "{src:?}");
+        }
+        let mut lexer = Lexer::with_comments("\u{feff}// a\n/* b */");
+        assert_eq!(lexer.next_token(), Ok(Some((Token::Comment(3, 7), Span::new(3, 7)))));
+        assert_eq!(lexer.next_token(), Ok(Some((Token::Comment(8, 15), Span::new(8, 15)))));
+        assert_eq!(lexer.next_token(), Ok(None));
+    }
+
+    #[test]
+    fn byte_order_mark() {
+        let mut lexer = Lexer::new("\u{feff}[1]");
+        assert_eq!(lexer.next_token(), Ok(Some((Token::LBracket, Span::new(3, 4)))));
+        assert_eq!(next(&mut lexer), Some(Token::Int(1)));
+        assert_eq!(next(&mut lexer), Some(Token::RBracket));
+        assert_eq!(next(&mut lexer), None);
+
+        // Only the first char of the source can be one.
+        let mut lexer = Lexer::new("[\u{feff}]");
+        assert_eq!(next(&mut lexer), Some(Token::LBracket));
+        assert_eq!(lexer.next_token().unwrap_err().kind(), &ErrorKind::UnexpectedChar('\u{feff}'));
+        assert_eq!(Lexer::new("\u{feff}\u{feff}").next_token().unwrap_err().span(), Some(Span::new(3, 6)));
+    }
+
+    #[test]
+    fn empty_string() {
+        let mut lexer = Lexer::new(r#""""#);
+        assert_eq!(next(&mut lexer), Some(Token::Str(1, 1)));
+        assert_eq!(next(&mut lexer), None);
+    }
+
+    #[test]
+    fn spans() {
+        let mut lexer = Lexer::new(" Some(-12, \"ab\")");
+        assert_eq!(lexer.next_token().unwrap(), Some((Token::SomeOptValue, Span::new(1, 5))));
+        assert_eq!(lexer.next_token().unwrap(), Some((Token::LParen, Span::new(5, 6))));
+        assert_eq!(lexer.next_token().unwrap(), Some((Token::Int(-12), Span::new(6, 9))));
+        assert_eq!(lexer.next_token().unwrap(), Some((Token::Comma, Span::new(9, 10))));
+        assert_eq!(lexer.next_token().unwrap(), Some((Token::Str(12, 14), Span::new(11, 15))));
+        assert_eq!(lexer.next_token().unwrap(), Some((Token::RParen, Span::new(15, 16))));
+        assert_eq!(lexer.next_token().unwrap(), None);
+    }
+
+    #[test]
+    fn unterminated_string() {
+        let mut lexer = Lexer::new("[\"abc");
+        assert_eq!(next(&mut lexer), Some(Token::LBracket));
+        let err = lexer.next_token().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::UnterminatedString);
+        assert_eq!(err.span(), Some(Span::new(1, 5)));
+    }
+
+    #[test]
+    fn iterator() {
+        let src = "[\"a, 1]";
+        let tokens: Vec<_> = Lexer::new(src).collect();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0], Ok((Token::LBracket, Span::new(0, 1))));
+        assert!(tokens[1].is_err());
+
+        let lexer = Lexer::new("(first: 1)");
+        let texts: Vec<_> = Lexer::new("(first: 1)").map(|x| lexer.text(x.unwrap().1)).collect();
+        assert_eq!(texts, ["(", "first", ":", "1", ")"]);
+    }
+
+    #[test]
+    fn kinds() {
+        assert!(TokenKind::LCurly.is_delimiter());
+        assert!(!TokenKind::Comma.is_delimiter());
+        assert!(TokenKind::NoneOptValue.is_literal());
+        assert!(!TokenKind::Ident.is_literal());
+    }
+
+    #[test]
+    fn comments() {
+        let src = "// line\n[1, /* block /* nested */ */ 2] // end";
+        let mut lexer = Lexer::new(src);
+        assert_eq!(next(&mut lexer), Some(Token::LBracket));
+        assert_eq!(next(&mut lexer), Some(Token::Int(1)));
+        assert_eq!(next(&mut lexer), Some(Token::Comma));
+        assert_eq!(next(&mut lexer), Some(Token::Int(2)));
+        assert_eq!(next(&mut lexer), Some(Token::RBracket));
+        assert_eq!(next(&mut lexer), None);
+
+        let mut lexer = Lexer::with_comments(src);
+        assert_eq!(next(&mut lexer), Some(Token::Comment(0, 7)));
+        assert_eq!(next(&mut lexer), Some(Token::LBracket));
+        assert_eq!(next(&mut lexer), Some(Token::Int(1)));
+        assert_eq!(next(&mut lexer), Some(Token::Comma));
+        assert_eq!(lexer.next_token().unwrap(), Some((Token::Comment(12, 36), Span::new(12, 36))));
+        assert_eq!(lexer.get_string(12, 36), "/* block /* nested */ */");
+        assert_eq!(next(&mut lexer), Some(Token::Int(2)));
+        assert_eq!(next(&mut lexer), Some(Token::RBracket));
+        assert_eq!(next(&mut lexer), Some(Token::Comment(40, 46)));
+        assert_eq!(next(&mut lexer), None);
+    }
+
+    #[test]
+    fn unterminated_comment() {
+        let mut lexer = Lexer::new("1 /* /* */");
+        assert_eq!(next(&mut lexer), Some(Token::Int(1)));
+        assert_eq!(lexer.next_token().unwrap_err().kind(), &ErrorKind::UnterminatedComment);
+        assert_eq!(next(&mut lexer), None);
+    }
+
+    #[test]
+    fn string_escapes() {
+        let src = r#"["a\"b", "\\\n\u{e9}\x41\u{1F980}", "plain", "\x80", "\u{}", "\u{110000}", "\u{e9"]"#;
+        let mut lexer = Lexer::new(src);
+        assert_eq!(next(&mut lexer), Some(Token::LBracket));
+        assert_eq!(next(&mut lexer), Some(Token::Str(2, 6)));
+        assert_eq!(lexer.get_str(2, 6), "a\"b");
+        assert_eq!(next(&mut lexer), Some(Token::Comma));
+        assert_eq!(next(&mut lexer), Some(Token::Str(10, 33)));
+        assert_eq!(lexer.get_str(10, 33), "\\\néA🦀");
+        assert_eq!(next(&mut lexer), Some(Token::Comma));
+        assert_eq!(next(&mut lexer), Some(Token::Str(37, 42)));
+        assert!(matches!(lexer.get_str(37, 42), Cow::Borrowed("plain")));
+        for escape in ["\\x", "\\u", "\\u", "\\u"] {
+            assert_eq!(next(&mut lexer), Some(Token::Comma));
+            assert_eq!(lexer.next_token().unwrap_err().kind(), &ErrorKind::InvalidEscape(String::from(escape)));
+        }
+        assert_eq!(next(&mut lexer), Some(Token::RBracket));
+        assert_eq!(Lexer::new("a\\q").get_str(0, 3), "a\\q");
+    }
+
+    #[test]
+    fn byte_strings() {
+        let src = r#"[b"abc", b"\x00\xfF\"\\\n", b, b""]"#;
+        let mut lexer = Lexer::new(src);
+        assert_eq!(next(&mut lexer), Some(Token::LBracket));
+        assert_eq!(lexer.next_token().unwrap(), Some((Token::Bytes(3, 6), Span::new(1, 7))));
+        assert_eq!(lexer.get_bytes(3, 6), Cow::Borrowed(b"abc"));
+        assert_eq!(next(&mut lexer), Some(Token::Comma));
+        assert_eq!(next(&mut lexer), Some(Token::Bytes(11, 25)));
+        assert!(matches!(lexer.get_bytes(11, 25), Cow::Owned(x) if x == b"\x00\xff\"\\\n"));
+        assert_eq!(next(&mut lexer), Some(Token::Comma));
+        assert_eq!(next(&mut lexer), Some(Token::Ident(28, 29)));
+        assert_eq!(next(&mut lexer), Some(Token::Comma));
+        assert_eq!(next(&mut lexer), Some(Token::Bytes(33, 33)));
+        assert_eq!(next(&mut lexer), Some(Token::RBracket));
+    }
+
+    #[test]
+    fn invalid_byte_strings() {
+        let mut lexer = Lexer::new(r#"b"a\q\"b" 1 b"\x4" 2 b"abc"#);
+        let err = lexer.next_token().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidEscape(String::from("\\q")));
+        assert_eq!(err.span(), Some(Span::new(3, 5)));
+        assert_eq!(next(&mut lexer), Some(Token::Int(1)));
+        assert_eq!(lexer.next_token().unwrap_err().kind(), &ErrorKind::InvalidEscape(String::from("\\x")));
+        assert_eq!(next(&mut lexer), Some(Token::Int(2)));
+        let err = lexer.next_token().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::UnterminatedString);
+        assert_eq!(err.span(), Some(Span::new(21, 26)));
+        assert_eq!(next(&mut lexer), None);
+    }
+
+    #[test]
+    fn identifiers() {
+        let mut lexer = Lexer::new("_a ñandú r#true r#foo-bar.1 a٣");
+        assert_eq!(next(&mut lexer), Some(Token::Ident(0, 2)));
+        assert_eq!(next(&mut lexer), Some(Token::Ident(3, 10)));
+        assert_eq!(lexer.next_token().unwrap(), Some((Token::Ident(13, 17), Span::new(11, 17))));
+        assert_eq!(next(&mut lexer), Some(Token::Ident(20, 29)));
+        assert_eq!(next(&mut lexer), Some(Token::Ident(30, 33)));
+        assert_eq!(next(&mut lexer), None);
+
+        assert!(is_plain_ident("_") && is_plain_ident("ñ1"));
+        assert!(!is_plain_ident("1a") && !is_plain_ident("a-b") && !is_plain_ident("None") && !is_plain_ident(""));
+        assert!(is_raw_ident("a-b.c") && !is_raw_ident("a b") && !is_raw_ident(""));
+    }
+
+    #[test]
+    fn invalid_identifiers() {
+        let mut lexer = Lexer::new("@ 1 ٣x 2 r# 3");
+        let err = lexer.next_token().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::UnexpectedChar('@'));
+        assert_eq!(err.message(), "Unexpected character '@'");
+        assert_eq!(next(&mut lexer), Some(Token::Int(1)));
+        assert_eq!(lexer.next_token().unwrap_err().kind(), &ErrorKind::UnexpectedChar('٣'));
+        assert_eq!(next(&mut lexer), Some(Token::Ident(6, 7)));
+        assert_eq!(next(&mut lexer), Some(Token::Int(2)));
+        assert_eq!(lexer.next_token().unwrap_err().span(), Some(Span::new(10, 12)));
+        assert_eq!(next(&mut lexer), Some(Token::Int(3)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn number_suffixes() {
+        let mut lexer = Lexer::new("42u8 -1i64 1.5f32 2f64 300u8 1.5u8 1abc 3");
+        assert_eq!(next(&mut lexer), Some(Token::TypedInt(42, NumberSuffix::U8)));
+        assert_eq!(next(&mut lexer), Some(Token::TypedInt(-1, NumberSuffix::I64)));
+        assert_eq!(next(&mut lexer), Some(Token::TypedFloat(1.5, NumberSuffix::F32)));
+        assert_eq!(next(&mut lexer), Some(Token::TypedFloat(2.0, NumberSuffix::F64)));
+        let err = lexer.next_token().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidNumber(String::from("300u8")));
+        assert_eq!(err.span(), Some(Span::new(23, 28)));
+        assert_eq!(lexer.next_token().unwrap_err().kind(), &ErrorKind::InvalidNumber(String::from("1.5u8")));
+        assert_eq!(lexer.next_token().unwrap_err().message(), "Invalid number 1abc");
+        assert_eq!(next(&mut lexer), Some(Token::Int(3)));
+        assert_eq!(Token::TypedFloat(1.0, NumberSuffix::F32).kind(), TokenKind::Float);
+    }
+
+    #[test]
+    fn non_finite_floats() {
+        let mut lexer = Lexer::new("inf -inf NaN r#inf infinity -infinity");
+        assert_eq!(next(&mut lexer), Some(Token::Float(f64::INFINITY)));
+        assert_eq!(lexer.next_token().unwrap(), Some((Token::Float(f64::NEG_INFINITY), Span::new(4, 8))));
+        assert!(matches!(next(&mut lexer), Some(Token::Float(x)) if x.is_nan()));
+        assert_eq!(next(&mut lexer), Some(Token::Ident(15, 18)));
+        assert_eq!(next(&mut lexer), Some(Token::Ident(19, 27)));
+        assert_eq!(lexer.next_token().unwrap_err().message(), "Invalid number -infinity");
+        assert!(!is_plain_ident("inf") && !is_plain_ident("NaN"));
+    }
+
+    #[test]
+    fn wide_suffixed_ints() {
+        let mut lexer = Lexer::new("18446744073709551615u64 -170141183460469231731687303715884105728i128 170141183460469231731687303715884105727u128 18446744073709551616u64 -1u64");
+        assert_eq!(next(&mut lexer), Some(Token::TypedInt(u64::MAX as i128, NumberSuffix::U64)));
+        assert_eq!(next(&mut lexer), Some(Token::TypedInt(i128::MIN, NumberSuffix::I128)));
+        assert_eq!(next(&mut lexer), Some(Token::TypedInt(i128::MAX, NumberSuffix::U128)));
+        assert_eq!(lexer.next_token().unwrap_err().message(), "Invalid number 18446744073709551616u64");
+        assert_eq!(lexer.next_token().unwrap_err().message(), "Invalid number -1u64");
+    }
+
+    #[test]
+    fn invalid_chars() {
+        let mut lexer = Lexer::new("'' 1 'ab' 2 'é\n3 '");
+        let err = lexer.next_token().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidChar(String::from("''")));
+        assert_eq!(next(&mut lexer), Some(Token::Int(1)));
+        assert_eq!(lexer.next_token().unwrap_err().span(), Some(Span::new(5, 9)));
+        assert_eq!(next(&mut lexer), Some(Token::Int(2)));
+        assert_eq!(lexer.next_token().unwrap_err().message(), "Invalid char literal 'é");
+        assert_eq!(next(&mut lexer), Some(Token::Int(3)));
+        assert_eq!(lexer.next_token().unwrap_err().kind(), &ErrorKind::InvalidChar(String::from("'")));
+        assert_eq!(next(&mut lexer), None);
+    }
+
+    #[test]
+    fn char_escapes() {
+        let mut lexer = Lexer::new(r"'\'' '\\' '\n' '\x41' '\u{1F980}' '🦀' '\u{0}'");
+        for c in ['\'', '\\', '\n', 'A', '🦀', '🦀', '\0'] {
+            assert_eq!(next(&mut lexer), Some(Token::Char(c)));
+        }
+        assert_eq!(next(&mut lexer), None);
+
+        let mut lexer = Lexer::new(r"'\q' 1 '\u{D800}' 2 'a\'' 3 '\'");
+        let err = lexer.next_token().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidEscape(String::from(r"\q")));
+        assert_eq!(err.span(), Some(Span::new(1, 3)));
+        assert_eq!(next(&mut lexer), Some(Token::Int(1)));
+        assert_eq!(lexer.next_token().unwrap_err().kind(), &ErrorKind::InvalidEscape(String::from(r"\u")));
+        assert_eq!(next(&mut lexer), Some(Token::Int(2)));
+        assert_eq!(lexer.next_token().unwrap_err().kind(), &ErrorKind::InvalidChar(String::from(r"'a\''")));
+        assert_eq!(next(&mut lexer), Some(Token::Int(3)));
+        assert_eq!(lexer.next_token().unwrap_err().kind(), &ErrorKind::InvalidChar(String::from(r"'\")));
+        assert_eq!(lexer.next_token().unwrap_err().kind(), &ErrorKind::InvalidChar(String::from("'")));
+        assert_eq!(next(&mut lexer), None);
+    }
+
+    #[test]
+    fn token_cache() {
+        let src = "Player(name: \"a b\", /* hp */ hp: 10, tags: [A, B], pos: (1, 2))";
+        let edits = [
+            (Span::new(29, 31), "200"),
+            (Span::new(13, 13), "x"),
+            (Span::new(14, 14), "\""),
+            (Span::new(20, 20), "/*"),
+            (Span::new(0, 6), ""),
+            (Span::new(40, 40), "C, "),
+            (Span::new(63, 63), " // end"),
+            (Span::new(0, 63), "1"),
+        ];
+        for (span, text) in edits {
+            for comments in [false, true] {
+                let mut cache = if comments { TokenCache::with_comments(src) } else { TokenCache::new(src) };
+                let before = cache.tokens().to_vec();
+                let relexed = cache.relex_range(TextEdit { span, text: String::from(text) });
+
+                let fresh = if comments { TokenCache::with_comments(cache.src()) } else { TokenCache::new(cache.src()) };
+                assert_eq!(cache.tokens(), fresh.tokens(), "{span:?} {text:?}");
+                assert_eq!(before.len() - relexed.old.len(), cache.tokens().len() - relexed.new.len());
+                assert_eq!(before[..relexed.old.start], cache.tokens()[..relexed.new.start]);
+            }
+        }
+    }
+
+    #[test]
+    fn token_cache_random_edits() {
+        let pieces = ["", "x", "1", "\"", "'", "/", "*", "//", "/*", "*/", "\n", " ", "(", ")", "[", "]", ",", ":", ".", "-", "r#", "b\"", "\\", "é", "u8"];
+        let sources = ["// a note\n[1]", "Player(name: \"a b\", /* hp */ hp: 10, tags: [A, B], pos: (1, 2))", "[1.5, -2, 'c', b\"x\", r#a.b] /* x", "{\"a\": 1}\n// end"];
+        // A xorshift generator, so failures can be replayed.
+        let mut state = 0x2545f4914f6cdd1du64;
+        let mut next = |n: usize| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            return (state % n as u64) as usize;
+        };
+
+        for src in sources {
+            for comments in [false, true] {
+                let mut cache = if comments { TokenCache::with_comments(src) } else { TokenCache::new(src) };
+                for _ in 0..300 {
+                    let bounds: Vec<usize> = (0..=cache.src().len()).filter(|&x| cache.src().is_char_boundary(x)).collect();
+                    let (a, b) = (bounds[next(bounds.len())], bounds[next(bounds.len())]);
+                    let span = Span::new(a.min(b), if next(3) == 0 { a.max(b) } else { a.min(b) });
+                    let text = String::from(pieces[next(pieces.len())]);
+                    let before = cache.tokens().to_vec();
+                    let relexed = cache.relex_range(TextEdit { span, text: text.clone() });
+
+                    let fresh = if comments { TokenCache::with_comments(cache.src()) } else { TokenCache::new(cache.src()) };
+                    assert_eq!(cache.tokens(), fresh.tokens(), "{src:?} {span:?} {text:?} -> {:?}", cache.src());
+                    assert_eq!(before.len() - relexed.old.len(), cache.tokens().len() - relexed.new.len());
+                }
+            }
+        }
+
+        let mut cache = TokenCache::new("// a note\n[1]");
+        cache.relex_range(TextEdit { span: Span::new(5, 5), text: String::from("x") });
+        assert_eq!(cache.tokens(), TokenCache::new("// a xnote\n[1]").tokens());
+    }
+
+    #[test]
+    fn token_cache_invalidation() {
+        let mut cache = TokenCache::new("[1, 2, 3, 4]");
+        let relexed = cache.relex_range(TextEdit { span: Span::new(4, 5), text: String::from("20") });
+        assert_eq!(relexed, Relexed { old: 3..4, new: 3..4 });
+        assert_eq!(cache.tokens()[3], (Ok(Token::Int(20)), Span::new(4, 6)));
+        assert_eq!(cache.tokens()[7], (Ok(Token::Int(4)), Span::new(11, 12)));
+
+        // Opening a string changes everything after it.
+        let relexed = cache.relex_range(TextEdit { span: Span::new(1, 1), text: String::from("\"") });
+        assert_eq!(relexed, Relexed { old: 0..9, new: 0..2 });
+        assert_eq!(cache.tokens()[1], (Err(ErrorKind::UnterminatedString), Span::new(1, 14)));
+    }
+
+    #[test]
+    fn never_panics() {
+        let inputs = ["'", "'\u{301}", "-", "--1", "1.2.3", "b\"\\", "b\"\\é\"", "r#", "r#é", "/", "/*/", "\"", "\"\\", "\"\\u{", "1é", "é", "\u{0}", "'a", "99999999999999999999"];
+        for src in inputs {
+            let mut lexer = Lexer::new(src);
+            // Every call has to make progress, so the number of tokens and errors is bounded by the length.
+            for _ in 0..=src.len() {
+                if let Ok(None) = lexer.next_token() {
+                    break;
+                }
+            }
+            assert_eq!(lexer.next_token().ok(), Some(None), "{src:?}");
+        }
+
+        let lexer = Lexer::new("é");
+        assert_eq!(lexer.get_string(1, 2), "");
+        assert_eq!(lexer.get_string(0, 9), "");
+        assert_eq!(lexer.get_bytes(0, 9), Cow::Borrowed(b""));
+        assert_eq!(Lexer::new("a\\q\\x4").get_bytes(0, 6), Cow::<[u8]>::Owned(b"a\\q\\x4".to_vec()));
+    }
+}
@@ -1,11 +1,41 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use smallvec::SmallVec;
+
+pub use buffer::RonEventBuffer;
+pub use deserialize::{read_tuple_struct, RonDeserialize};
+#[cfg(feature = "derive")]
+pub use light_ron_derive::RonDeserialize;
+pub use enum_reader::{read_enum, VariantKind, VariantReader};
+pub(crate) use deserialize::{describe, unexpected};
+pub use error::{ErrorKind, RonError, Span};
+pub use lexer::{NumberSuffix, TokenKind};
+pub use source::RonSource;
+pub use struct_reader::{DeserializeFields, StructReader};
+pub use trace::{TraceEvent, Tracer};
+pub use tracked::{PathSegment, TrackedDeserializer};
 use lexer::{Lexer, Token};
 
-mod lexer;
-
+mod buffer;
+mod deserialize;
+mod enum_reader;
+mod error;
+pub mod lexer;
+mod source;
+mod struct_reader;
+mod trace;
+mod tracked;
+
+#[derive(Clone)]
 enum InternalState<'a> {
     SecondValue,
-    Map,
-    Struct { name: Option<&'a str> },
+    /// Keys found so far, only kept when duplicated keys are rejected.
+    Map { keys: Vec<(RonPrimitive<'a>, Span)> },
+    /// `implicit` is set for a top level struct written without its parentheses, which ends with the document.
+    Struct { name: Option<&'a str>, implicit: bool },
     Tuple { name: Option<&'a str> },
     List,
     OptionalSomeValue,
@@ -13,292 +43,971 @@ enum InternalState<'a> {
 }
 
 
+/// Settings of a [`RonDeserializer`].
+#[derive(Debug, Clone, Default)]
+pub struct DeserializerOptions {
+    /// Instead of failing on the first syntax error, yield it as a `RonEvent::Error` and keep going from the next `,` or
+    /// closing delimiter. Containers left open at the end of the document get their end events, so the stream stays balanced.
+    pub recover_errors: bool,
+
+    /// Produce a `RonEvent::Comment` for every comment, right before the event that follows it in the source.
+    pub comments: bool,
+
+    /// Fail on map keys that appear more than once in the same map. The error points at the repeated key, and its kind
+    /// holds the span of the first one.
+    pub reject_duplicate_map_keys: bool,
+
+    /// Read the source as a stream of documents, one per top level value, and produce a `RonEvent::DocumentEnd` after
+    /// each of them. Without this, the values just follow each other.
+    pub documents: bool,
+
+    /// Which syntax is accepted besides the basic one.
+    pub syntax: SyntaxOptions,
+
+    /// How the names of structs are checked against the ones decoders expect. See [`RonDeserializer::check_struct_name`].
+    pub struct_names: StructNames,
+
+    /// Read newtype structs as the value they wrap, like `5.0` for `Meters(5.0)`. Only used by the
+    /// [`serde`](crate::serde) bridge, the same as the `unwrap_newtypes` extension of the `ron` crate.
+    pub unwrap_newtypes: bool,
+
+    /// Read newtype variants that wrap a struct or tuple sharing the parentheses with it, like `Shape(side: 2)` for
+    /// `Shape(Square { side: 2 })`. Only used by the [`serde`](crate::serde) bridge, the same as the
+    /// `unwrap_variant_newtypes` extension of the `ron` crate.
+    pub unwrap_variant_newtypes: bool,
+
+    /// Called with the start and end of the document, every container opened and closed, and every syntax error.
+    pub tracer: Option<Tracer>,
+}
+
+/// Whether the name written before a struct has to match the type it's read into. See
+/// [`DeserializerOptions::struct_names`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum StructNames {
+    /// Any name is accepted, or none.
+    #[default]
+    Ignore,
+    /// The name can be left out, but has to match if it's written.
+    Verify,
+    /// The name has to be written and match.
+    Require,
+}
+
+/// How lenient a [`RonDeserializer`] is with the syntax. The default accepts everything it always has.
+#[derive(Debug, Clone)]
+pub struct SyntaxOptions {
+    /// Whether the last element of a container can, must or can't be followed by a comma.
+    pub trailing_commas: TrailingCommas,
+
+    /// Accept elements that aren't separated by commas, like `[1 2]`, and a comma before the first one.
+    pub optional_commas: bool,
+
+    /// Accept identifiers that don't start with an uppercase letter as enum variants, like `fire` or map keys like
+    /// `{crabs: 30}`.
+    pub lowercase_enums: bool,
+
+    /// Accept a struct at the top of the document written without its parentheses, like `name: "a", lives: 3`.
+    pub implicit_top_struct: bool,
+}
+
+/// Whether a comma after the last element of a container is accepted. See [`SyntaxOptions::trailing_commas`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailingCommas {
+    #[default]
+    Allowed,
+    Required,
+    Forbidden,
+}
+
+impl SyntaxOptions {
+    /// Only commas between elements, with or without trailing ones, and enum variants in PascalCase.
+    pub fn strict() -> Self {
+        return Self { trailing_commas: TrailingCommas::Allowed, optional_commas: false, lowercase_enums: false, implicit_top_struct: false };
+    }
+
+    /// Everything the deserializer can read.
+    pub fn lenient() -> Self {
+        return Self { implicit_top_struct: true, ..Default::default() };
+    }
+}
+
+impl Default for SyntaxOptions {
+    fn default() -> Self {
+        return Self { trailing_commas: TrailingCommas::Allowed, optional_commas: true, lowercase_enums: true, implicit_top_struct: false };
+    }
+}
+
+/// Containers that can be open at once before the deserializer allocates. See [`RonDeserializer`].
+pub const INLINE_DEPTH: usize = 16;
+
+/// Reads a RON document as a stream of [`RonEvent`]s.
+///
+/// Reading doesn't allocate as long as the document is nested at most [`INLINE_DEPTH`] containers deep, has no
+/// escapes in its strings and byte strings, and there are no errors. Comments and checking for duplicate map keys
+/// also allocate when enabled. This makes it fine to read small documents every frame.
+#[derive(Clone)]
 pub struct RonDeserializer<'a> {
     lexer: Lexer<'a>,
-    tok_queue: Vec<Token>,
-    stack: Vec<InternalState<'a>>
+    /// Tokens read ahead and put back, the next one last. The parser never puts back more than four.
+    tok_queue: SmallVec<[(Token, Span); 4]>,
+    stack: SmallVec<[InternalState<'a>; INLINE_DEPTH]>,
+    peeked: Option<(RonEvent<'a>, Span)>,
+    last_span: Span,
+    options: DeserializerOptions,
+    /// Token the last resynchronization stopped at, to avoid stopping at it again if it causes another error.
+    resync_stop: Option<Span>,
+    /// Set when recovering from an error at the end of the document, while the open containers get closed.
+    unwinding: bool,
+    /// Comments found while reading tokens, waiting to be returned before the event that follows them.
+    comments: VecDeque<(&'a str, Span)>,
+    /// Event already parsed, waiting for the comments before it to be returned.
+    held: Option<(RonEvent<'a>, Span)>,
+    /// End of the top level value just finished, waiting for its `DocumentEnd`.
+    document_end: Option<usize>,
+    /// Whether the innermost open container has no elements yet, for the comma rules.
+    first_element: bool,
+    /// Whether the first value of the document has been started, since only it can be an implicit struct.
+    started: bool,
+    /// Events recorded by a [`RonEventBuffer`], returned instead of parsing.
+    replay: Option<Replay<'a>>,
+    /// Whether the tracer was told about the start and the end of the document.
+    traced_start: bool,
+    traced_end: bool,
+}
+
+#[derive(Clone)]
+struct Replay<'a> {
+    events: Arc<[(RonEvent<'a>, Span)]>,
+    next: usize,
 }
 
 impl<'a> RonDeserializer<'a> {
     pub fn new(src: &'a str) -> Self {
-        return Self { lexer: Lexer::new(src), tok_queue: Vec::new(), stack: Vec::new() };
+        return Self::with_options(src, DeserializerOptions::default());
+    }
+
+    pub fn with_options(src: &'a str, options: DeserializerOptions) -> Self {
+        return Self {
+            lexer: if options.comments { Lexer::with_comments(src) } else { Lexer::new(src) },
+            tok_queue: SmallVec::new(),
+            stack: SmallVec::new(),
+            peeked: None,
+            last_span: Span::default(),
+            options,
+            resync_stop: None,
+            unwinding: false,
+            comments: VecDeque::new(),
+            held: None,
+            document_end: None,
+            first_element: false,
+            started: false,
+            replay: None,
+            traced_start: false,
+            traced_end: false,
+        };
     }
 
-    pub fn next_event(&mut self) -> RonEvent<'a> {
+    /// A deserializer that returns `events`, recorded from `src`, instead of parsing it.
+    fn replaying(src: &'a str, options: DeserializerOptions, events: Arc<[(RonEvent<'a>, Span)]>) -> Self {
+        return Self { replay: Some(Replay { events, next: 0 }), ..Self::with_options(src, options) };
+    }
+
+    pub fn next_event(&mut self) -> Result<RonEvent<'a>, RonError> {
+        let (event, span) = match self.peeked.take() {
+            Some(x) => x,
+            None => self.read_event()?,
+        };
+        self.last_span = span;
+        return Ok(event);
+    }
+
+    /// Returns the event the next call to `next_event` will return, without consuming it.
+    pub fn peek_event(&mut self) -> Result<&RonEvent<'a>, RonError> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.read_event()?);
+        }
+        return Ok(&self.peeked.as_ref().unwrap().0);
+    }
+
+    /// Like `next_event`, skipping `Comment` events. Decoders read with it, so they work with the `comments` option.
+    pub fn next_non_comment(&mut self) -> Result<RonEvent<'a>, RonError> {
         loop {
-            match self.stack.last() {
-                Some(InternalState::Map) => {
-                    match self.next_token() {
-                        Some(Token::Comma) => {},
-                        Some(x) => self.tok_queue.insert(0, x),
-                        None => panic!("Expected ',' or '}}', got EOF!"),
+            match self.next_event()? {
+                RonEvent::Comment(_) => {},
+                x => return Ok(x),
+            }
+        }
+    }
+
+    /// Like `peek_event`, consuming the `Comment` events before the one returned.
+    pub fn peek_non_comment(&mut self) -> Result<&RonEvent<'a>, RonError> {
+        while let RonEvent::Comment(_) = self.peek_event()? {
+            self.next_event()?;
+        }
+        return self.peek_event();
+    }
+
+    /// Consumes all the events of the next value, without building anything out of them.
+    pub fn skip_value(&mut self) -> Result<(), RonError> {
+        let mut depth = 0usize;
+        loop {
+            match self.next_event()? {
+                // A `Some` is always followed by its value, and comments aren't values.
+                RonEvent::OptionalSomeValue | RonEvent::Comment(_) => continue,
+                RonEvent::NamedField(_) if depth > 0 => continue,
+                RonEvent::Primitive(_) => {},
+                RonEvent::StructStart { .. } | RonEvent::TupleStart { .. } | RonEvent::MapStart | RonEvent::ListStart => depth += 1,
+                RonEvent::StructEnd { .. } | RonEvent::TupleEnd { .. } | RonEvent::MapEnd | RonEvent::ListEnd if depth > 0 => depth -= 1,
+                x => return Err(deserialize::unexpected(self, "a value", x)),
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Saves the current state, so reading can go back to it with `restore`, like to try another way of reading a value.
+    /// ```
+    /// # use light_ron::deserial::{RonDeserialize, RonDeserializer};
+    /// let mut de = RonDeserializer::new("(x: 1.0, y: 2.0)");
+    /// let checkpoint = de.checkpoint();
+    /// let pos = match <(f32, f32)>::deserialize(&mut de) {
+    ///     Ok(x) => x,
+    ///     Err(_) => {
+    ///         de.restore(&checkpoint);
+    ///         let mut fields = [0.0; 2];
+    ///         de.next_event().unwrap();
+    ///         for x in &mut fields {
+    ///             de.next_event().unwrap();
+    ///             *x = f32::deserialize(&mut de).unwrap();
+    ///         }
+    ///         (fields[0], fields[1])
+    ///     },
+    /// };
+    /// assert_eq!(pos, (1.0, 2.0));
+    /// ```
+    pub fn checkpoint(&self) -> Checkpoint<'a> {
+        return Checkpoint { state: self.clone() };
+    }
+
+    /// Goes back to the state saved by `checkpoint`. The same checkpoint can be restored any number of times.
+    ///
+    /// # Panics
+    /// If the checkpoint was saved by a deserializer reading another source.
+    pub fn restore(&mut self, checkpoint: &Checkpoint<'a>) {
+        assert!(std::ptr::eq(self.src(), checkpoint.state.src()), "Restored a checkpoint of another source!");
+        *self = checkpoint.state.clone();
+    }
+
+    /// Consumes the next value and returns its source text, from its first token to its last one, so it can be
+    /// parsed later or kept as it is.
+    /// ```
+    /// # use light_ron::deserial::{RonDeserializer, RonEvent};
+    /// let mut de = RonDeserializer::new("(script: Some( Call(a, [1, 2]) ), b: 1)");
+    /// de.next_event().unwrap();
+    /// de.next_event().unwrap();
+    /// assert_eq!(de.next_raw_value().unwrap(), "Some( Call(a, [1, 2]) )");
+    /// assert_eq!(de.next_event().unwrap(), RonEvent::NamedField("b"));
+    /// ```
+    pub fn next_raw_value(&mut self) -> Result<&'a str, RonError> {
+        // Comments before the value aren't part of it.
+        self.peek_non_comment()?;
+        let start = self.peeked.as_ref().unwrap().1.start;
+
+        let mut somes = 0;
+        while *self.peek_event()? == RonEvent::OptionalSomeValue {
+            self.next_event()?;
+            somes += 1;
+        }
+        self.skip_value()?;
+
+        let mut end = self.last_span.end;
+        for _ in 0..somes {
+            end = lexer::find_some_close(self.src(), end)?;
+        }
+        return Ok(&self.src()[start..end]);
+    }
+
+    /// Deserializes a `T` out of every top level value left, for sources that hold a stream of them, like one per line.
+    ///
+    /// Works with or without the `documents` option. The iteration stops after the first error.
+    /// ```
+    /// # use light_ron::deserial::RonDeserializer;
+    /// let mut de = RonDeserializer::new("(1, \"a\")\n(2, \"b\")\n");
+    /// let entries: Result<Vec<(i32, &str)>, _> = de.documents().collect();
+    /// assert_eq!(entries.unwrap(), [(1, "a"), (2, "b")]);
+    /// ```
+    pub fn documents<T: RonDeserialize<'a>>(&mut self) -> Documents<'_, 'a, T> {
+        return Documents { de: self, failed: false, marker: PhantomData };
+    }
+
+    /// The document being read.
+    pub fn src(&self) -> &'a str {
+        return self.lexer.src();
+    }
+
+    pub fn options(&self) -> &DeserializerOptions {
+        return &self.options;
+    }
+
+    /// Span of the source text of the last event returned by `next_event`.
+    ///
+    /// Start events span their name and opening delimiter, end events their closing delimiter, and `NamedField` the field name.
+    pub fn last_span(&self) -> Span {
+        return self.last_span;
+    }
+
+    /// The 1-based line and column (in chars) and the byte offset of the point right after the last event returned by
+    /// `next_event`. Lines are counted from the start of the document on every call, so it's meant for reporting errors.
+    /// ```
+    /// # use light_ron::deserial::RonDeserializer;
+    /// let mut de = RonDeserializer::new("[\n  Fira,\n]");
+    /// de.next_event().unwrap();
+    /// de.next_event().unwrap();
+    /// assert_eq!(de.position(), (2, 7, 8));
+    /// ```
+    pub fn position(&self) -> (usize, usize, usize) {
+        let before = &self.lexer.src()[..self.last_span.end];
+        let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+        return (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1, before.len());
+    }
+
+    /// Checks the name of the struct whose start was just read against the `expected` one, following
+    /// [`DeserializerOptions::struct_names`]. The error points at the start of the struct.
+    /// ```
+    /// # use light_ron::deserial::{DeserializerOptions, RonDeserializer, RonEvent, StructNames};
+    /// let options = DeserializerOptions { struct_names: StructNames::Verify, ..Default::default() };
+    /// let mut de = RonDeserializer::with_options("Vec3(x: 1)", options);
+    /// let RonEvent::StructStart { name } = de.next_event().unwrap() else { panic!() };
+    /// assert_eq!(de.check_struct_name(name, "Vec2").unwrap_err().message(), "Expected struct Vec2, found struct Vec3");
+    /// ```
+    pub fn check_struct_name(&self, name: Option<&str>, expected: &str) -> Result<(), RonError> {
+        let matches = match (self.options.struct_names, name) {
+            (StructNames::Ignore, _) | (StructNames::Verify, None) => true,
+            (_, Some(name)) => name == expected,
+            (StructNames::Require, None) => false,
+        };
+        if matches {
+            return Ok(());
+        }
+        return Err(self.error(ErrorKind::StructName { expected: String::from(expected), found: name.map(String::from) }));
+    }
+
+    /// Builds an error pointing at the last event returned by `next_event`, so decoders can report their own errors with a location.
+    pub fn error(&self, kind: ErrorKind) -> RonError {
+        return RonError::at(kind, self.last_span, self.lexer.src());
+    }
+
+    /// Parses the next event, telling the tracer about it.
+    fn read_event(&mut self) -> Result<(RonEvent<'a>, Span), RonError> {
+        let result = self.parse_event();
+        let Some(tracer) = &self.options.tracer else {
+            return result;
+        };
+        if !self.traced_start {
+            self.traced_start = true;
+            tracer.trace(&TraceEvent::Start { len: self.lexer.src().len() });
+        }
+
+        let (event, span) = match &result {
+            Ok(x) => x,
+            Err(err) => {
+                tracer.trace(&TraceEvent::Error(err));
+                return result;
+            },
+        };
+        let kind = match event {
+            RonEvent::StructStart { .. } | RonEvent::StructEnd { .. } => "struct",
+            RonEvent::TupleStart { .. } | RonEvent::TupleEnd { .. } => "tuple",
+            RonEvent::MapStart | RonEvent::MapEnd => "map",
+            RonEvent::ListStart | RonEvent::ListEnd => "list",
+            _ => "",
+        };
+        match event {
+            RonEvent::StructStart { name } | RonEvent::TupleStart { name } => tracer.trace(&TraceEvent::Enter { kind, name: *name, offset: span.start }),
+            RonEvent::MapStart | RonEvent::ListStart => tracer.trace(&TraceEvent::Enter { kind, name: None, offset: span.start }),
+            RonEvent::StructEnd { .. } | RonEvent::TupleEnd { .. } | RonEvent::MapEnd | RonEvent::ListEnd => {
+                tracer.trace(&TraceEvent::Exit { kind, offset: span.end });
+            },
+            RonEvent::Error(err) => tracer.trace(&TraceEvent::Error(err)),
+            RonEvent::Eof if !self.traced_end => {
+                self.traced_end = true;
+                tracer.trace(&TraceEvent::End { offset: span.end });
+            },
+            _ => {},
+        }
+        return result;
+    }
+
+    fn parse_event(&mut self) -> Result<(RonEvent<'a>, Span), RonError> {
+        if let Some(replay) = &mut self.replay {
+            // The recording ends with `Eof`, which keeps being returned.
+            let event = replay.events[replay.next.min(replay.events.len() - 1)].clone();
+            replay.next += 1;
+            return Ok(event);
+        }
+        if !self.options.comments {
+            return self.parse_recovering();
+        }
+
+        // Parsing an event may read comments that come after it, so it's held until the ones before it are returned.
+        let held = match self.held.take() {
+            Some(x) => x,
+            None => self.parse_recovering()?,
+        };
+        if let Some(&(text, span)) = self.comments.front() {
+            if span.start < held.1.start {
+                self.comments.pop_front();
+                self.held = Some(held);
+                return Ok((RonEvent::Comment(text), span));
+            }
+        }
+        return Ok(held);
+    }
+
+    fn parse_recovering(&mut self) -> Result<(RonEvent<'a>, Span), RonError> {
+        if self.unwinding {
+            if let Some(x) = self.unwind() {
+                return Ok(x);
+            }
+        }
+
+        let event = match self.parse_next() {
+            Err(err) if self.options.recover_errors => {
+                let span = err.span().unwrap_or_else(|| self.eof_span());
+                self.resync();
+                return Ok((RonEvent::Error(err), span));
+            },
+            x => x?,
+        };
+        if self.options.documents && self.stack.is_empty() && !matches!(event.0, RonEvent::OptionalSomeValue | RonEvent::DocumentEnd | RonEvent::Eof) {
+            self.document_end = Some(event.1.end);
+        }
+        return Ok(event);
+    }
+
+    /// Skips tokens until the next `,` or closing delimiter of the current container, leaving it for the state machine.
+    fn resync(&mut self) {
+        if self.stack.is_empty() {
+            // Top level errors always consume the offending token, so the next value can just be tried.
+            return;
+        }
+
+        let mut depth = 0usize;
+        loop {
+            let (tok, span) = match self.next_token() {
+                Ok(Some(x)) => x,
+                Ok(None) => {
+                    self.unwinding = true;
+                    return;
+                },
+                // The lexer always advances past errors.
+                Err(_) => continue,
+            };
+
+            match tok {
+                Token::LParen | Token::LBracket | Token::LCurly => depth += 1,
+                Token::RParen | Token::RBracket | Token::RCurly if depth > 0 => depth -= 1,
+                Token::Comma | Token::RParen | Token::RBracket | Token::RCurly if depth == 0 => {
+                    if self.resync_stop == Some(span) {
+                        continue;
                     }
+                    self.resync_stop = Some(span);
 
-                    match self.next_token() {
-                        Some(Token::RCurly) => {
+                    // A `Some` whose value failed is dropped at the next element, but can still be closed.
+                    if tok == Token::Comma {
+                        while let Some(InternalState::EndedOptionalSomeValue) = self.stack.last() {
                             self.stack.pop();
-                            return RonEvent::MapEnd;
-                        },
-                        Some(x) => self.tok_queue.insert(0, x),
-                        None => panic!("Expected '}}' when closing the map, got EOF!"),
+                        }
+                    }
+
+                    // A mismatched closing delimiter is taken as the one the current container expects.
+                    let tok = match (tok, self.stack.last()) {
+                        (Token::Comma, _) => Token::Comma,
+                        (_, Some(InternalState::Map { .. })) => Token::RCurly,
+                        (_, Some(InternalState::List)) => Token::RBracket,
+                        (_, Some(InternalState::Struct { .. } | InternalState::Tuple { .. } | InternalState::EndedOptionalSomeValue)) => Token::RParen,
+                        _ => continue,
+                    };
+                    self.tok_queue.push((tok, span));
+                    return;
+                },
+                _ => {},
+            }
+        }
+    }
+
+    /// Closes one of the containers left open at the end of the document. Returns `None` when all are closed.
+    fn unwind(&mut self) -> Option<(RonEvent<'a>, Span)> {
+        let span = self.eof_span();
+        while let Some(state) = self.stack.pop() {
+            match state {
+                InternalState::Map { .. } => return Some((RonEvent::MapEnd, span)),
+                InternalState::Struct { name, .. } => return Some((RonEvent::StructEnd { name }, span)),
+                InternalState::Tuple { name } => return Some((RonEvent::TupleEnd { name }, span)),
+                InternalState::List => return Some((RonEvent::ListEnd, span)),
+                _ => {},
+            }
+        }
+        self.unwinding = false;
+        return None;
+    }
+
+    fn parse_next(&mut self) -> Result<(RonEvent<'a>, Span), RonError> {
+        loop {
+            match self.stack.last() {
+                Some(InternalState::Map { .. }) => {
+                    if let Some(span) = self.separator(Some(Token::RCurly))? {
+                        self.stack.pop();
+                        return Ok((RonEvent::MapEnd, span));
                     }
 
-                    let key = self.try_value().expect("Expected key in map!");
+                    let key = self.expect_value()?;
+
+                    self.expect_token(Token::Colon)?;
 
-                    assert!(self.next_token() == Some(Token::Colon), "Expected ':'");
+                    if self.options.reject_duplicate_map_keys {
+                        self.check_map_key(&key)?;
+                    }
                     
                     self.stack.push(InternalState::SecondValue);
-                    return key;
+                    return Ok(key);
                 },
-                Some(InternalState::Struct { name }) => {
-                    let name = *name;
-                    match self.next_token() {
-                        Some(Token::Comma) => {},
-                        Some(x) => self.tok_queue.insert(0, x),
-                        None => panic!("Expected ',' or ')', got EOF!"),
+                Some(&InternalState::Struct { name, implicit }) => {
+                    let close = if implicit { None } else { Some(Token::RParen) };
+                    if let Some(span) = self.separator(close)? {
+                        self.stack.pop();
+                        return Ok((RonEvent::StructEnd { name }, span));
                     }
 
-                    match self.next_token() {
-                        Some(Token::RParen) => {
-                            self.stack.pop();
-                            return RonEvent::StructEnd { name };
-                        },
-                        Some(x) => self.tok_queue.insert(0, x),
-                        None => panic!("Expected ')' when closing the struct, got EOF!"),
-                    }
-
-                    let ident = match self.next_token() {
-                        Some(Token::Ident(a, b)) => self.lexer.get_string(a, b),
-                        x => panic!("Expected IDENTIFIER, got {x:?}"),
+                    let (ident, span) = match self.next_token()? {
+                        Some((Token::Ident(a, b), span)) => (self.lexer.get_string(a, b), span),
+                        x if implicit => return Err(self.unexpected(x, &[TokenKind::Ident])),
+                        x => return Err(self.unexpected(x, &[TokenKind::Ident, TokenKind::RParen])),
                     };
 
-                    assert!(self.next_token() == Some(Token::Colon), "Expected ':'");
+                    self.expect_token(Token::Colon)?;
                     
                     self.stack.push(InternalState::SecondValue);
-                    return RonEvent::NamedField(ident);
+                    return Ok((RonEvent::NamedField(ident), span));
                 },
                 Some(InternalState::SecondValue) => {
                     self.stack.pop();
-                    return self.try_value().expect("Expected value!");
+                    return self.expect_value();
                 }
-                Some(InternalState::Tuple { name }) => {
-                    let name = *name;
-                    match self.next_token() {
-                        Some(Token::Comma) => {},
-                        Some(x) => self.tok_queue.insert(0, x),
-                        None => panic!("Expected ',' or ')', got EOF!"),
-                    }
-
-                    match self.next_token() {
-                        Some(Token::RParen) => {
-                            self.stack.pop();
-                            return RonEvent::TupleEnd { name };
-                        },
-                        Some(x) => self.tok_queue.insert(0, x),
-                        None => panic!("Expected ')' when closing the tuple, got EOF!"),
+                Some(&InternalState::Tuple { name }) => {
+                    if let Some(span) = self.separator(Some(Token::RParen))? {
+                        self.stack.pop();
+                        return Ok((RonEvent::TupleEnd { name }, span));
                     }
-                    
-                    return self.try_value().expect("Expected value!");
+                    return self.expect_value();
                 },
                 Some(InternalState::List) => {
-                    match self.next_token() {
-                        Some(Token::Comma) => {},
-                        Some(x) => self.tok_queue.insert(0, x),
-                        None => panic!("Expected ',' or ']', got EOF!"),
-                    }
-
-                    match self.next_token() {
-                        Some(Token::RBracket) => {
-                            self.stack.pop();
-                            return RonEvent::ListEnd;
-                        },
-                        Some(x) => self.tok_queue.insert(0, x),
-                        None => panic!("Expected ']' when closing the list, got EOF!"),
+                    if let Some(span) = self.separator(Some(Token::RBracket))? {
+                        self.stack.pop();
+                        return Ok((RonEvent::ListEnd, span));
                     }
-                    
-                    return self.try_value().expect("Expected value!");
+                    return self.expect_value();
                 },
                 Some(InternalState::OptionalSomeValue) => {
                     self.stack.pop();
                     self.stack.push(InternalState::EndedOptionalSomeValue);
-                    return self.try_value().expect("Expected value inside Some option!");
+                    return self.expect_value();
                 }
                 Some(InternalState::EndedOptionalSomeValue) => {
-                    assert!(self.next_token() == Some(Token::RParen), "Expected ')'!");
+                    let span = self.expect_token(Token::RParen)?;
                     self.stack.pop();
+                    if self.options.documents && self.stack.is_empty() {
+                        self.document_end = Some(span.end);
+                    }
                     continue;
                 }
                 None => {
-                    if let Some(x) = self.try_value() {
-                        return x;
-                    } else {
-                        return RonEvent::Eof;
+                    if let Some(end) = self.document_end.take() {
+                        return Ok((RonEvent::DocumentEnd, Span::new(end, end)));
+                    }
+
+                    if !std::mem::replace(&mut self.started, true) && self.options.syntax.implicit_top_struct {
+                        if let Some(x) = self.try_implicit_struct()? {
+                            return Ok(x);
+                        }
+                    }
+
+                    if let Some(x) = self.try_value()? {
+                        return Ok(x);
                     }
+
+                    return match self.next_token()? {
+                        Some((tok, span)) => Err(RonError::at(ErrorKind::ExpectedValue, span, self.lexer.src()).with_tokens(Some(tok.kind()), &[])),
+                        None => Ok((RonEvent::Eof, self.eof_span())),
+                    };
                 },
             }
         }
     }
 
-    fn try_value(&mut self) -> Option<RonEvent<'a>> {
-        if let Some(x) = self.try_struct() {
-            return Some(x);
+    /// Reads what comes before the next element of the current container: a comma, unless it's the first element, or
+    /// the closing delimiter `close`, whose span is returned. A `close` of `None` stands for the end of the document.
+    fn separator(&mut self, close: Option<Token>) -> Result<Option<Span>, RonError> {
+        let first = std::mem::replace(&mut self.first_element, false);
+        let syntax = &self.options.syntax;
+        let (optional_commas, trailing_commas) = (syntax.optional_commas, syntax.trailing_commas);
+        let expected: &[TokenKind] = match close {
+            Some(x) => &[TokenKind::Comma, x.kind()],
+            None => &[TokenKind::Comma],
+        };
+
+        // Offending tokens are put back, so recovery can stop at them if they're a `,` or a closing delimiter.
+        let comma = match self.next_token()? {
+            Some((Token::Comma, span)) if first && !optional_commas => {
+                self.tok_queue.push((Token::Comma, span));
+                return Err(RonError::at(ErrorKind::ExpectedValue, span, self.lexer.src()).with_tokens(Some(TokenKind::Comma), &[]));
+            },
+            Some((Token::Comma, _)) => true,
+            Some(x) => {
+                self.tok_queue.push(x);
+                false
+            },
+            None if close.is_none() => false,
+            None => return Err(self.unexpected(None, expected)),
+        };
+
+        let end = match self.next_token()? {
+            Some((tok, span)) if Some(tok) == close => Some((tok, span)),
+            None if close.is_none() => None,
+            Some(x) => {
+                if !comma && !first && !optional_commas {
+                    self.tok_queue.push(x);
+                    return Err(self.unexpected(Some(x), expected));
+                }
+                self.tok_queue.push(x);
+                return Ok(None);
+            },
+            None => return Err(self.unexpected(None, &expected[1..])),
+        };
+
+        if comma && trailing_commas == TrailingCommas::Forbidden {
+            let found = end.map(|x| x.0.kind());
+            let span = end.map_or_else(|| self.eof_span(), |x| x.1);
+            if let Some(x) = end {
+                self.tok_queue.push(x);
+            }
+            return Err(RonError::at(ErrorKind::ExpectedValue, span, self.lexer.src()).with_tokens(found, &[]));
+        }
+        if !comma && !first && trailing_commas == TrailingCommas::Required {
+            if let Some(x) = end {
+                self.tok_queue.push(x);
+            }
+            // Recovery stops right at the closing delimiter, which shouldn't fail again.
+            self.first_element = true;
+            return Err(self.unexpected(end, &[TokenKind::Comma]));
+        }
+        return Ok(Some(end.map_or_else(|| self.eof_span(), |x| x.1)));
+    }
+
+    /// Starts a top level struct without parentheses if the document starts with a field.
+    fn try_implicit_struct(&mut self) -> Result<Option<(RonEvent<'a>, Span)>, RonError> {
+        let Some(field_tok) = self.next_token()? else {
+            return Ok(None);
+        };
+        let (Token::Ident(..), field_span) = field_tok else {
+            self.tok_queue.push(field_tok);
+            return Ok(None);
+        };
+        let colon_tok = self.next_token()?;
+        if let Some(x) = colon_tok {
+            self.tok_queue.push(x);
+        }
+        self.tok_queue.push(field_tok);
+        if !matches!(colon_tok, Some((Token::Colon, _))) {
+            return Ok(None);
+        }
+
+        self.stack.push(InternalState::Struct { name: None, implicit: true });
+        self.first_element = true;
+        return Ok(Some((RonEvent::StructStart { name: None }, Span::new(field_span.start, field_span.start))));
+    }
+
+    /// Fails if `key` has already appeared in the current map, pointing at both appearances.
+    fn check_map_key(&mut self, key: &(RonEvent<'a>, Span)) -> Result<(), RonError> {
+        let (RonEvent::Primitive(primitive), span) = key else {
+            return Ok(());
+        };
+        let Some(InternalState::Map { keys }) = self.stack.last_mut() else {
+            return Ok(());
+        };
+        if let Some(&(_, first)) = keys.iter().find(|x| x.0 == *primitive) {
+            let key = String::from(&self.lexer.src()[span.start..span.end]);
+            return Err(RonError::at(ErrorKind::DuplicateMapKey { key, first }, *span, self.lexer.src()));
+        }
+        keys.push((primitive.clone(), *span));
+        return Ok(());
+    }
+
+    fn eof_span(&self) -> Span {
+        let len = self.lexer.src().len();
+        return Span::new(len, len);
+    }
+
+    /// Builds the error for a token, or the end of the document if `found` is `None`, that isn't one of `expected`.
+    fn unexpected(&self, found: Option<(Token, Span)>, expected: &[TokenKind]) -> RonError {
+        return match found {
+            Some((tok, span)) => RonError::at(ErrorKind::UnexpectedToken, span, self.lexer.src()).with_tokens(Some(tok.kind()), expected),
+            None => RonError::at(ErrorKind::UnexpectedEof, self.eof_span(), self.lexer.src()).with_tokens(None, expected),
+        };
+    }
+
+    fn expect_value(&mut self) -> Result<(RonEvent<'a>, Span), RonError> {
+        if let Some(x) = self.try_value()? {
+            return Ok(x);
+        }
+
+        // The token is put back so recovery can stop at it if it's a `,` or a closing delimiter.
+        let (found, span) = match self.next_token()? {
+            Some((tok, span)) => {
+                self.tok_queue.push((tok, span));
+                (Some(tok.kind()), span)
+            },
+            None => (None, self.eof_span()),
+        };
+        return Err(RonError::at(ErrorKind::ExpectedValue, span, self.lexer.src()).with_tokens(found, &[]));
+    }
+
+    fn expect_token(&mut self, expected: Token) -> Result<Span, RonError> {
+        return match self.next_token()? {
+            Some((tok, span)) if tok == expected => Ok(span),
+            x => Err(self.unexpected(x, &[expected.kind()])),
+        };
+    }
+
+    fn try_value(&mut self) -> Result<Option<(RonEvent<'a>, Span)>, RonError> {
+        if let Some(x) = self.try_struct()? {
+            return Ok(Some(x));
         }
         
-        if let Some(x) = self.try_tuple() {
-            return Some(x);
+        if let Some(x) = self.try_tuple()? {
+            return Ok(Some(x));
         }
         
-        if let Some(x) = self.try_primitive() {
-            return Some(x);
+        if let Some(x) = self.try_primitive()? {
+            return Ok(Some(x));
         }
         
-        if let Some(x) = self.try_optional_some() {
-            return Some(x);
+        if let Some(x) = self.try_optional_some()? {
+            return Ok(Some(x));
         }
 
-        if let Some(x) = self.try_map() {
-            return Some(x);
+        if let Some(x) = self.try_map()? {
+            return Ok(Some(x));
         }
         
         return self.try_list();
     }
 
-    fn try_struct(&mut self) -> Option<RonEvent<'a>> {
-        let ident_tok = self.next_token()?;
-        let name = if let Token::Ident(a, b) = ident_tok {
+    fn try_struct(&mut self) -> Result<Option<(RonEvent<'a>, Span)>, RonError> {
+        let Some(ident_tok) = self.next_token()? else {
+            return Ok(None);
+        };
+        let name = if let (Token::Ident(a, b), _) = ident_tok {
             Some(self.lexer.get_string(a, b))
         } else {
-            self.tok_queue.insert(0, ident_tok);
+            self.tok_queue.push(ident_tok);
             None
         };
         
-        let Some(paren_tok) = self.next_token() else {
-            if name.is_some() { self.tok_queue.insert(0, ident_tok) }
-            return None;
+        let Some(paren_tok) = self.next_token()? else {
+            if name.is_some() { self.tok_queue.push(ident_tok) }
+            return Ok(None);
         };
 
-        let Token::LParen = paren_tok else {
-            self.tok_queue.insert(0, paren_tok);
-            if name.is_some() { self.tok_queue.insert(0, ident_tok) }
-            return None;
+        let (Token::LParen, paren_span) = paren_tok else {
+            self.tok_queue.push(paren_tok);
+            if name.is_some() { self.tok_queue.push(ident_tok) }
+            return Ok(None);
         };
 
-        let Some(field_tok) = self.next_token() else {
-            self.tok_queue.insert(0, paren_tok);
-            if name.is_some() { self.tok_queue.insert(0, ident_tok) }
-            return None;
+        let Some(field_tok) = self.next_token()? else {
+            self.tok_queue.push(paren_tok);
+            if name.is_some() { self.tok_queue.push(ident_tok) }
+            return Ok(None);
         };
 
-        let Token::Ident(_, _) = field_tok else {
-            self.tok_queue.insert(0, field_tok);
-            self.tok_queue.insert(0, paren_tok);
-            if name.is_some() { self.tok_queue.insert(0, ident_tok) }
-            return None;
+        let (Token::Ident(_, _), _) = field_tok else {
+            self.tok_queue.push(field_tok);
+            self.tok_queue.push(paren_tok);
+            if name.is_some() { self.tok_queue.push(ident_tok) }
+            return Ok(None);
         };
 
-        let Some(colon_tok) = self.next_token() else {
-            self.tok_queue.insert(0, field_tok);
-            self.tok_queue.insert(0, paren_tok);
-            if name.is_some() { self.tok_queue.insert(0, ident_tok) }
-            return None;
+        let Some(colon_tok) = self.next_token()? else {
+            self.tok_queue.push(field_tok);
+            self.tok_queue.push(paren_tok);
+            if name.is_some() { self.tok_queue.push(ident_tok) }
+            return Ok(None);
         };
 
-        let Token::Colon = colon_tok else {
-            self.tok_queue.insert(0, colon_tok);
-            self.tok_queue.insert(0, field_tok);
-            self.tok_queue.insert(0, paren_tok);
-            if name.is_some() { self.tok_queue.insert(0, ident_tok) }
-            return None;
+        let (Token::Colon, _) = colon_tok else {
+            self.tok_queue.push(colon_tok);
+            self.tok_queue.push(field_tok);
+            self.tok_queue.push(paren_tok);
+            if name.is_some() { self.tok_queue.push(ident_tok) }
+            return Ok(None);
         };
 
-        self.tok_queue.insert(0, colon_tok);
-        self.tok_queue.insert(0, field_tok);
+        self.tok_queue.push(colon_tok);
+        self.tok_queue.push(field_tok);
 
-        self.stack.push(InternalState::Struct { name });
-        return Some(RonEvent::StructStart { name });
+        let span = if name.is_some() { ident_tok.1.to(paren_span) } else { paren_span };
+        self.stack.push(InternalState::Struct { name, implicit: false });
+        self.first_element = true;
+        return Ok(Some((RonEvent::StructStart { name }, span)));
     }
 
-    fn try_tuple(&mut self) -> Option<RonEvent<'a>> {
-        let ident_tok = self.next_token()?;
-        let name = if let Token::Ident(a, b) = ident_tok {
+    fn try_tuple(&mut self) -> Result<Option<(RonEvent<'a>, Span)>, RonError> {
+        let Some(ident_tok) = self.next_token()? else {
+            return Ok(None);
+        };
+        let name = if let (Token::Ident(a, b), _) = ident_tok {
             Some(self.lexer.get_string(a, b))
         } else {
-            self.tok_queue.insert(0, ident_tok);
+            self.tok_queue.push(ident_tok);
             None
         };
         
-        if let Some(tok) = self.next_token() {
-            if let Token::LParen = tok {
+        if let Some(tok) = self.next_token()? {
+            if let (Token::LParen, paren_span) = tok {
+                let span = if name.is_some() { ident_tok.1.to(paren_span) } else { paren_span };
                 self.stack.push(InternalState::Tuple { name });
-                return Some(RonEvent::TupleStart { name });
+                self.first_element = true;
+                return Ok(Some((RonEvent::TupleStart { name }, span)));
             }
-            self.tok_queue.insert(0, tok);
-            if name.is_some() { self.tok_queue.insert(0, ident_tok) };
-            return None;
+            self.tok_queue.push(tok);
+            if name.is_some() { self.tok_queue.push(ident_tok) };
+            return Ok(None);
         } else {
-            if name.is_some() { self.tok_queue.insert(0, ident_tok) };
-            return None;
+            if name.is_some() { self.tok_queue.push(ident_tok) };
+            return Ok(None);
         }
     }
 
-    fn try_list(&mut self) -> Option<RonEvent<'a>> {
-        let tok = self.next_token()?;
-        if let Token::LBracket = tok {
+    fn try_list(&mut self) -> Result<Option<(RonEvent<'a>, Span)>, RonError> {
+        let Some(tok) = self.next_token()? else {
+            return Ok(None);
+        };
+        if let (Token::LBracket, span) = tok {
             self.stack.push(InternalState::List);
-            return Some(RonEvent::ListStart);
+            self.first_element = true;
+            return Ok(Some((RonEvent::ListStart, span)));
         }
-        self.tok_queue.insert(0, tok);
-        return None;
+        self.tok_queue.push(tok);
+        return Ok(None);
     }
 
-    fn try_map(&mut self) -> Option<RonEvent<'a>> {
-        let tok = self.next_token()?;
-        if let Token::LCurly = tok {
-            self.stack.push(InternalState::Map);
-            return Some(RonEvent::MapStart);
+    fn try_map(&mut self) -> Result<Option<(RonEvent<'a>, Span)>, RonError> {
+        let Some(tok) = self.next_token()? else {
+            return Ok(None);
+        };
+        if let (Token::LCurly, span) = tok {
+            self.stack.push(InternalState::Map { keys: Vec::new() });
+            self.first_element = true;
+            return Ok(Some((RonEvent::MapStart, span)));
         }
-        self.tok_queue.insert(0, tok);
-        return None;
+        self.tok_queue.push(tok);
+        return Ok(None);
     }
 
-    fn try_optional_some(&mut self) -> Option<RonEvent<'a>> {
-        let tok = self.next_token()?;
-        if let Token::SomeOptValue = tok {
-            assert!(self.next_token() == Some(Token::LParen), "Expected a '(' after a 'Some'!");
+    fn try_optional_some(&mut self) -> Result<Option<(RonEvent<'a>, Span)>, RonError> {
+        let Some(tok) = self.next_token()? else {
+            return Ok(None);
+        };
+        if let (Token::SomeOptValue, some_span) = tok {
+            let paren_span = self.expect_token(Token::LParen)?;
 
             self.stack.push(InternalState::OptionalSomeValue);
-            return Some(RonEvent::OptionalSomeValue);
+            return Ok(Some((RonEvent::OptionalSomeValue, some_span.to(paren_span))));
         }
-        self.tok_queue.insert(0, tok);
-        return None;
+        self.tok_queue.push(tok);
+        return Ok(None);
     }
 
-    fn try_primitive(&mut self) -> Option<RonEvent<'a>> {
-        let tok = self.next_token()?;
-        match tok {
-            Token::Ident(a, b) => Some(RonEvent::Primitive(RonPrimitive::Enum(self.lexer.get_string(a, b)))),
-            Token::Bool(x) => Some(RonEvent::Primitive(RonPrimitive::Bool(x))),
-            Token::Float(x) => Some(RonEvent::Primitive(RonPrimitive::Float(x))),
-            Token::Int(x) => Some(RonEvent::Primitive(RonPrimitive::Int(x))),
-            Token::Char(x) => Some(RonEvent::Primitive(RonPrimitive::Char(x))),
-            Token::Str(a, b) => Some(RonEvent::Primitive(RonPrimitive::Str(self.lexer.get_string(a, b)))),
-            Token::NoneOptValue => Some(RonEvent::Primitive(RonPrimitive::NoneOptValue)),
+    fn try_primitive(&mut self) -> Result<Option<(RonEvent<'a>, Span)>, RonError> {
+        let Some((tok, span)) = self.next_token()? else {
+            return Ok(None);
+        };
+        let primitive = match tok {
+            Token::Ident(a, b) => {
+                let ident = self.lexer.get_string(a, b);
+                if !self.options.syntax.lowercase_enums && !ident.starts_with(|x: char| x.is_uppercase()) {
+                    return Err(RonError::at(ErrorKind::LowercaseEnum(String::from(ident)), span, self.lexer.src()));
+                }
+                RonPrimitive::Enum(ident)
+            },
+            Token::Bool(x) => RonPrimitive::Bool(x),
+            Token::Float(x) => RonPrimitive::Float(x),
+            Token::Int(x) => RonPrimitive::Int(x),
+            Token::TypedFloat(x, suffix) => RonPrimitive::TypedFloat(x, suffix),
+            Token::TypedInt(x, suffix) => RonPrimitive::TypedInt(x, suffix),
+            Token::Char(x) => RonPrimitive::Char(x),
+            Token::Str(a, b) => RonPrimitive::Str(self.lexer.get_str(a, b)),
+            Token::Bytes(a, b) => RonPrimitive::Bytes(self.lexer.get_bytes(a, b)),
+            Token::NoneOptValue => RonPrimitive::NoneOptValue,
             _ => {
-                self.tok_queue.insert(0, tok);
-                None
+                self.tok_queue.push((tok, span));
+                return Ok(None);
             },
+        };
+        return Ok(Some((RonEvent::Primitive(primitive), span)));
+    }
+
+    fn next_token(&mut self) -> Result<Option<(Token, Span)>, RonError> {
+        if let Some(tok) = self.tok_queue.pop() {
+            return Ok(Some(tok));
+        }
+        loop {
+            match self.lexer.next_token()? {
+                Some((Token::Comment(a, b), span)) => self.comments.push_back((self.lexer.get_string(a, b), span)),
+                x => return Ok(x),
+            }
         }
     }
+}
+
+/// State of a [`RonDeserializer`] saved by [`RonDeserializer::checkpoint`].
+#[derive(Clone)]
+pub struct Checkpoint<'a> {
+    state: RonDeserializer<'a>,
+}
 
-    fn next_token(&mut self) -> Option<Token> {
-        if self.tok_queue.len() > 0 {
-            return Some(self.tok_queue.remove(0));
+/// Iterator over the top level values of a document, made by [`RonDeserializer::documents`].
+pub struct Documents<'d, 'a, T> {
+    de: &'d mut RonDeserializer<'a>,
+    failed: bool,
+    marker: PhantomData<T>,
+}
+
+impl<'a, T: RonDeserialize<'a>> Iterator for Documents<'_, 'a, T> {
+    type Item = Result<T, RonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
         }
-        return self.lexer.next_token();
+        let value = match self.de.peek_event() {
+            Ok(RonEvent::Eof) => return None,
+            Ok(_) => T::deserialize(self.de).and_then(|x| {
+                if *self.de.peek_event()? == RonEvent::DocumentEnd {
+                    self.de.next_event()?;
+                }
+                return Ok(x);
+            }),
+            Err(err) => Err(err),
+        };
+        self.failed = value.is_err();
+        return Some(value);
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RonEvent<'a> {
     /// Signals that the next events will contain its wrapped value. There's no end event.
     OptionalSomeValue,
@@ -333,13 +1042,90 @@ pub enum RonEvent<'a> {
     /// Signals the end of a list. End event is 'ListEnd'.
     ListEnd,
 
+    /// Only produced when comments are enabled. Holds the whole comment, delimiters included.
+    Comment(&'a str),
+
+    /// Only produced in recovery mode. Stands in for the part of the document that couldn't be parsed, which may be
+    /// a whole value or the rest of a container. Events continue after the point the parser resynchronized at.
+    Error(RonError),
+
+    /// Only produced when documents are enabled. Signals the end of a top level value.
+    DocumentEnd,
+
     /// Signals the end of the deserialization.
     Eof
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RonPrimitive<'a> {
-    NoneOptValue, Int(i64), Float(f64), Bool(bool), Char(char), Str(&'a str), Enum(&'a str),
+    NoneOptValue, Int(i128), Float(f64), Bool(bool), Char(char), Enum(&'a str),
+    /// A string, with its escapes decoded. Only owned if it had escapes.
+    Str(Cow<'a, str>),
+    /// A byte string, like `b"\x00ab"`. Only owned if it had escapes.
+    Bytes(Cow<'a, [u8]>),
+    /// A number with a type suffix, like `42u8` or `1.5f32`. Integers always fit in their type, and are held as an
+    /// `i128` like the ones without a suffix, so `u128`s past `i128::MAX` can't be read.
+    TypedInt(i128, NumberSuffix), TypedFloat(f64, NumberSuffix),
+}
+
+macro_rules! int_accessors {
+    ($($name:ident: $ty:ty = $suffix:ident),*) => {$(
+        #[doc = concat!("The integer as a `", stringify!($ty), "`, failing if it doesn't fit in one or has another suffix.")]
+        pub fn $name(&self) -> Result<$ty, ErrorKind> {
+            return match *self {
+                RonPrimitive::Int(x) | RonPrimitive::TypedInt(x, NumberSuffix::$suffix) => {
+                    <$ty>::try_from(x).map_err(|_| ErrorKind::InvalidValue(format!("{x} doesn't fit in a {}", stringify!($ty))))
+                },
+                RonPrimitive::TypedInt(..) | RonPrimitive::TypedFloat(..) => Err(self.invalid_type(concat!("a ", stringify!($ty)))),
+                _ => Err(self.invalid_type("an integer")),
+            };
+        }
+    )*};
+}
+
+/// Range checked conversions for decoders. Numbers with a suffix are only accepted as their own type. Errors don't
+/// have a location, so they're usually passed to [`RonDeserializer::error`] to point at the primitive that was just
+/// read.
+/// ```
+/// # use light_ron::deserial::{RonDeserializer, RonEvent};
+/// let mut de = RonDeserializer::new("[300]");
+/// de.next_event().unwrap();
+/// let RonEvent::Primitive(x) = de.next_event().unwrap() else { panic!() };
+/// assert_eq!(x.as_u16(), Ok(300));
+/// let err = de.error(x.as_u8().unwrap_err());
+/// assert_eq!(err.to_string(), "300 doesn't fit in a u8 at line 1, column 2\n  |\n1 | [300]\n  |  ^^^");
+/// ```
+impl<'a> RonPrimitive<'a> {
+    int_accessors!(as_i8: i8 = I8, as_i16: i16 = I16, as_i32: i32 = I32, as_i64: i64 = I64, as_i128: i128 = I128, as_isize: isize = Isize);
+    int_accessors!(as_u8: u8 = U8, as_u16: u16 = U16, as_u32: u32 = U32, as_u64: u64 = U64, as_u128: u128 = U128, as_usize: usize = Usize);
+
+    /// The number as an `f64`. Integers without a suffix are accepted too.
+    pub fn as_f64(&self) -> Result<f64, ErrorKind> {
+        return match *self {
+            RonPrimitive::Float(x) | RonPrimitive::TypedFloat(x, NumberSuffix::F64) => Ok(x),
+            RonPrimitive::Int(x) => Ok(x as f64),
+            RonPrimitive::TypedInt(..) | RonPrimitive::TypedFloat(..) => Err(self.invalid_type("a f64")),
+            _ => Err(self.invalid_type("a number")),
+        };
+    }
+
+    /// The number as an `f32`, failing if it's finite but too big for one. Precision is still lost silently.
+    pub fn as_f32_checked(&self) -> Result<f32, ErrorKind> {
+        let x = match *self {
+            RonPrimitive::Float(x) | RonPrimitive::TypedFloat(x, NumberSuffix::F32) => x,
+            RonPrimitive::Int(x) => x as f64,
+            RonPrimitive::TypedInt(..) | RonPrimitive::TypedFloat(..) => return Err(self.invalid_type("a f32")),
+            _ => return Err(self.invalid_type("a number")),
+        };
+        if x.is_finite() && (x as f32).is_infinite() {
+            return Err(ErrorKind::InvalidValue(format!("{x} doesn't fit in a f32")));
+        }
+        return Ok(x as f32);
+    }
+
+    fn invalid_type(&self, expected: &str) -> ErrorKind {
+        return ErrorKind::InvalidType { expected: String::from(expected), found: describe(&RonEvent::Primitive(self.clone())) };
+    }
 }
 
 
@@ -351,156 +1137,169 @@ mod tests {
     fn none_test() {
         let mut parser = RonDeserializer::new("None");
 
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::NoneOptValue));
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::NoneOptValue));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
    
     #[test]
     fn bool_true_test() {
         let mut parser = RonDeserializer::new("true");
 
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Bool(true)));
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Bool(true)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn bool_false_test() {
         let mut parser = RonDeserializer::new("false");
 
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Bool(false)));
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Bool(false)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn int_test() {
         let mut parser = RonDeserializer::new("123");
 
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(123)));
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(123)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn float_test() {
         let mut parser = RonDeserializer::new("123.0");
 
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Float(123.0)));
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Float(123.0)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn char_test() {
         let mut parser = RonDeserializer::new("'a'");
 
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Char('a')));
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Char('a')));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn str_test() {
         let mut parser = RonDeserializer::new("\"abc\"");
 
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Str("abc")));
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("abc"))));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn enum_test() {
         let mut parser = RonDeserializer::new("SomeEnum");
 
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Enum("SomeEnum")));
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Enum("SomeEnum")));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn some_test() {
         let mut parser = RonDeserializer::new("Some(420)");
 
-        assert_eq!(parser.next_event(), RonEvent::OptionalSomeValue);
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(420)));
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::OptionalSomeValue);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(420)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn peek_test() {
+        let mut parser = RonDeserializer::new("[1]");
+
+        assert_eq!(parser.peek_event().unwrap(), &RonEvent::ListStart);
+        assert_eq!(parser.peek_event().unwrap(), &RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.peek_event().unwrap(), &RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn list_test() {
         let mut parser = RonDeserializer::new("[1, 2, None, 4, EnumVal, Some(6),]");
-        assert_eq!(parser.next_event(), RonEvent::ListStart);
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(1)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(2)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::NoneOptValue));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(4)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Enum("EnumVal")));
-        assert_eq!(parser.next_event(), RonEvent::OptionalSomeValue);
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(6)));
-        assert_eq!(parser.next_event(), RonEvent::ListEnd);
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::NoneOptValue));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(4)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Enum("EnumVal")));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::OptionalSomeValue);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(6)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn unnamed_tuple_test() {
         let mut parser = RonDeserializer::new("(1, 2, 3)");
 
-        assert_eq!(parser.next_event(), RonEvent::TupleStart { name: None });
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(1)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(2)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(3)));
-        assert_eq!(parser.next_event(), RonEvent::TupleEnd { name: None });
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::TupleStart { name: None });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(3)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::TupleEnd { name: None });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn named_tuple_test() {
         let mut parser = RonDeserializer::new("Named(1, 2, 3)");
 
-        assert_eq!(parser.next_event(), RonEvent::TupleStart { name: Some("Named") });
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(1)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(2)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(3)));
-        assert_eq!(parser.next_event(), RonEvent::TupleEnd { name: Some("Named") });
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::TupleStart { name: Some("Named") });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(3)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::TupleEnd { name: Some("Named") });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn unnamed_struct_test() {
         let mut parser = RonDeserializer::new("(first: 1, second: 2, third: 3)");
 
-        assert_eq!(parser.next_event(), RonEvent::StructStart { name: None });
-        assert_eq!(parser.next_event(), RonEvent::NamedField("first"));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(1)));
-        assert_eq!(parser.next_event(), RonEvent::NamedField("second"));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(2)));
-        assert_eq!(parser.next_event(), RonEvent::NamedField("third"));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(3)));
-        assert_eq!(parser.next_event(), RonEvent::StructEnd { name: None });
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructStart { name: None });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("first"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("second"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("third"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(3)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructEnd { name: None });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn named_struct_test() {
         let mut parser = RonDeserializer::new("Named(first: 1, second: 2, third: 3)");
 
-        assert_eq!(parser.next_event(), RonEvent::StructStart { name: Some("Named") });
-        assert_eq!(parser.next_event(), RonEvent::NamedField("first"));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(1)));
-        assert_eq!(parser.next_event(), RonEvent::NamedField("second"));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(2)));
-        assert_eq!(parser.next_event(), RonEvent::NamedField("third"));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(3)));
-        assert_eq!(parser.next_event(), RonEvent::StructEnd { name: Some("Named") });
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructStart { name: Some("Named") });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("first"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("second"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("third"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(3)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructEnd { name: Some("Named") });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
     fn map_test() {
         let mut parser = RonDeserializer::new(r#"{ "red": 0, "green": 1, "blue": 2 }"#);
-        assert_eq!(parser.next_event(), RonEvent::MapStart);
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Str("red")));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(0)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Str("green")));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(1)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Str("blue")));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(2)));
-        assert_eq!(parser.next_event(), RonEvent::MapEnd);
-        assert_eq!(parser.next_event(), RonEvent::Eof);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::MapStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("red"))));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(0)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("green"))));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("blue"))));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::MapEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
     }
 
     #[test]
@@ -519,34 +1318,514 @@ mod tests {
         )
         "#);
 
-        assert_eq!(parser.next_event(), RonEvent::StructStart { name: Some("Player") });
-        assert_eq!(parser.next_event(), RonEvent::NamedField("name"));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Str("SomePlayer69")));
-        assert_eq!(parser.next_event(), RonEvent::NamedField("pos"));
-        assert_eq!(parser.next_event(), RonEvent::TupleStart { name: None });
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Float(0.0)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Float(0.0)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Float(0.0)));
-        assert_eq!(parser.next_event(), RonEvent::TupleEnd { name: None });
-        assert_eq!(parser.next_event(), RonEvent::NamedField("factions"));
-        assert_eq!(parser.next_event(), RonEvent::MapStart);
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Str("pirates")));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(-100)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Str("alliance")));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(20)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Str("crabs")));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(30)));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Str("neutral")));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Int(0)));
-        assert_eq!(parser.next_event(), RonEvent::MapEnd);
-        assert_eq!(parser.next_event(), RonEvent::NamedField("powers"));
-        assert_eq!(parser.next_event(), RonEvent::ListStart);
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Enum("Fire")));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Enum("Water")));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Enum("Ice")));
-        assert_eq!(parser.next_event(), RonEvent::Primitive(RonPrimitive::Enum("Air")));
-        assert_eq!(parser.next_event(), RonEvent::ListEnd);
-        assert_eq!(parser.next_event(), RonEvent::StructEnd { name: Some("Player") });
-        assert_eq!(parser.next_event(), RonEvent::Eof);
-    }
-}
\ No newline at end of file
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructStart { name: Some("Player") });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("name"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("SomePlayer69"))));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("pos"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::TupleStart { name: None });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Float(0.0)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Float(0.0)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Float(0.0)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::TupleEnd { name: None });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("factions"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::MapStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("pirates"))));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(-100)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("alliance"))));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(20)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("crabs"))));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(30)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("neutral"))));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(0)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::MapEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("powers"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Enum("Fire")));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Enum("Water")));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Enum("Ice")));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Enum("Air")));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructEnd { name: Some("Player") });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn unexpected_token_test() {
+        let mut parser = RonDeserializer::new("(1, 2]");
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::TupleStart { name: None });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        let err = parser.next_event().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::ExpectedValue);
+        assert_eq!(err.found(), Some(TokenKind::RBracket));
+        assert_eq!(err.span(), Some(Span::new(5, 6)));
+    }
+
+    #[test]
+    fn unexpected_eof_test() {
+        let mut parser = RonDeserializer::new("[1");
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        let err = parser.next_event().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::UnexpectedEof);
+        assert_eq!(err.found(), None);
+        assert_eq!(err.expected(), &[TokenKind::Comma, TokenKind::RBracket]);
+        assert_eq!(err.span(), Some(Span::new(2, 2)));
+    }
+
+    #[test]
+    fn missing_colon_test() {
+        let mut parser = RonDeserializer::new("{ \"a\" 1 }");
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::MapStart);
+        let err = parser.next_event().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::UnexpectedToken);
+        assert_eq!(err.found(), Some(TokenKind::Int));
+        assert_eq!(err.expected(), &[TokenKind::Colon]);
+        assert_eq!(err.to_string(), "Expected ':', found integer at line 1, column 7\n  |\n1 | { \"a\" 1 }\n  |       ^");
+    }
+
+    #[test]
+    fn stray_token_test() {
+        let mut parser = RonDeserializer::new(")");
+
+        assert_eq!(parser.next_event().unwrap_err().kind(), &ErrorKind::ExpectedValue);
+    }
+
+    #[test]
+    fn event_span_test() {
+        let mut parser = RonDeserializer::new("Named(a: Some(1))");
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructStart { name: Some("Named") });
+        assert_eq!(parser.last_span(), Span::new(0, 6));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("a"));
+        assert_eq!(parser.last_span(), Span::new(6, 7));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::OptionalSomeValue);
+        assert_eq!(parser.last_span(), Span::new(9, 14));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.last_span(), Span::new(14, 15));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructEnd { name: Some("Named") });
+        assert_eq!(parser.last_span(), Span::new(16, 17));
+    }
+
+    #[test]
+    fn decoder_error_test() {
+        let err = u8::deserialize(&mut RonDeserializer::new("[300]")).unwrap_err();
+        assert_eq!(err.span(), Some(Span::new(0, 1)));
+
+        let err = Vec::<u8>::deserialize(&mut RonDeserializer::new("[1, 300]")).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::InvalidValue(String::from("300 doesn't fit in a u8")));
+        assert_eq!(err.span(), Some(Span::new(4, 7)));
+    }
+
+    fn recovering(src: &str) -> RonDeserializer<'_> {
+        return RonDeserializer::with_options(src, DeserializerOptions { recover_errors: true, ..Default::default() });
+    }
+
+    fn is_error(event: RonEvent) -> bool {
+        return matches!(event, RonEvent::Error(_));
+    }
+
+    #[test]
+    fn recover_list_test() {
+        let mut parser = recovering("[1, :, 3]");
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert!(is_error(parser.next_event().unwrap()));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(3)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn recover_struct_test() {
+        let mut parser = recovering("(a: 1, b: , c: Some(], d: 4)");
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructStart { name: None });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("a"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("b"));
+        let RonEvent::Error(err) = parser.next_event().unwrap() else { panic!() };
+        assert_eq!(err.span(), Some(Span::new(10, 11)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("c"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::OptionalSomeValue);
+        assert!(is_error(parser.next_event().unwrap()));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("d"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(4)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructEnd { name: None });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn recover_mismatched_delimiter_test() {
+        let mut parser = recovering("(1, 2]");
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::TupleStart { name: None });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert!(is_error(parser.next_event().unwrap()));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::TupleEnd { name: None });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn recover_eof_test() {
+        let mut parser = recovering(r#"{"a": [1, 2"#);
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::MapStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("a"))));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert!(is_error(parser.next_event().unwrap()));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::MapEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn recover_terminates_test() {
+        let sources = [
+            ")", "]]]", "(,,,)", "[,]", "{:}", "{\"a\" 1, 2: }", "Some", "Some(", "Some(1", "(a: (b: [", "((((", "[}",
+            "{ \"a\": 1 ]", "(a: 1 b: 2)", "[1 2 3", "(a: \"unterminated)", "Some Some Some", ":::", "(,a:)",
+        ];
+        for src in sources {
+            let mut parser = recovering(src);
+            let mut depth = 0i32;
+            for i in 0.. {
+                assert!(i < 100, "{src:?} didn't terminate");
+                match parser.next_event().unwrap() {
+                    RonEvent::StructStart { .. } | RonEvent::TupleStart { .. } | RonEvent::MapStart | RonEvent::ListStart => depth += 1,
+                    RonEvent::StructEnd { .. } | RonEvent::TupleEnd { .. } | RonEvent::MapEnd | RonEvent::ListEnd => depth -= 1,
+                    RonEvent::Eof => break,
+                    _ => {},
+                }
+            }
+            assert_eq!(depth, 0, "{src:?} isn't balanced");
+        }
+    }
+
+    #[test]
+    fn blank_document_test() {
+        for src in ["", "  \n\t", "\u{feff}", "// a\n/* b */"] {
+            for (recover_errors, documents, implicit) in [(false, false, false), (true, true, false), (false, false, true)] {
+                let syntax = SyntaxOptions { implicit_top_struct: implicit, ..Default::default() };
+                let options = DeserializerOptions { recover_errors, documents, syntax, ..Default::default() };
+                let mut parser = RonDeserializer::with_options(src, options);
+                assert_eq!(parser.next_event().unwrap(), RonEvent::Eof, "{src:?}");
+                assert_eq!(parser.next_event().unwrap(), RonEvent::Eof, "{src:?}");
+            }
+        }
+
+        let mut parser = RonDeserializer::with_options("\u{feff}// a", DeserializerOptions { comments: true, ..Default::default() });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Comment("// a"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+        assert_eq!(crate::from_str::<Vec<i32>>("\u{feff}[1, 2]\n"), Ok(vec![1, 2]));
+        assert_eq!(crate::from_str::<Option<i32>>("").unwrap_err().message(), "Expected an option, found the end of the document");
+    }
+
+    #[test]
+    fn comments_test() {
+        let src = "// Player\nPlayer( /* a */ a: 1, // b\n b: [2 /* c */], /* d */ ) // e";
+        let mut parser = RonDeserializer::with_options(src, DeserializerOptions { comments: true, ..Default::default() });
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Comment("// Player"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructStart { name: Some("Player") });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Comment("/* a */"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("a"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Comment("// b"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::NamedField("b"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Comment("/* c */"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Comment("/* d */"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::StructEnd { name: Some("Player") });
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Comment("// e"));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn comments_skipped_test() {
+        let mut parser = RonDeserializer::new("[1, /* two */ 2] // end");
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn skip_value_test() {
+        let mut parser = RonDeserializer::new("[Some(Named(a: [1, {2: (3)}], b: None)), 4]");
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        parser.skip_value().unwrap();
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(4)));
+        assert_eq!(parser.skip_value().unwrap_err().kind(), &ErrorKind::InvalidType { expected: String::from("a value"), found: String::from("the end of a list") });
+    }
+
+    #[test]
+    fn duplicate_map_key_test() {
+        let options = DeserializerOptions { reject_duplicate_map_keys: true, ..Default::default() };
+        let mut parser = RonDeserializer::with_options("{\"a\": 1, \"b\": {\"a\": 2}, \"a\": 3}", options);
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::MapStart);
+        for _ in 0..2 {
+            parser.next_event().unwrap();
+            parser.skip_value().unwrap();
+        }
+        let err = parser.next_event().unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::DuplicateMapKey { key: String::from("\"a\""), first: Span::new(1, 4) });
+        assert_eq!(err.span(), Some(Span::new(24, 27)));
+        assert_eq!(err.message(), "Duplicate map key \"a\"");
+
+        let mut parser = RonDeserializer::new("{1: 1, 1: 2}");
+        parser.skip_value().unwrap();
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn document_end_test() {
+        let options = DeserializerOptions { documents: true, ..Default::default() };
+        let mut parser = RonDeserializer::with_options("1 [2]\nSome(3)", options);
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::DocumentEnd);
+        assert_eq!(parser.last_span(), Span::new(1, 1));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::DocumentEnd);
+        assert_eq!(parser.last_span(), Span::new(5, 5));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::OptionalSomeValue);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(3)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::DocumentEnd);
+        assert_eq!(parser.last_span(), Span::new(13, 13));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn documents_test() {
+        let src = "(a: 1)\n(a: 2)\n";
+        let mut parser = RonDeserializer::new(src);
+        assert_eq!(parser.documents::<crate::value::RonValue>().count(), 2);
+
+        let mut parser = RonDeserializer::with_options(src, DeserializerOptions { documents: true, ..Default::default() });
+        let values: Vec<_> = parser.documents::<crate::value::RonValue>().map(|x| x.unwrap()["a"].as_i64()).collect();
+        assert_eq!(values, [Some(1), Some(2)]);
+
+        let mut parser = RonDeserializer::new("1 \"x\" 3");
+        let mut documents = parser.documents::<i32>();
+        assert_eq!(documents.next(), Some(Ok(1)));
+        assert!(documents.next().unwrap().is_err());
+        assert_eq!(documents.next(), None);
+
+        assert_eq!(RonDeserializer::new("  ").documents::<i32>().next(), None);
+    }
+
+    #[test]
+    fn raw_value_test() {
+        let mut parser = RonDeserializer::new("[ 1 , Named(a: {1: 2}), Some(Some(x /* in */) /* out */) , ]");
+        parser.next_event().unwrap();
+        assert_eq!(parser.next_raw_value().unwrap(), "1");
+        assert_eq!(parser.next_raw_value().unwrap(), "Named(a: {1: 2})");
+        assert_eq!(parser.next_raw_value().unwrap(), "Some(Some(x /* in */) /* out */)");
+        assert!(parser.next_raw_value().is_err());
+
+        let options = DeserializerOptions { comments: true, ..Default::default() };
+        let mut parser = RonDeserializer::with_options("// before\n(1, 2) // after", options);
+        assert_eq!(parser.next_raw_value().unwrap(), "(1, 2)");
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Comment("// after"));
+
+        let mut parser = RonDeserializer::new("[Some(1]");
+        parser.next_event().unwrap();
+        let err = parser.next_raw_value().unwrap_err();
+        assert_eq!((err.kind(), err.span()), (&ErrorKind::UnexpectedToken, Some(Span::new(7, 8))));
+        assert_eq!(err.message(), "Expected ')', found ']'");
+        let mut parser = RonDeserializer::new("[Some(1 @");
+        parser.next_event().unwrap();
+        assert_eq!(parser.next_raw_value().unwrap_err().span(), Some(Span::new(8, 9)));
+    }
+
+    #[test]
+    fn checkpoint_test() {
+        let options = DeserializerOptions { comments: true, ..Default::default() };
+        let mut parser = RonDeserializer::with_options("[Some(1) /* a */, {2: 3}]", options);
+        parser.next_event().unwrap();
+        parser.next_event().unwrap();
+        let checkpoint = parser.checkpoint();
+        assert_eq!(parser.peek_event().unwrap(), &RonEvent::Primitive(RonPrimitive::Int(1)));
+
+        for _ in 0..2 {
+            parser.restore(&checkpoint);
+            assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+            assert_eq!(parser.next_event().unwrap(), RonEvent::Comment("/* a */"));
+            parser.skip_value().unwrap();
+            assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+            assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+        }
+
+        parser.restore(&checkpoint);
+        assert!(bool::deserialize(&mut parser).is_err());
+        parser.restore(&checkpoint);
+        assert_eq!(i32::deserialize(&mut parser), Ok(1));
+        assert_eq!(parser.last_span(), Span::new(6, 7));
+    }
+
+    #[test]
+    #[should_panic]
+    fn checkpoint_other_source_test() {
+        let checkpoint = RonDeserializer::new("1").checkpoint();
+        RonDeserializer::new("2").restore(&checkpoint);
+    }
+
+    /// Reads all the events of `src`, or the first error.
+    fn read_with(src: &str, syntax: SyntaxOptions) -> Result<Vec<RonEvent<'_>>, RonError> {
+        let mut parser = RonDeserializer::with_options(src, DeserializerOptions { syntax, ..Default::default() });
+        let mut events = Vec::new();
+        loop {
+            match parser.next_event()? {
+                RonEvent::Eof => return Ok(events),
+                x => events.push(x),
+            }
+        }
+    }
+
+    #[test]
+    fn optional_commas_test() {
+        assert_eq!(read_with("[1 2, 3]", SyntaxOptions::default()).unwrap().len(), 5);
+        assert_eq!(read_with("(a: 1 b: [,2])", SyntaxOptions::default()).unwrap().len(), 8);
+
+        let err = read_with("[1 2, 3]", SyntaxOptions::strict()).unwrap_err();
+        assert_eq!(err.message(), "Expected ',' or ']', found integer");
+        assert_eq!(err.span(), Some(Span::new(3, 4)));
+        let err = read_with("{, 1: 2}", SyntaxOptions::strict()).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::ExpectedValue);
+        assert_eq!(err.span(), Some(Span::new(1, 2)));
+        assert!(read_with("[1, [2, 3], (a: 4, b: 5), (6, 7)]", SyntaxOptions::strict()).is_ok());
+    }
+
+    #[test]
+    fn trailing_commas_test() {
+        let syntax = |trailing_commas| SyntaxOptions { trailing_commas, ..SyntaxOptions::strict() };
+        assert!(read_with("[1, 2,]", syntax(TrailingCommas::Allowed)).is_ok());
+        assert!(read_with("[1, 2]", syntax(TrailingCommas::Allowed)).is_ok());
+
+        let err = read_with("(a: [1, 2,])", syntax(TrailingCommas::Forbidden)).unwrap_err();
+        assert_eq!(err.message(), "Expected a value, found ']'");
+        assert!(read_with("(a: [1, 2], b: ())", syntax(TrailingCommas::Forbidden)).is_ok());
+
+        let err = read_with("(a: [1, 2,])", syntax(TrailingCommas::Required)).unwrap_err();
+        assert_eq!(err.message(), "Expected ',', found ')'");
+        assert_eq!(err.span(), Some(Span::new(11, 12)));
+        assert!(read_with("(a: [1, 2,], b: [], c: (),)", syntax(TrailingCommas::Required)).is_ok());
+    }
+
+    #[test]
+    fn trailing_commas_recover_test() {
+        let syntax = SyntaxOptions { trailing_commas: TrailingCommas::Required, ..Default::default() };
+        let mut parser = RonDeserializer::with_options("[[1], 2,]", DeserializerOptions { recover_errors: true, syntax, ..Default::default() });
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(1)));
+        assert!(is_error(parser.next_event().unwrap()));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Int(2)));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+    }
+
+    #[test]
+    fn lowercase_enums_test() {
+        assert_eq!(read_with("{crabs: fire}", SyntaxOptions::default()).unwrap()[1], RonEvent::Primitive(RonPrimitive::Enum("crabs")));
+
+        let err = read_with("[Fire, water]", SyntaxOptions::strict()).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::LowercaseEnum(String::from("water")));
+        assert_eq!(err.message(), "Expected an enum variant starting with an uppercase letter, found water");
+        assert!(read_with("Named(field: Fire, other: Ünicode(1), ok: true)", SyntaxOptions::strict()).is_ok());
+    }
+
+    #[test]
+    fn implicit_top_struct_test() {
+        let events = read_with("name: \"a\",\nlives: Some(3),\n", SyntaxOptions::lenient()).unwrap();
+        assert_eq!(events, [
+            RonEvent::StructStart { name: None },
+            RonEvent::NamedField("name"),
+            RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("a"))),
+            RonEvent::NamedField("lives"),
+            RonEvent::OptionalSomeValue,
+            RonEvent::Primitive(RonPrimitive::Int(3)),
+            RonEvent::StructEnd { name: None },
+        ]);
+        assert_eq!(read_with("Fire", SyntaxOptions::lenient()).unwrap(), [RonEvent::Primitive(RonPrimitive::Enum("Fire"))]);
+        assert_eq!(read_with("(a: 1)", SyntaxOptions::lenient()).unwrap().len(), 4);
+
+        assert!(read_with("a: 1, b: 2", SyntaxOptions::default()).is_err());
+        assert_eq!(read_with("a: 1)", SyntaxOptions::lenient()).unwrap_err().message(), "Expected identifier, found ')'");
+        let syntax = SyntaxOptions { implicit_top_struct: true, ..SyntaxOptions::strict() };
+        assert_eq!(read_with("a: 1)", syntax).unwrap_err().message(), "Expected ',', found ')'");
+        assert_eq!(read_with("a: 1, 2", SyntaxOptions::lenient()).unwrap_err().message(), "Expected identifier, found integer");
+    }
+
+    #[test]
+    fn bytes_test() {
+        let mut parser = RonDeserializer::new(r#"[b"a\x01", b"b"]"#);
+
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListStart);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Bytes(Cow::Owned(vec![b'a', 1]))));
+        assert_eq!(parser.last_span(), Span::new(1, 9));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Primitive(RonPrimitive::Bytes(Cow::Borrowed(b"b"))));
+        assert_eq!(parser.next_event().unwrap(), RonEvent::ListEnd);
+        assert_eq!(parser.next_event().unwrap(), RonEvent::Eof);
+
+        let err = RonDeserializer::new(r#"b"\q""#).next_event().unwrap_err();
+        assert_eq!(err.message(), "Invalid escape sequence \\q");
+    }
+
+    #[test]
+    fn primitive_accessors_test() {
+        assert_eq!(RonPrimitive::Int(-128).as_i8(), Ok(-128));
+        assert_eq!(RonPrimitive::Int(-1).as_u64(), Err(ErrorKind::InvalidValue(String::from("-1 doesn't fit in a u64"))));
+        assert_eq!(RonPrimitive::Int(i64::MAX as i128).as_i128(), Ok(i64::MAX as i128));
+        assert_eq!(RonPrimitive::Int(u64::MAX as i128).as_u64(), Ok(u64::MAX));
+        assert_eq!(RonPrimitive::Int(u64::MAX as i128 + 1).as_u64(), Err(ErrorKind::InvalidValue(String::from("18446744073709551616 doesn't fit in a u64"))));
+        assert_eq!(RonPrimitive::Int(u64::MAX as i128).as_i64(), Err(ErrorKind::InvalidValue(String::from("18446744073709551615 doesn't fit in a i64"))));
+        assert_eq!(RonPrimitive::TypedInt(u64::MAX as i128, NumberSuffix::U64).as_u64(), Ok(u64::MAX));
+        assert_eq!(RonPrimitive::TypedInt(-1, NumberSuffix::I128).as_u128(), Err(ErrorKind::InvalidType { expected: String::from("a u128"), found: String::from("integer -1i128") }));
+        assert_eq!(RonPrimitive::Float(1.5).as_i32(), Err(ErrorKind::InvalidType { expected: String::from("an integer"), found: String::from("float 1.5") }));
+
+        assert_eq!(RonPrimitive::Int(2).as_f64(), Ok(2.0));
+        assert_eq!(RonPrimitive::Float(1.5).as_f32_checked(), Ok(1.5));
+        assert_eq!(RonPrimitive::Float(f64::INFINITY).as_f32_checked(), Ok(f32::INFINITY));
+        assert!(RonPrimitive::Float(1e39).as_f32_checked().is_err());
+        assert!(RonPrimitive::Str(Cow::Borrowed("1")).as_f64().is_err());
+
+        assert_eq!(RonPrimitive::TypedInt(5, NumberSuffix::U8).as_u8(), Ok(5));
+        assert_eq!(RonPrimitive::TypedInt(5, NumberSuffix::U8).as_i32(), Err(ErrorKind::InvalidType { expected: String::from("a i32"), found: String::from("integer 5u8") }));
+        assert_eq!(RonPrimitive::TypedFloat(1.5, NumberSuffix::F32).as_f32_checked(), Ok(1.5));
+        assert!(RonPrimitive::TypedFloat(1.5, NumberSuffix::F32).as_f64().is_err());
+        assert!(RonPrimitive::TypedInt(1, NumberSuffix::I64).as_f64().is_err());
+    }
+
+    #[test]
+    fn position_test() {
+        let mut parser = RonDeserializer::new("(\r\n  ñame: \"x\",\n)");
+        assert_eq!(parser.position(), (1, 1, 0));
+        parser.next_event().unwrap();
+        parser.next_event().unwrap();
+        assert_eq!(parser.position(), (2, 7, 10));
+        parser.peek_event().unwrap();
+        assert_eq!(parser.position(), (2, 7, 10));
+        parser.next_event().unwrap();
+        assert_eq!(parser.position(), (2, 12, 15));
+        parser.next_event().unwrap();
+        assert_eq!(parser.position(), (3, 2, 18));
+    }
+}
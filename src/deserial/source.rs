@@ -0,0 +1,96 @@
+use std::path::Path;
+
+use super::{DeserializerOptions, ErrorKind, RonDeserializer, RonError, Span};
+
+/// Owns the bytes of a document, so deserializers can borrow their strings from it instead of copying them.
+///
+/// Any buffer works, including a memory mapped file. Mapping a file is `unsafe`, since the mapping changes if the file
+/// is changed while it's mapped, so it's left to the caller:
+/// ```ignore
+/// let file = std::fs::File::open("assets.ron")?;
+/// let source = RonSource::new(unsafe { memmap2::Mmap::map(&file)? });
+/// let mut de = source.deserializer()?;
+/// ```
+pub struct RonSource<B = Vec<u8>> {
+    bytes: B,
+}
+
+impl<B: AsRef<[u8]>> RonSource<B> {
+    pub fn new(bytes: B) -> Self {
+        return Self { bytes };
+    }
+
+    /// The document as a string. It's checked to be valid UTF-8 on every call, which is the only pass over the whole
+    /// document that isn't parsing.
+    pub fn as_str(&self) -> Result<&str, RonError> {
+        let bytes = self.bytes.as_ref();
+        return std::str::from_utf8(bytes).map_err(|err| {
+            let valid = &bytes[..err.valid_up_to()];
+            let src = std::str::from_utf8(valid).unwrap_or_default();
+            let span = Span::new(valid.len(), valid.len() + err.error_len().unwrap_or(0));
+            return RonError::at(ErrorKind::Message(String::from("The document isn't valid UTF-8")), span, src);
+        });
+    }
+
+    /// A deserializer that borrows from the source.
+    pub fn deserializer(&self) -> Result<RonDeserializer<'_>, RonError> {
+        return self.deserializer_with_options(DeserializerOptions::default());
+    }
+
+    pub fn deserializer_with_options(&self, options: DeserializerOptions) -> Result<RonDeserializer<'_>, RonError> {
+        return Ok(RonDeserializer::with_options(self.as_str()?, options));
+    }
+
+    pub fn into_inner(self) -> B {
+        return self.bytes;
+    }
+}
+
+impl RonSource {
+    /// Reads the whole file at `path` into memory. Reading without a copy needs a mapping of the file owned by the
+    /// caller, passed to [`RonSource::new`].
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, RonError> {
+        let path = path.as_ref();
+        return match std::fs::read(path) {
+            Ok(x) => Ok(Self::new(x)),
+            Err(err) => Err(RonError::new(format!("Failed to read '{}': {err}", path.display()))),
+        };
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deserial::RonDeserialize;
+
+    #[test]
+    fn borrow_test() {
+        let source = RonSource::new(b"(\"a\", \"b\")".as_slice());
+        let mut de = source.deserializer().unwrap();
+        let value: (&str, &str) = RonDeserialize::deserialize(&mut de).unwrap();
+        assert_eq!(value, ("a", "b"));
+    }
+
+    #[test]
+    fn path_test() {
+        let path = std::env::temp_dir().join(format!("light-ron-source-{}.ron", std::process::id()));
+        std::fs::write(&path, "[1, 2, 3]").unwrap();
+        let source = RonSource::from_path(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let value: Vec<i32> = RonDeserialize::deserialize(&mut source.deserializer().unwrap()).unwrap();
+        assert_eq!(value, [1, 2, 3]);
+
+        let err = RonSource::from_path(&path).err().unwrap();
+        assert!(err.message().starts_with("Failed to read '"));
+    }
+
+    #[test]
+    fn utf8_test() {
+        let source = RonSource::new(b"[1,\n 2, \xff]".to_vec());
+        let err = source.deserializer().err().unwrap();
+        assert_eq!(err.message(), "The document isn't valid UTF-8");
+        assert_eq!(err.span(), Some(Span::new(8, 9)));
+        assert_eq!(err.line_col(), Some((2, 5)));
+    }
+}
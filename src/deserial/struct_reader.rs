@@ -0,0 +1,276 @@
+use std::borrow::Cow;
+
+use super::{unexpected, ErrorKind, RonDeserialize, RonDeserializer, RonError, RonEvent};
+
+/// Reads the fields of a struct one by one, checking them against the declared ones.
+///
+/// By default every declared field is required, and unknown or repeated fields are errors.
+/// ```
+/// # use light_ron::deserial::{RonDeserializer, StructReader};
+/// let mut de = RonDeserializer::new("Player(name: \"a\", hp: 3)");
+/// let mut reader = StructReader::new(&mut de, &["name", "hp", "pet"]).unwrap().optional(&["pet"]);
+///
+/// let (mut name, mut hp, mut pet) = ("", 0, None);
+/// while let Some(field) = reader.next_field().unwrap() {
+///     match field {
+///         "name" => name = reader.read().unwrap(),
+///         "hp" => hp = reader.read().unwrap(),
+///         _ => pet = reader.read().unwrap(),
+///     }
+/// }
+/// assert_eq!((name, hp, pet), ("a", 3, None::<&str>));
+/// ```
+pub struct StructReader<'d, 'a> {
+    de: &'d mut RonDeserializer<'a>,
+    name: Option<&'a str>,
+    fields: &'d [&'static str],
+    seen: Vec<bool>,
+    required: Vec<bool>,
+    deny_unknown: bool,
+    deny_duplicates: bool,
+    /// Set for `()`, whose end has already been consumed.
+    empty: bool,
+    ended: bool,
+}
+
+impl<'d, 'a> StructReader<'d, 'a> {
+    /// Consumes the start of the next struct. `()` is accepted as a struct without fields.
+    pub fn new(de: &'d mut RonDeserializer<'a>, fields: &'d [&'static str]) -> Result<Self, RonError> {
+        return Self::start(de, None, fields);
+    }
+
+    /// Like `new`, also checking that the struct is named `name` as the deserializer's
+    /// [`struct_names`](super::DeserializerOptions::struct_names) option says.
+    pub fn with_name(de: &'d mut RonDeserializer<'a>, name: &str, fields: &'d [&'static str]) -> Result<Self, RonError> {
+        return Self::start(de, Some(name), fields);
+    }
+
+    fn start(de: &'d mut RonDeserializer<'a>, expected: Option<&str>, fields: &'d [&'static str]) -> Result<Self, RonError> {
+        let (name, empty) = match de.next_non_comment()? {
+            RonEvent::StructStart { name } => (name, false),
+            RonEvent::TupleStart { name } if matches!(de.peek_non_comment()?, RonEvent::TupleEnd { .. }) => (name, true),
+            x => return Err(unexpected(de, "a struct", x)),
+        };
+        if let Some(expected) = expected {
+            de.check_struct_name(name, expected)?;
+        }
+        if empty {
+            de.next_non_comment()?;
+        }
+
+        return Ok(Self {
+            de,
+            name,
+            fields,
+            seen: vec![false; fields.len()],
+            required: vec![true; fields.len()],
+            deny_unknown: true,
+            deny_duplicates: true,
+            empty,
+            ended: false,
+        });
+    }
+
+    /// Makes `fields` optional, so they can be missing.
+    pub fn optional(mut self, fields: &[&str]) -> Self {
+        for (i, x) in self.fields.iter().enumerate() {
+            if fields.contains(x) {
+                self.required[i] = false;
+            }
+        }
+        return self;
+    }
+
+    /// Whether fields that weren't declared are errors. If they aren't, they're skipped.
+    pub fn deny_unknown_fields(mut self, deny: bool) -> Self {
+        self.deny_unknown = deny;
+        return self;
+    }
+
+    /// Whether fields that appear more than once are errors. If they aren't, every appearance is returned.
+    pub fn deny_duplicate_fields(mut self, deny: bool) -> Self {
+        self.deny_duplicates = deny;
+        return self;
+    }
+
+    /// The name written before the struct, if any.
+    pub fn name(&self) -> Option<&'a str> {
+        return self.name;
+    }
+
+    /// Whether `field` has been returned by `next_field`.
+    pub fn has_seen(&self, field: &str) -> bool {
+        return self.fields.iter().position(|x| *x == field).is_some_and(|i| self.seen[i]);
+    }
+
+    /// Returns the next declared field, whose value must be read before calling this again.
+    ///
+    /// Returns `None` once the struct ends, after checking that no required field is missing.
+    pub fn next_field(&mut self) -> Result<Option<&'static str>, RonError> {
+        if self.ended {
+            return Ok(None);
+        }
+        if self.empty {
+            self.end()?;
+            return Ok(None);
+        }
+        loop {
+            match self.de.next_non_comment()? {
+                RonEvent::NamedField(name) => {
+                    let Some(i) = self.fields.iter().position(|x| *x == name) else {
+                        if self.deny_unknown {
+                            return Err(self.de.error(ErrorKind::UnknownField(String::from(name))));
+                        }
+                        self.de.skip_value()?;
+                        continue;
+                    };
+                    if self.seen[i] && self.deny_duplicates {
+                        return Err(self.de.error(ErrorKind::DuplicateField(String::from(name))));
+                    }
+                    self.seen[i] = true;
+                    return Ok(Some(self.fields[i]));
+                },
+                RonEvent::StructEnd { .. } => {
+                    self.end()?;
+                    return Ok(None);
+                },
+                x => return Err(unexpected(self.de, "a field", x)),
+            }
+        }
+    }
+
+    /// Reads the value of the field last returned by `next_field`.
+    pub fn read<T: RonDeserialize<'a>>(&mut self) -> Result<T, RonError> {
+        return T::deserialize(self.de);
+    }
+
+    /// Skips the value of the field last returned by `next_field`.
+    pub fn skip(&mut self) -> Result<(), RonError> {
+        return self.de.skip_value();
+    }
+
+    /// The deserializer the struct is read from.
+    pub fn deserializer(&mut self) -> &mut RonDeserializer<'a> {
+        return self.de;
+    }
+
+    fn end(&mut self) -> Result<(), RonError> {
+        self.ended = true;
+        let missing: Vec<_> = (0..self.fields.len()).filter(|&i| self.required[i] && !self.seen[i]).map(|i| String::from(self.fields[i])).collect();
+        if !missing.is_empty() {
+            return Err(self.de.error(ErrorKind::MissingFields(missing)));
+        }
+        return Ok(());
+    }
+}
+
+
+/// The named fields of a type, read one at a time into a partially built value. Implemented by the `RonDeserialize`
+/// derive for structs with named fields, so other structs can flatten them into their own fields.
+pub trait DeserializeFields<'a>: Sized {
+    /// The fields read so far.
+    type Partial;
+
+    /// Names of every field that can be read, including aliases and the fields of flattened structs.
+    fn fields() -> Cow<'static, [&'static str]>;
+
+    /// The value of `Partial` before reading anything.
+    fn partial() -> Self::Partial;
+
+    /// Reads the value of `field`, which `reader` just returned, returning whether it belongs to `Self`.
+    fn read_field(partial: &mut Self::Partial, field: &'static str, reader: &mut StructReader<'_, 'a>) -> Result<bool, RonError>;
+
+    /// Builds the value once the struct ends, or adds the required fields that weren't read to `missing`.
+    fn finish(partial: Self::Partial, missing: &mut Vec<String>) -> Option<Self>;
+
+    /// Reads the remaining fields from `reader` and builds the value, skipping fields `Self` doesn't have.
+    fn read_all(mut reader: StructReader<'_, 'a>) -> Result<Self, RonError> {
+        let mut partial = Self::partial();
+        while let Some(field) = reader.next_field()? {
+            if !Self::read_field(&mut partial, field, &mut reader)? {
+                reader.skip()?;
+            }
+        }
+        let mut missing = Vec::new();
+        return match Self::finish(partial, &mut missing) {
+            Some(value) => Ok(value),
+            None => Err(reader.deserializer().error(ErrorKind::MissingFields(missing))),
+        };
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deserial::{DeserializerOptions, Span, StructNames};
+
+    /// Reads every field of `src` as an `i32`.
+    fn read_all<'s>(src: &'s str, configure: impl for<'d> FnOnce(StructReader<'d, 's>) -> StructReader<'d, 's>) -> Result<Vec<(&'static str, i32)>, RonError> {
+        let mut de = RonDeserializer::new(src);
+        let mut reader = configure(StructReader::new(&mut de, &["a", "b", "c"])?);
+        let mut fields = Vec::new();
+        while let Some(x) = reader.next_field()? {
+            fields.push((x, reader.read()?));
+        }
+        return Ok(fields);
+    }
+
+    #[test]
+    fn read_test() {
+        assert_eq!(read_all("(b: 2, a: 1, c: 3)", |x| x), Ok(vec![("b", 2), ("a", 1), ("c", 3)]));
+        assert_eq!(read_all("Named(a: 1)", |x| x.optional(&["b", "c"])), Ok(vec![("a", 1)]));
+        assert_eq!(read_all("()", |x| x.optional(&["a", "b", "c"])), Ok(vec![]));
+    }
+
+    #[test]
+    fn unknown_test() {
+        let err = read_all("(a: 1, d: 4, b: 2, c: 3)", |x| x).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::UnknownField(String::from("d")));
+        assert_eq!(err.span(), Some(Span::new(7, 8)));
+
+        assert_eq!(read_all("(a: 1, d: [4], b: 2, c: 3)", |x| x.deny_unknown_fields(false)), Ok(vec![("a", 1), ("b", 2), ("c", 3)]));
+    }
+
+    #[test]
+    fn duplicate_test() {
+        let err = read_all("(a: 1, b: 2, a: 3, c: 3)", |x| x).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::DuplicateField(String::from("a")));
+
+        assert_eq!(read_all("(a: 1, a: 2, b: 2, c: 3)", |x| x.deny_duplicate_fields(false)).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn missing_test() {
+        let err = read_all("Named(b: 1)", |x| x).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::MissingFields(vec![String::from("a"), String::from("c")]));
+        assert_eq!(err.message(), "Missing fields a, c");
+        assert_eq!(err.span(), Some(Span::new(10, 11)));
+
+        assert_eq!(read_all("()", |x| x).unwrap_err().kind(), &ErrorKind::MissingFields(vec![String::from("a"), String::from("b"), String::from("c")]));
+    }
+
+    #[test]
+    fn name_test() {
+        let read = |src, struct_names| {
+            let mut de = RonDeserializer::with_options(src, DeserializerOptions { struct_names, ..Default::default() });
+            return StructReader::with_name(&mut de, "Vec2", &[]).map(|x| x.name());
+        };
+        assert_eq!(read("Vec3()", StructNames::Ignore), Ok(Some("Vec3")));
+        assert_eq!(read("()", StructNames::Verify), Ok(None));
+        assert_eq!(read("Vec2()", StructNames::Require), Ok(Some("Vec2")));
+
+        let err = read("Vec3(x: 1)", StructNames::Verify).unwrap_err();
+        assert_eq!(err.kind(), &ErrorKind::StructName { expected: String::from("Vec2"), found: Some(String::from("Vec3")) });
+        assert_eq!(err.span(), Some(Span::new(0, 5)));
+        let err = read("()", StructNames::Require).unwrap_err();
+        assert_eq!(err.message(), "Expected struct Vec2, found a struct without a name");
+        assert_eq!(err.span(), Some(Span::new(0, 1)));
+    }
+
+    #[test]
+    fn not_a_struct_test() {
+        assert!(read_all("[1]", |x| x).is_err());
+        assert!(read_all("(1, 2)", |x| x).is_err());
+    }
+}
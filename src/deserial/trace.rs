@@ -0,0 +1,112 @@
+use std::fmt;
+use std::sync::Arc;
+
+use super::RonError;
+
+/// What a [`Tracer`] is told about, as the deserializer reads. Offsets are in bytes from the start of the source.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TraceEvent<'e> {
+    /// The first event of the source is about to be read. Holds the length of the source.
+    Start { len: usize },
+    /// A container was opened. `kind` is `"struct"`, `"tuple"`, `"map"` or `"list"`.
+    Enter { kind: &'static str, name: Option<&'e str>, offset: usize },
+    /// A container was closed, with `offset` right after its closing delimiter.
+    Exit { kind: &'static str, offset: usize },
+    /// A syntax error, returned or recovered from. Errors of decoders aren't seen by the deserializer.
+    Error(&'e RonError),
+    /// The end of the source was reached.
+    End { offset: usize },
+}
+
+/// A function called with every [`TraceEvent`] of a deserializer, set in [`DeserializerOptions::tracer`].
+///
+/// Timing and output are left to the function, so it can forward the events to any logging or profiling tool, like
+/// spans of the `tracing` crate around each document:
+/// ```ignore
+/// let tracer = Tracer::new(|event| match *event {
+///     TraceEvent::Start { len } => tracing::debug!(len, "parse start"),
+///     TraceEvent::Error(err) => tracing::warn!(offset = err.span().map(|x| x.start), "{err}"),
+///     TraceEvent::End { offset } => tracing::debug!(offset, "parse end"),
+///     _ => {},
+/// });
+/// ```
+///
+/// [`DeserializerOptions::tracer`]: super::DeserializerOptions::tracer
+#[derive(Clone)]
+pub struct Tracer(Arc<dyn Fn(&TraceEvent) + Send + Sync>);
+
+impl Tracer {
+    pub fn new(f: impl Fn(&TraceEvent) + Send + Sync + 'static) -> Self {
+        return Self(Arc::new(f));
+    }
+
+    pub(crate) fn trace(&self, event: &TraceEvent) {
+        (self.0)(event);
+    }
+}
+
+impl fmt::Debug for Tracer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str("Tracer");
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::deserial::{DeserializerOptions, RonDeserializer, RonEvent};
+
+    fn trace(src: &str, recover_errors: bool) -> Vec<String> {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let sink = log.clone();
+        let tracer = Tracer::new(move |event| {
+            let line = match *event {
+                TraceEvent::Start { len } => format!("start {len}"),
+                TraceEvent::Enter { kind, name, offset } => format!("enter {kind} {name:?} {offset}"),
+                TraceEvent::Exit { kind, offset } => format!("exit {kind} {offset}"),
+                TraceEvent::Error(err) => format!("error {:?}", err.span()),
+                TraceEvent::End { offset } => format!("end {offset}"),
+            };
+            sink.lock().unwrap().push(line);
+        });
+        let options = DeserializerOptions { tracer: Some(tracer), recover_errors, ..Default::default() };
+        let mut de = RonDeserializer::with_options(src, options);
+        loop {
+            match de.next_event() {
+                Ok(RonEvent::Eof) => {
+                    // The end is only traced once.
+                    de.next_event().unwrap();
+                    break;
+                },
+                Ok(_) => {},
+                Err(_) => break,
+            }
+        }
+        return log.lock().unwrap().clone();
+    }
+
+    #[test]
+    fn trace_test() {
+        assert_eq!(trace("A(b: [1, {2: 3}], c: (4,))", false), [
+            "start 26",
+            "enter struct Some(\"A\") 0",
+            "enter list None 5",
+            "enter map None 9",
+            "exit map 15",
+            "exit list 16",
+            "enter tuple None 21",
+            "exit tuple 25",
+            "exit struct 26",
+            "end 26",
+        ]);
+    }
+
+    #[test]
+    fn error_test() {
+        assert_eq!(trace("[1, @, 2]", true), ["start 9", "enter list None 0", "error Some(Span { start: 4, end: 5 })", "exit list 9", "end 9"]);
+        assert_eq!(trace("[1, @", false), ["start 5", "enter list None 0", "error Some(Span { start: 4, end: 5 })"]);
+    }
+}
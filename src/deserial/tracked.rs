@@ -0,0 +1,215 @@
+use std::fmt::Write;
+
+use super::{RonDeserializer, RonError, RonEvent};
+
+/// One step of the path reported by a [`TrackedDeserializer`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment<'a> {
+    /// A struct field, or the name of the struct or tuple at the top of the document.
+    Field(&'a str),
+    /// The value of a map entry, with the source text of its key.
+    Key(&'a str),
+    /// An element of a list or a tuple.
+    Index(usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Frame {
+    Struct,
+    /// A list or a tuple, with the index of the next element.
+    Seq(usize),
+    /// A map while reading a key, with where the key started once it has.
+    MapKey(Option<usize>),
+    /// A map while reading the value of an entry.
+    MapValue,
+    Some,
+}
+
+/// Wraps a [`RonDeserializer`] and keeps the path of the value each event belongs to, like `Player.factions["pirates"]`
+/// or `powers[3]`.
+///
+/// Start and end events have the path of their value, `NamedField` the path of the field, and the events of map keys
+/// the path of their map. `Some` is looked through, so its value has the same path.
+/// ```
+/// # use light_ron::deserial::{RonDeserializer, RonEvent, RonPrimitive, TrackedDeserializer};
+/// let mut de = TrackedDeserializer::new(RonDeserializer::new("Player(factions: {\"pirates\": -100}, powers: [Fire, Fira])"));
+/// let mut paths = Vec::new();
+/// while de.next_event().unwrap() != RonEvent::Eof {
+///     paths.push(de.path());
+/// }
+/// assert_eq!(paths[4], "Player.factions[\"pirates\"]");
+/// assert_eq!(paths[9], "Player.powers[1]");
+/// ```
+pub struct TrackedDeserializer<'a> {
+    de: RonDeserializer<'a>,
+    frames: Vec<Frame>,
+    segments: Vec<PathSegment<'a>>,
+    /// Set when the last event finished a value, whose segment is removed before the next event.
+    finished: bool,
+}
+
+impl<'a> TrackedDeserializer<'a> {
+    pub fn new(de: RonDeserializer<'a>) -> Self {
+        return Self { de, frames: Vec::new(), segments: Vec::new(), finished: false };
+    }
+
+    /// The wrapped deserializer, for its spans and errors. It can't be advanced directly, since the path would be lost.
+    pub fn inner(&self) -> &RonDeserializer<'a> {
+        return &self.de;
+    }
+
+    pub fn into_inner(self) -> RonDeserializer<'a> {
+        return self.de;
+    }
+
+    pub fn peek_event(&mut self) -> Result<&RonEvent<'a>, RonError> {
+        return self.de.peek_event();
+    }
+
+    /// Returns the next event, updating the path to the one of its value.
+    pub fn next_event(&mut self) -> Result<RonEvent<'a>, RonError> {
+        if self.finished {
+            self.finished = false;
+            self.finish_value();
+        }
+
+        let event = self.de.next_event()?;
+        match event {
+            RonEvent::Primitive(_) => {
+                self.start_value();
+                self.finished = true;
+            },
+            RonEvent::OptionalSomeValue => {
+                self.start_value();
+                self.frames.push(Frame::Some);
+            },
+            RonEvent::StructStart { name } | RonEvent::TupleStart { name } => {
+                self.start_value();
+                if let (Some(name), true) = (name, self.frames.is_empty()) {
+                    self.segments.push(PathSegment::Field(name));
+                }
+                self.frames.push(if matches!(event, RonEvent::StructStart { .. }) { Frame::Struct } else { Frame::Seq(0) });
+            },
+            RonEvent::ListStart => {
+                self.start_value();
+                self.frames.push(Frame::Seq(0));
+            },
+            RonEvent::MapStart => {
+                self.start_value();
+                self.frames.push(Frame::MapKey(None));
+            },
+            RonEvent::NamedField(name) => self.segments.push(PathSegment::Field(name)),
+            RonEvent::StructEnd { .. } | RonEvent::TupleEnd { .. } | RonEvent::ListEnd | RonEvent::MapEnd => {
+                self.frames.pop();
+                self.finished = true;
+            },
+            RonEvent::DocumentEnd | RonEvent::Eof => {
+                self.frames.clear();
+                self.segments.clear();
+            },
+            RonEvent::Comment(_) | RonEvent::Error(_) => {},
+        }
+        return Ok(event);
+    }
+
+    pub fn segments(&self) -> &[PathSegment<'a>] {
+        return &self.segments;
+    }
+
+    /// The path of the last event, in the syntax of [`query`](crate::query) with map keys written as `[key]`. Empty
+    /// for the top level value.
+    pub fn path(&self) -> String {
+        let mut path = String::new();
+        for x in &self.segments {
+            match x {
+                PathSegment::Field(name) if path.is_empty() => path.push_str(name),
+                PathSegment::Field(name) => write!(path, ".{name}").unwrap(),
+                PathSegment::Key(key) => write!(path, "[{key}]").unwrap(),
+                PathSegment::Index(i) => write!(path, "[{i}]").unwrap(),
+            }
+        }
+        return path;
+    }
+
+    /// Called before a value starts, adding the segment that leads to it from its container.
+    fn start_value(&mut self) {
+        match self.frames.last_mut() {
+            Some(Frame::Seq(i)) => {
+                self.segments.push(PathSegment::Index(*i));
+                *i += 1;
+            },
+            Some(Frame::MapKey(start @ None)) => *start = Some(self.de.last_span().start),
+            _ => {},
+        }
+    }
+
+    /// Called after a value ends, removing the segment that led to it.
+    fn finish_value(&mut self) {
+        match self.frames.last_mut() {
+            Some(Frame::Struct | Frame::Seq(_)) => {
+                self.segments.pop();
+            },
+            Some(frame @ Frame::MapKey(_)) => {
+                let Frame::MapKey(Some(start)) = *frame else {
+                    return;
+                };
+                *frame = Frame::MapValue;
+                self.segments.push(PathSegment::Key(&self.de.src()[start..self.de.last_span().end]));
+            },
+            Some(frame @ Frame::MapValue) => {
+                *frame = Frame::MapKey(None);
+                self.segments.pop();
+            },
+            Some(Frame::Some) => {
+                self.frames.pop();
+                self.finish_value();
+            },
+            // The top level value ended, which only leaves its name.
+            None => self.segments.clear(),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(src: &str) -> Vec<String> {
+        let mut de = TrackedDeserializer::new(RonDeserializer::new(src));
+        let mut paths = Vec::new();
+        while de.next_event().unwrap() != RonEvent::Eof {
+            paths.push(de.path());
+        }
+        return paths;
+    }
+
+    #[test]
+    fn struct_test() {
+        assert_eq!(paths("Player(name: \"a\", pos: (1, 2))"), ["Player", "Player.name", "Player.name", "Player.pos", "Player.pos", "Player.pos[0]", "Player.pos[1]", "Player.pos", "Player"]);
+        assert_eq!(paths("(a: Some((b: 1)))"), ["", "a", "a", "a", "a.b", "a.b", "a", ""]);
+    }
+
+    #[test]
+    fn list_test() {
+        assert_eq!(paths("[[1], Some([]), 2]"), ["", "[0]", "[0][0]", "[0]", "[1]", "[1]", "[1]", "[2]", ""]);
+    }
+
+    #[test]
+    fn map_test() {
+        let src = "{\"a\": [1], 'b': { 2: X }}";
+        assert_eq!(paths(src), ["", "", "[\"a\"]", "[\"a\"][0]", "[\"a\"]", "", "['b']", "['b']", "['b'][2]", "['b']", ""]);
+    }
+
+    #[test]
+    fn documents_test() {
+        let options = crate::deserial::DeserializerOptions { documents: true, ..Default::default() };
+        let mut de = TrackedDeserializer::new(RonDeserializer::with_options("A(x: 1) [2]", options));
+        let mut paths = Vec::new();
+        while de.next_event().unwrap() != RonEvent::Eof {
+            paths.push(de.path());
+        }
+        assert_eq!(paths, ["A", "A.x", "A.x", "A", "", "", "[0]", "", ""]);
+        assert_eq!(de.segments(), []);
+    }
+}
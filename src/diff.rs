@@ -0,0 +1,247 @@
+//! Finding what changed between two documents.
+//!
+//! Structs are compared field by field and maps key by key, so reordering them isn't a change. Lists and tuples are
+//! compared element by element, with the extra elements of the longest one added or removed.
+//! ```
+//! # use light_ron::diff::{diff_str, Change};
+//! let changes = diff_str("(hp: 3, powers: [Fire])", "(hp: 5, powers: [Fire, Water], pet: None)").unwrap();
+//! let changes: Vec<_> = changes.iter().map(|x| x.to_string()).collect();
+//! assert_eq!(changes, ["~ hp: 3 -> 5", "+ powers[1]: Water", "+ pet: None"]);
+//! ```
+//!
+//! Changes can be written and read as RON, like `Changed(path: "hp", old: 3, new: 5)`, so a list of them works as a
+//! patch that [`merge::apply`](crate::merge::apply) can replay later.
+
+use std::fmt::{self, Write};
+
+use crate::deserial::lexer::is_plain_ident;
+use crate::deserial::{read_enum, RonDeserialize, RonDeserializer, RonError, RonEvent};
+use crate::serial::{PrettyConfig, RonSerialize, RonSerializer};
+use crate::value::{key_matches, RonValue};
+
+/// A difference at a path, in the syntax of [`query`](crate::query).
+///
+/// Map keys that are strings holding an identifier are written like fields, like `keys.jump`, unless another key of the
+/// map has the same text, like the identifier `jump`. Any other key is written as its RON text in brackets, like
+/// `keys["a.b"]`, `keys[""]`, `keys["jump"]` or `keys[1]`, so every path selects a single entry.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added { path: String, value: RonValue },
+    Removed { path: String, value: RonValue },
+    Changed { path: String, old: RonValue, new: RonValue },
+}
+
+impl Change {
+    pub fn path(&self) -> &str {
+        return match self {
+            Change::Added { path, .. } | Change::Removed { path, .. } | Change::Changed { path, .. } => path,
+        };
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (sign, path) = match self {
+            Change::Added { path, .. } => ('+', path),
+            Change::Removed { path, .. } => ('-', path),
+            Change::Changed { path, .. } => ('~', path),
+        };
+        write!(f, "{sign} ")?;
+        // A change of the whole document has no path.
+        if !path.is_empty() {
+            write!(f, "{path}: ")?;
+        }
+        return match self {
+            Change::Added { value, .. } | Change::Removed { value, .. } => write!(f, "{value}"),
+            Change::Changed { old, new, .. } => write!(f, "{old} -> {new}"),
+        };
+    }
+}
+
+impl<'a> RonDeserialize<'a> for Change {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        let (i, variant) = read_enum(de, &["Added", "Removed", "Changed"])?;
+        let mut reader = variant.fields(if i < 2 { &["path", "value"] } else { &["path", "old", "new"] })?;
+        let (mut path, mut value, mut old, mut new) = (String::new(), RonValue::None, RonValue::None, RonValue::None);
+        while let Some(field) = reader.next_field()? {
+            match field {
+                "path" => path = reader.read()?,
+                "value" => value = reader.read()?,
+                "old" => old = reader.read()?,
+                _ => new = reader.read()?,
+            }
+        }
+        return Ok(match i {
+            0 => Change::Added { path, value },
+            1 => Change::Removed { path, value },
+            _ => Change::Changed { path, old, new },
+        });
+    }
+}
+
+impl RonSerialize for Change {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        let name = match self {
+            Change::Added { .. } => "Added",
+            Change::Removed { .. } => "Removed",
+            Change::Changed { .. } => "Changed",
+        };
+        ser.write_event(RonEvent::StructStart { name: Some(name) })?;
+        ser.write_event(RonEvent::NamedField("path"))?;
+        self.path().serialize(ser)?;
+        match self {
+            Change::Added { value, .. } | Change::Removed { value, .. } => {
+                ser.write_event(RonEvent::NamedField("value"))?;
+                value.serialize(ser)?;
+            },
+            Change::Changed { old, new, .. } => {
+                ser.write_event(RonEvent::NamedField("old"))?;
+                old.serialize(ser)?;
+                ser.write_event(RonEvent::NamedField("new"))?;
+                new.serialize(ser)?;
+            },
+        }
+        return ser.write_event(RonEvent::StructEnd { name: Some(name) });
+    }
+}
+
+/// Parses both documents and returns the changes that turn `old` into `new`.
+pub fn diff_str(old: &str, new: &str) -> Result<Vec<Change>, RonError> {
+    return Ok(diff(&crate::from_str(old)?, &crate::from_str(new)?));
+}
+
+/// Returns the changes that turn `old` into `new`, in the order of `old` followed by what only `new` has.
+pub fn diff(old: &RonValue, new: &RonValue) -> Vec<Change> {
+    let mut changes = Vec::new();
+    compare(old, new, "", &mut changes);
+    return changes;
+}
+
+fn compare(old: &RonValue, new: &RonValue, path: &str, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (RonValue::Some(a), RonValue::Some(b)) => compare(a, b, path, changes),
+        (RonValue::Struct { name: n1, fields: a }, RonValue::Struct { name: n2, fields: b }) if n1 == n2 => {
+            for (name, x) in a {
+                let path = field_path(path, name);
+                match b.iter().find(|y| y.0 == *name) {
+                    Some(y) => compare(x, &y.1, &path, changes),
+                    None => changes.push(Change::Removed { path, value: x.clone() }),
+                }
+            }
+            for (name, y) in b {
+                if !a.iter().any(|x| x.0 == *name) {
+                    changes.push(Change::Added { path: field_path(path, name), value: y.clone() });
+                }
+            }
+        },
+        (RonValue::Map(a), RonValue::Map(b)) => {
+            let keys: Vec<_> = a.iter().chain(b).map(|x| &x.0).collect();
+            for (key, x) in a {
+                let path = key_path(path, key, &keys);
+                match b.iter().find(|y| y.0 == *key) {
+                    Some(y) => compare(x, &y.1, &path, changes),
+                    None => changes.push(Change::Removed { path, value: x.clone() }),
+                }
+            }
+            for (key, y) in b {
+                if !a.iter().any(|x| x.0 == *key) {
+                    changes.push(Change::Added { path: key_path(path, key, &keys), value: y.clone() });
+                }
+            }
+        },
+        (RonValue::List(a), RonValue::List(b)) => compare_items(a, b, path, changes),
+        (RonValue::Tuple { name: n1, values: a }, RonValue::Tuple { name: n2, values: b }) if n1 == n2 && a.len() == b.len() => {
+            compare_items(a, b, path, changes);
+        },
+        (a, b) if a != b => changes.push(Change::Changed { path: String::from(path), old: a.clone(), new: b.clone() }),
+        _ => {},
+    }
+}
+
+fn compare_items(a: &[RonValue], b: &[RonValue], path: &str, changes: &mut Vec<Change>) {
+    for (i, x) in a.iter().enumerate() {
+        let path = format!("{path}[{i}]");
+        match b.get(i) {
+            Some(y) => compare(x, y, &path, changes),
+            None => changes.push(Change::Removed { path, value: x.clone() }),
+        }
+    }
+    for (i, y) in b.iter().enumerate().skip(a.len()) {
+        changes.push(Change::Added { path: format!("{path}[{i}]"), value: y.clone() });
+    }
+}
+
+/// The path of a map entry, written like a field when the key is a string holding an identifier that none of the other
+/// `keys` of the map also match, and as the key's RON text in brackets otherwise.
+pub(crate) fn key_path(path: &str, key: &RonValue, keys: &[&RonValue]) -> String {
+    if let RonValue::Str(x) = key {
+        if is_plain_ident(x) && !keys.iter().any(|k| *k != key && key_matches(k, x)) {
+            return field_path(path, x);
+        }
+    }
+    let mut text = format!("{path}[");
+    // Values always serialize, and so does anything written to a string.
+    key.serialize(&mut RonSerializer::new(&mut text, PrettyConfig::compact())).unwrap();
+    text.push(']');
+    return text;
+}
+
+pub(crate) fn field_path(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        return String::from(field);
+    }
+    return format!("{path}.{field}");
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changes(old: &str, new: &str) -> Vec<String> {
+        return diff_str(old, new).unwrap().iter().map(|x| x.to_string()).collect();
+    }
+
+    #[test]
+    fn struct_test() {
+        assert_eq!(changes("Player(a: 1, b: 2)", "Player(b: 2, a: 1)"), Vec::<String>::new());
+        assert_eq!(changes("Player(a: 1, b: 2)", "Player(c: 3, a: 1)"), ["- b: 2", "+ c: 3"]);
+        assert_eq!(changes("Player(a: 1)", "Enemy(a: 1)"), ["~ Player(\n    a: 1,\n) -> Enemy(\n    a: 1,\n)"]);
+    }
+
+    #[test]
+    fn map_test() {
+        assert_eq!(changes("{\"pirates\": -100, 1: Some(2)}", "{1: Some(3), \"crabs\": 30}"), ["- pirates: -100", "~ [1]: 2 -> 3", "+ crabs: 30"]);
+        assert_eq!(changes("{\"a.b\": 1, \"\": 2}", "{\"a.b\": 3}"), ["~ [\"a.b\"]: 1 -> 3", "- [\"\"]: 2"]);
+        assert_eq!(changes("(m: {1: 1, \"1\": 1, One: 1})", "(m: {1: 2, \"1\": 2, One: 2})"), ["~ m[1]: 1 -> 2", "~ m[\"1\"]: 1 -> 2", "~ m[One]: 1 -> 2"]);
+        assert_eq!(changes("{One: 1, \"One\": 2}", "{One: 1, \"One\": 3}"), ["~ [\"One\"]: 2 -> 3"]);
+        assert_eq!(changes("{One: 1}", "{\"One\": 1}"), ["- [One]: 1", "+ [\"One\"]: 1"]);
+        assert_eq!(changes("{true: 1, 'x': 1}", "{true: 2, 'x': 2}"), ["~ [true]: 1 -> 2", "~ ['x']: 1 -> 2"]);
+    }
+
+    #[test]
+    fn list_test() {
+        assert_eq!(changes("[1, 2, 3]", "[1, 5]"), ["~ [1]: 2 -> 5", "- [2]: 3"]);
+        assert_eq!(changes("(a: [(1, 2)])", "(a: [(1, 3)])"), ["~ a[0][1]: 2 -> 3"]);
+        assert_eq!(changes("(1, 2)", "(1, 2, 3)"), ["~ (1, 2) -> (1, 2, 3)"]);
+    }
+
+    #[test]
+    fn path_test() {
+        let changes = diff(&crate::from_str("(a: Some((b: 1)))").unwrap(), &crate::from_str("(a: None)").unwrap());
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path(), "a");
+        assert!(diff_str("[1", "[1]").is_err());
+    }
+
+    #[test]
+    fn serialize_test() {
+        let changes = diff_str("(a: 1, b: [2], c: Some(3))", "(a: 2, b: [], d: 4)").unwrap();
+        let src = crate::to_string(&changes).unwrap();
+        assert_eq!(crate::from_str::<Vec<Change>>(&src), Ok(changes));
+
+        assert!(crate::from_str::<Change>("Added(path: \"a\")").is_err());
+        assert!(crate::from_str::<Change>("Moved(path: \"a\", value: 1)").is_err());
+        assert!(crate::from_str::<Change>("Changed(path: \"a\", value: 1)").is_err());
+    }
+}
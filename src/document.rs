@@ -0,0 +1,241 @@
+use std::fmt;
+
+use crate::deserial::lexer::find_some_close;
+use crate::deserial::{unexpected, ErrorKind, RonDeserialize, RonDeserializer, RonError, RonEvent, Span};
+use crate::path::{self, Segment};
+use crate::serial::{PrettyConfig, RonSerialize, RonSerializer};
+use crate::value::RonValue;
+
+/// A RON document kept as its source text, for editing it without touching anything but the edited values.
+///
+/// Comments, formatting and the order of everything else are left as they were, so saving an edited config file
+/// changes only the lines of the values that were set.
+/// ```
+/// # use light_ron::document::RonDocument;
+/// let mut doc = RonDocument::parse("Game(\n    // Who plays\n    player: (name: \"a\", lives: 3),\n)").unwrap();
+/// doc.set("player.lives", &5).unwrap();
+/// assert_eq!(doc.as_str(), "Game(\n    // Who plays\n    player: (name: \"a\", lives: 5),\n)");
+/// assert_eq!(doc.get::<&str>("player.name"), Ok("a"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RonDocument {
+    src: String,
+    root: Node,
+}
+
+/// Where a value is in the source, and where its children are.
+#[derive(Debug, Clone)]
+struct Node {
+    span: Span,
+    children: Children,
+}
+
+#[derive(Debug, Clone)]
+enum Children {
+    None,
+    Some(Box<Node>),
+    Fields(Vec<(String, Node)>),
+    Items(Vec<Node>),
+    Entries(Vec<(RonValue, Node)>),
+}
+
+impl RonDocument {
+    pub fn parse(src: impl Into<String>) -> Result<Self, RonError> {
+        let src = src.into();
+        let root = read_tree(&src)?;
+        return Ok(Self { src, root });
+    }
+
+    pub fn as_str(&self) -> &str {
+        return &self.src;
+    }
+
+    pub fn into_string(self) -> String {
+        return self.src;
+    }
+
+    /// The span of the source taken by the value at `path`.
+    pub fn span(&self, path: &str) -> Result<Span, RonError> {
+        let segments = path::parse(path)?;
+        let mut node = &self.root;
+        for x in segments {
+            node = node.child(&x).ok_or_else(|| RonError::new(format!("No value at '{path}'")))?;
+        }
+        return Ok(node.span);
+    }
+
+    /// Deserializes the value at `path`.
+    pub fn get<'a, T: RonDeserialize<'a>>(&'a self, path: &str) -> Result<T, RonError> {
+        let span = self.span(path)?;
+        return crate::from_str(&self.src[span.start..span.end]);
+    }
+
+    /// Replaces the value at `path` with `value`, leaving the rest of the source as it was.
+    ///
+    /// The new value is written with the default [`PrettyConfig`], and its lines are indented like the line it starts in.
+    pub fn set<T: RonSerialize + ?Sized>(&mut self, path: &str, value: &T) -> Result<(), RonError> {
+        let span = self.span(path)?;
+
+        let mut ser = RonSerializer::new(String::new(), PrettyConfig::default());
+        value.serialize(&mut ser).map_err(|_| RonError::new("Value can't be represented in RON"))?;
+        let text = ser.into_inner();
+
+        let line_start = self.src[..span.start].rfind('\n').map_or(0, |x| x + 1);
+        let indent: String = self.src[line_start..].chars().take_while(|x| *x == ' ' || *x == '\t').collect();
+        let text = text.replace('\n', &format!("\n{indent}"));
+
+        let mut src = String::with_capacity(self.src.len() + text.len());
+        src.push_str(&self.src[..span.start]);
+        src.push_str(&text);
+        src.push_str(&self.src[span.end..]);
+
+        self.root = read_tree(&src)?;
+        self.src = src;
+        return Ok(());
+    }
+}
+
+impl fmt::Display for RonDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(&self.src);
+    }
+}
+
+impl Node {
+    fn child(&self, segment: &Segment) -> Option<&Node> {
+        return match (&self.children, segment) {
+            (Children::Some(x), _) => x.child(segment),
+            (Children::Fields(fields), Segment::Field(name)) => fields.iter().find(|x| x.0 == *name).map(|x| &x.1),
+            (Children::Entries(entries), segment) => entries.iter().find(|x| segment.selects_key(&x.0)).map(|x| &x.1),
+            (Children::Items(items), Segment::Index(i)) => items.get(*i),
+            _ => None,
+        };
+    }
+}
+
+fn read_tree(src: &str) -> Result<Node, RonError> {
+    let mut de = RonDeserializer::new(src);
+    let event = de.next_event()?;
+    let root = read_node(&mut de, event)?;
+    return match de.next_event()? {
+        RonEvent::Eof => Ok(root),
+        _ => Err(de.error(ErrorKind::TrailingCharacters)),
+    };
+}
+
+/// Reads the node of the value that starts with `event`, which has just been returned by `de`.
+fn read_node(de: &mut RonDeserializer, event: RonEvent) -> Result<Node, RonError> {
+    let start = de.last_span();
+    let children = match event {
+        RonEvent::Primitive(_) => return Ok(Node { span: start, children: Children::None }),
+        RonEvent::OptionalSomeValue => {
+            let event = de.next_event()?;
+            let inner = read_node(de, event)?;
+            let end = find_some_close(de.src(), inner.span.end)?;
+            return Ok(Node { span: start.to(Span::new(end, end)), children: Children::Some(Box::new(inner)) });
+        },
+        RonEvent::StructStart { .. } => {
+            let mut fields = Vec::new();
+            loop {
+                match de.next_event()? {
+                    RonEvent::NamedField(name) => {
+                        let event = de.next_event()?;
+                        fields.push((String::from(name), read_node(de, event)?));
+                    },
+                    RonEvent::StructEnd { .. } => break,
+                    x => return Err(unexpected(de, "a field", x)),
+                }
+            }
+            Children::Fields(fields)
+        },
+        RonEvent::TupleStart { .. } | RonEvent::ListStart => {
+            let mut items = Vec::new();
+            loop {
+                match de.next_event()? {
+                    RonEvent::TupleEnd { .. } | RonEvent::ListEnd => break,
+                    x => items.push(read_node(de, x)?),
+                }
+            }
+            Children::Items(items)
+        },
+        RonEvent::MapStart => {
+            let mut entries = Vec::new();
+            loop {
+                match de.next_event()? {
+                    RonEvent::MapEnd => break,
+                    x => {
+                        let key = read_node(de, x)?;
+                        let key = crate::from_str(&de.src()[key.span.start..key.span.end])?;
+                        let event = de.next_event()?;
+                        entries.push((key, read_node(de, event)?));
+                    },
+                }
+            }
+            Children::Entries(entries)
+        },
+        x => return Err(unexpected(de, "a value", x)),
+    };
+    return Ok(Node { span: start.to(de.last_span()), children });
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = r#"// Saved game
+Player(
+    name: "SomePlayer69", // The name
+    pos: (0.0, 0.0),
+    factions: {"pirates": -100, crabs: 30},
+    powers: [Fire, Some(Water)],
+)
+"#;
+
+    #[test]
+    fn get_test() {
+        let doc = RonDocument::parse(SRC).unwrap();
+        assert_eq!(doc.get::<&str>("name"), Ok("SomePlayer69"));
+        assert_eq!(doc.get::<f64>("pos[1]"), Ok(0.0));
+        assert_eq!(doc.get::<i32>("factions.pirates"), Ok(-100));
+        assert_eq!(doc.get::<i32>("factions.crabs"), Ok(30));
+        assert_eq!(doc.get::<RonValue>("powers[1]"), Ok(RonValue::Some(Box::new(RonValue::Enum(String::from("Water"))))));
+        assert!(doc.get::<i32>("factions.sharks").is_err());
+        assert!(doc.get::<i32>("powers[2]").is_err());
+    }
+
+    #[test]
+    fn set_test() {
+        let mut doc = RonDocument::parse(SRC).unwrap();
+        doc.set("name", "Other").unwrap();
+        doc.set("factions.crabs", &-5).unwrap();
+        doc.set("powers[1]", &None::<i32>).unwrap();
+        assert_eq!(doc.as_str(), SRC.replace("SomePlayer69", "Other").replace("30", "-5").replace("Some(Water)", "None"));
+    }
+
+    #[test]
+    fn set_indent_test() {
+        let mut doc = RonDocument::parse(SRC).unwrap();
+        doc.set("pos", &vec![1, 2]).unwrap();
+        assert!(doc.as_str().contains("    pos: [\n        1,\n        2,\n    ],\n    factions"));
+        assert_eq!(doc.get::<Vec<i32>>("pos"), Ok(vec![1, 2]));
+
+        doc.set("", &1).unwrap();
+        assert_eq!(doc.as_str(), "// Saved game\n1\n");
+    }
+
+    #[test]
+    fn span_test() {
+        let doc = RonDocument::parse("Some( [1] )").unwrap();
+        assert_eq!(doc.span(""), Ok(Span::new(0, 11)));
+        assert_eq!(doc.span("[0]"), Ok(Span::new(7, 8)));
+    }
+
+    #[test]
+    fn invalid_test() {
+        assert!(RonDocument::parse("[1, 2").is_err());
+        assert!(RonDocument::parse("1 2").is_err());
+        assert_eq!(RonDocument::parse("[Some(1]").unwrap_err().span(), Some(Span::new(7, 8)));
+        assert!(RonDocument::parse("[1]").unwrap().set("[x]", &1).is_err());
+    }
+}
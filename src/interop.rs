@@ -0,0 +1,252 @@
+//! Conversions between RON and other config formats, for moving configs to RON or exporting them to tools that don't
+//! read it. TOML is behind the `toml` feature and YAML behind the `yaml` one.
+//!
+//! TOML values follow the rules of the [`json`](crate::json) module, so named structs and tuples are tagged with their
+//! name, like `{ Dog = { age = 2 } }`. TOML has no null though: `None` fields are left out of tables, and a `None`
+//! anywhere else can't be converted. Dates are read as strings.
+//! ```
+//! # #[cfg(feature = "toml")] {
+//! # use light_ron::interop::from_toml;
+//! # use light_ron::serial::PrettyConfig;
+//! let config: toml::Value = "name = \"a\"\n[audio]\nvolume = 0.5".parse().unwrap();
+//! assert_eq!(from_toml(&config, PrettyConfig::default()).unwrap(), "(\n    name: \"a\",\n    audio: (\n        volume: 0.5,\n    ),\n)");
+//! # }
+//! ```
+//!
+//! YAML can hold the names of structs and tuples as tags, like `!Cast 3` or `!Dog {age: 2}`, so those are kept. Map
+//! keys are converted like any other value. The rest is like JSON: bare identifiers and chars become strings, unnamed
+//! tuples become sequences and `None` becomes null.
+
+use crate::deserial::lexer::is_plain_ident;
+use crate::deserial::RonError;
+use crate::serial::PrettyConfig;
+use crate::value::RonValue;
+#[cfg(feature = "toml")]
+use crate::value::{is_type_name, key_text};
+
+/// Parses a RON document into a TOML value.
+#[cfg(feature = "toml")]
+pub fn to_toml(src: &str) -> Result<toml::Value, RonError> {
+    return value_to_toml(&crate::from_str(src)?);
+}
+
+/// Writes a TOML value as a RON document with the given formatting rules.
+#[cfg(feature = "toml")]
+pub fn from_toml(value: &toml::Value, config: PrettyConfig) -> Result<String, RonError> {
+    return crate::to_string_pretty(&toml_to_value(value), config);
+}
+
+/// Fails on `None`s that aren't the value of a field, since TOML can't hold them.
+#[cfg(feature = "toml")]
+pub fn value_to_toml(value: &RonValue) -> Result<toml::Value, RonError> {
+    use toml::{Table, Value};
+
+    let array = |values: &[RonValue]| values.iter().map(value_to_toml).collect::<Result<Vec<_>, _>>().map(Value::Array);
+    return Ok(match value {
+        RonValue::None => return Err(RonError::new("TOML can't hold None outside of a field")),
+        RonValue::Some(x) => value_to_toml(x)?,
+        RonValue::Int(x) => Value::Integer(i64::try_from(*x).map_err(|_| RonError::new(format!("TOML can't hold {x}, which doesn't fit in an i64")))?),
+        RonValue::Float(x) => Value::Float(*x),
+        RonValue::Bool(x) => Value::Boolean(*x),
+        RonValue::Char(x) => Value::String(x.to_string()),
+        RonValue::Str(x) | RonValue::Enum(x) => Value::String(x.clone()),
+        RonValue::Bytes(x) => Value::Array(x.iter().map(|&x| Value::Integer(x as i64)).collect()),
+        RonValue::List(values) | RonValue::Tuple { name: None, values } => array(values)?,
+        RonValue::Tuple { name: Some(name), values } => {
+            let inner = if values.len() == 1 { value_to_toml(&values[0])? } else { array(values)? };
+            Value::Table(Table::from_iter([(name.clone(), inner)]))
+        },
+        RonValue::Map(entries) => {
+            let mut table = Table::new();
+            for (k, v) in entries.iter().filter(|x| x.1 != RonValue::None) {
+                table.insert(key_text(k), value_to_toml(v)?);
+            }
+            Value::Table(table)
+        },
+        RonValue::Struct { name, fields } => {
+            let mut table = Table::new();
+            for (k, v) in fields.iter().filter(|x| x.1 != RonValue::None) {
+                table.insert(k.clone(), value_to_toml(v)?);
+            }
+            match name {
+                Some(name) => Value::Table(Table::from_iter([(name.clone(), Value::Table(table))])),
+                None => Value::Table(table),
+            }
+        },
+    });
+}
+
+#[cfg(feature = "toml")]
+pub fn toml_to_value(value: &toml::Value) -> RonValue {
+    use toml::Value;
+
+    return match value {
+        Value::String(x) => RonValue::Str(x.clone()),
+        Value::Integer(x) => RonValue::Int(i128::from(*x)),
+        Value::Float(x) => RonValue::Float(*x),
+        Value::Boolean(x) => RonValue::Bool(*x),
+        Value::Datetime(x) => RonValue::Str(x.to_string()),
+        Value::Array(values) => RonValue::List(values.iter().map(toml_to_value).collect()),
+        Value::Table(table) => {
+            if table.len() == 1 {
+                let (name, inner) = table.iter().next().unwrap();
+                if is_type_name(name) {
+                    return match inner {
+                        Value::Table(x) if x.keys().all(|x| is_plain_ident(x)) => named(Some(name), x.iter().map(|(k, v)| (k.clone(), toml_to_value(v)))),
+                        Value::Array(values) => RonValue::Tuple { name: Some(name.clone()), values: values.iter().map(toml_to_value).collect() },
+                        x => RonValue::Tuple { name: Some(name.clone()), values: vec![toml_to_value(x)] },
+                    };
+                }
+            }
+            named(None, table.iter().map(|(k, v)| (k.clone(), toml_to_value(v))))
+        },
+    };
+}
+
+/// Parses a RON document into a YAML value.
+#[cfg(feature = "yaml")]
+pub fn to_yaml(src: &str) -> Result<serde_yaml::Value, RonError> {
+    return Ok(value_to_yaml(&crate::from_str(src)?));
+}
+
+/// Writes a YAML value as a RON document with the given formatting rules.
+#[cfg(feature = "yaml")]
+pub fn from_yaml(value: &serde_yaml::Value, config: PrettyConfig) -> Result<String, RonError> {
+    return crate::to_string_pretty(&yaml_to_value(value), config);
+}
+
+#[cfg(feature = "yaml")]
+pub fn value_to_yaml(value: &RonValue) -> serde_yaml::Value {
+    use serde_yaml::value::{Tag, TaggedValue};
+    use serde_yaml::{Mapping, Value};
+
+    let sequence = |values: &[RonValue]| Value::Sequence(values.iter().map(value_to_yaml).collect());
+    let tagged = |name: &str, value| Value::Tagged(Box::new(TaggedValue { tag: Tag::new(name), value }));
+    return match value {
+        RonValue::None => Value::Null,
+        RonValue::Some(x) => value_to_yaml(x),
+        RonValue::Int(x) => match (i64::try_from(*x), u64::try_from(*x)) {
+            (Ok(x), _) => Value::from(x),
+            (_, Ok(x)) => Value::from(x),
+            _ => Value::from(*x as f64),
+        },
+        RonValue::Float(x) => Value::from(*x),
+        RonValue::Bool(x) => Value::Bool(*x),
+        RonValue::Char(x) => Value::String(x.to_string()),
+        RonValue::Str(x) | RonValue::Enum(x) => Value::String(x.clone()),
+        RonValue::Bytes(x) => Value::Sequence(x.iter().map(|&x| Value::from(x)).collect()),
+        RonValue::List(values) | RonValue::Tuple { name: None, values } => sequence(values),
+        RonValue::Tuple { name: Some(name), values } if values.len() == 1 => tagged(name, value_to_yaml(&values[0])),
+        RonValue::Tuple { name: Some(name), values } => tagged(name, sequence(values)),
+        RonValue::Map(entries) => Value::Mapping(entries.iter().map(|(k, v)| (value_to_yaml(k), value_to_yaml(v))).collect()),
+        RonValue::Struct { name, fields } => {
+            let mapping: Mapping = fields.iter().map(|(k, v)| (Value::String(k.clone()), value_to_yaml(v))).collect();
+            match name {
+                Some(name) => tagged(name, Value::Mapping(mapping)),
+                None => Value::Mapping(mapping),
+            }
+        },
+    };
+}
+
+/// Numbers that fit in an `i64` or a `u64` become integers and the rest floats. Mappings become structs when all their keys
+/// are identifiers, and maps otherwise.
+#[cfg(feature = "yaml")]
+pub fn yaml_to_value(value: &serde_yaml::Value) -> RonValue {
+    use serde_yaml::Value;
+
+    return match value {
+        Value::Null => RonValue::None,
+        Value::Bool(x) => RonValue::Bool(*x),
+        Value::Number(x) => match (x.as_i64(), x.as_u64()) {
+            (Some(x), _) => RonValue::Int(i128::from(x)),
+            (_, Some(x)) => RonValue::Int(i128::from(x)),
+            _ => RonValue::Float(x.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(x) => RonValue::Str(x.clone()),
+        Value::Sequence(values) => RonValue::List(values.iter().map(yaml_to_value).collect()),
+        Value::Mapping(mapping) => mapping_to_value(None, mapping),
+        Value::Tagged(x) => {
+            let name = x.tag.to_string();
+            let name = name.trim_start_matches('!');
+            match &x.value {
+                Value::Mapping(mapping) => mapping_to_value(Some(name), mapping),
+                Value::Sequence(values) => RonValue::Tuple { name: Some(String::from(name)), values: values.iter().map(yaml_to_value).collect() },
+                x => RonValue::Tuple { name: Some(String::from(name)), values: vec![yaml_to_value(x)] },
+            }
+        },
+    };
+}
+
+#[cfg(feature = "yaml")]
+fn mapping_to_value(name: Option<&str>, mapping: &serde_yaml::Mapping) -> RonValue {
+    let fields: Option<Vec<_>> = mapping.iter().map(|(k, v)| Some((String::from(k.as_str().filter(|x| is_plain_ident(x))?), yaml_to_value(v)))).collect();
+    return match fields {
+        Some(fields) => named(name, fields),
+        // A name with a map has nowhere to go, so it's kept on a tuple around it.
+        None if name.is_some() => RonValue::Tuple { name: name.map(String::from), values: vec![mapping_to_value(None, mapping)] },
+        None => RonValue::Map(mapping.iter().map(|(k, v)| (yaml_to_value(k), yaml_to_value(v))).collect()),
+    };
+}
+
+/// Builds a struct from `fields` when all their names are identifiers, or a map otherwise, which is only done for
+/// fields without a `name`.
+fn named(name: Option<&str>, fields: impl IntoIterator<Item = (String, RonValue)>) -> RonValue {
+    let fields: Vec<_> = fields.into_iter().collect();
+    if fields.iter().all(|x| is_plain_ident(&x.0)) {
+        return RonValue::Struct { name: name.map(String::from), fields };
+    }
+    return RonValue::Map(fields.into_iter().map(|(k, v)| (RonValue::Str(k), v)).collect());
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn to_toml_test() {
+        let value = to_toml(r#"Player(name: "a", pos: (1, 2.5), spell: Cast(3), pet: None, factions: {"pirates": -100, 1: 2})"#).unwrap();
+        let expected: toml::Value = r#"
+            [Player]
+            name = "a"
+            pos = [1, 2.5]
+            spell = { Cast = 3 }
+            factions = { pirates = -100, "1" = 2 }
+        "#.parse().unwrap();
+        assert_eq!(value, expected);
+        assert!(to_toml("[1, None]").is_err());
+        assert!(to_toml("[1,").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "toml")]
+    fn from_toml_test() {
+        let value: toml::Value = "a = 1979-05-27\nb = { Rect = [1, 2] }\nc = { Dog = { age = 2 } }\n\"pirate ship\" = true".parse().unwrap();
+        assert_eq!(toml_to_value(&value), crate::from_str(r#"{"a": "1979-05-27", "b": Rect(1, 2), "c": Dog(age: 2), "pirate ship": true}"#).unwrap());
+
+        let src = "(a: [1, 2.5], b: Cast(3), c: true, d: Dog(age: 2))";
+        assert_eq!(from_toml(&to_toml(src).unwrap(), PrettyConfig::default()).unwrap(), crate::to_string(&crate::from_str::<RonValue>(src).unwrap()).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn yaml_test() {
+        let src = r#"Player(name: "a", spell: Cast(3), area: Rect(1, 2), pet: Some(Dog(age: 2)), tag: None, factions: {"pirates": -100, 1: 2})"#;
+        let value = to_yaml(src).unwrap();
+        let expected: serde_yaml::Value = serde_yaml::from_str("!Player\nname: a\nspell: !Cast 3\narea: !Rect [1, 2]\npet: !Dog {age: 2}\ntag: null\nfactions: {pirates: -100, 1: 2}").unwrap();
+        assert_eq!(value, expected);
+
+        let src = r#"Player(name: "a", spell: Cast(3), area: Rect(1, 2), pet: Dog(age: 2), tag: None, factions: {"pirates": -100, 1: 2})"#;
+        assert_eq!(yaml_to_value(&value), crate::from_str(src).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "yaml")]
+    fn from_yaml_test() {
+        let value: serde_yaml::Value = serde_yaml::from_str("a: [1, 2.5]\nb: !Tagged {x y: 1}\nc d: ~").unwrap();
+        assert_eq!(yaml_to_value(&value), crate::from_str(r#"{"a": [1, 2.5], "b": Tagged({"x y": 1}), "c d": None}"#).unwrap());
+        assert_eq!(from_yaml(&serde_yaml::Value::from(5), PrettyConfig::default()).unwrap(), "5");
+    }
+}
@@ -0,0 +1,149 @@
+//! Conversions between RON documents and [`serde_json::Value`], behind the `json` feature.
+//!
+//! RON has more kinds of values than JSON, so the conversion follows these rules:
+//! - Structs become objects with their fields. Named ones are tagged with their name, like `{"Dog": {"age": 2}}`.
+//! - Named tuples are tagged the same way, like `{"Fire": [1, 2]}`, or `{"Fire": 1}` when they hold a single value.
+//!   Unnamed tuples and lists become arrays.
+//! - Bare identifiers, like enum variants without data, become strings.
+//! - Maps become objects. Keys that aren't strings are written as their RON text.
+//! - `None` becomes `null` and `Some(x)` becomes `x`. Chars become strings, and floats that JSON can't hold become `null`.
+//! - Integers beyond the range of an `i64` and a `u64` become floats.
+//! - Byte strings become arrays of numbers.
+//!
+//! Going back, an object with a single key that looks like a type name (an identifier starting with an uppercase
+//! letter) is read as a tagged value: an object inside becomes a named struct, an array a named tuple, and anything else a
+//! named tuple with that single value. Other objects become structs when all their keys are identifiers, and maps
+//! otherwise.
+
+use serde_json::{Map, Number, Value};
+
+use crate::deserial::lexer::is_plain_ident;
+use crate::deserial::RonError;
+use crate::serial::PrettyConfig;
+use crate::value::{is_type_name, key_text, RonValue};
+
+/// Parses a RON document into a JSON value.
+pub fn to_json(src: &str) -> Result<Value, RonError> {
+    return Ok(value_to_json(crate::from_str(src)?));
+}
+
+/// Writes a JSON value as a RON document with the given formatting rules.
+pub fn from_json(value: &Value, config: PrettyConfig) -> Result<String, RonError> {
+    return crate::to_string_pretty(&json_to_value(value), config);
+}
+
+fn value_to_json(value: RonValue) -> Value {
+    return match value {
+        RonValue::None => Value::Null,
+        RonValue::Some(x) => value_to_json(*x),
+        RonValue::Int(x) => match (i64::try_from(x), u64::try_from(x)) {
+            (Ok(x), _) => Value::from(x),
+            (_, Ok(x)) => Value::from(x),
+            _ => Value::from(x as f64),
+        },
+        RonValue::Float(x) => Number::from_f64(x).map_or(Value::Null, Value::Number),
+        RonValue::Bool(x) => Value::Bool(x),
+        RonValue::Char(x) => Value::String(x.to_string()),
+        RonValue::Str(x) | RonValue::Enum(x) => Value::String(x),
+        RonValue::Bytes(x) => Value::Array(x.into_iter().map(Value::from).collect()),
+        RonValue::List(values) | RonValue::Tuple { name: None, values } => Value::Array(values.into_iter().map(value_to_json).collect()),
+        RonValue::Tuple { name: Some(name), mut values } => {
+            let inner = if values.len() == 1 {
+                value_to_json(values.remove(0))
+            } else {
+                Value::Array(values.into_iter().map(value_to_json).collect())
+            };
+            Value::Object(Map::from_iter([(name, inner)]))
+        },
+        RonValue::Map(entries) => Value::Object(
+            entries
+                .into_iter()
+                .map(|(k, v)| (key_text(&k), value_to_json(v)))
+                .collect(),
+        ),
+        RonValue::Struct { name, fields } => {
+            let inner = Value::Object(fields.into_iter().map(|(k, v)| (k, value_to_json(v))).collect());
+            match name {
+                Some(name) => Value::Object(Map::from_iter([(name, inner)])),
+                None => inner,
+            }
+        },
+    };
+}
+
+fn json_to_value(value: &Value) -> RonValue {
+    return match value {
+        Value::Null => RonValue::None,
+        Value::Bool(x) => RonValue::Bool(*x),
+        Value::Number(x) => match (x.as_i64(), x.as_u64()) {
+            (Some(x), _) => RonValue::Int(i128::from(x)),
+            (_, Some(x)) => RonValue::Int(i128::from(x)),
+            _ => RonValue::Float(x.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(x) => RonValue::Str(x.clone()),
+        Value::Array(values) => RonValue::List(values.iter().map(json_to_value).collect()),
+        Value::Object(map) => {
+            if map.len() == 1 {
+                let (name, inner) = map.iter().next().unwrap();
+                if is_type_name(name) {
+                    return tagged(name, inner);
+                }
+            }
+
+            if map.keys().all(|x| is_plain_ident(x)) {
+                RonValue::Struct { name: None, fields: map.iter().map(|(k, v)| (k.clone(), json_to_value(v))).collect() }
+            } else {
+                RonValue::Map(map.iter().map(|(k, v)| (RonValue::Str(k.clone()), json_to_value(v))).collect())
+            }
+        },
+    };
+}
+
+/// Reads the value of an object tagged with `name`.
+fn tagged(name: &str, inner: &Value) -> RonValue {
+    let name = Some(String::from(name));
+    return match inner {
+        Value::Object(map) if map.keys().all(|x| is_plain_ident(x)) => {
+            RonValue::Struct { name, fields: map.iter().map(|(k, v)| (k.clone(), json_to_value(v))).collect() }
+        },
+        Value::Array(values) => RonValue::Tuple { name, values: values.iter().map(json_to_value).collect() },
+        x => RonValue::Tuple { name, values: vec![json_to_value(x)] },
+    };
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn to_json_test() {
+        let src = r#"(name: "a", pos: (1, 2.5), power: Fire, spell: Cast(3), area: Rect(1, 2), pet: None, tag: Some('x'), factions: {"pirates": -100, 1: 2})"#;
+        assert_eq!(to_json(src).unwrap(), json!({
+            "name": "a",
+            "pos": [1, 2.5],
+            "power": "Fire",
+            "spell": {"Cast": 3},
+            "area": {"Rect": [1, 2]},
+            "pet": null,
+            "tag": "x",
+            "factions": {"pirates": -100, "1": 2},
+        }));
+        assert_eq!(to_json("Player(hp: 3, pet: Dog(age: 2))").unwrap(), json!({"Player": {"hp": 3, "pet": {"Dog": {"age": 2}}}}));
+        assert!(to_json("[1,").is_err());
+    }
+
+    #[test]
+    fn from_json_test() {
+        let value = json!({"name": "a", "stats": [1, 2.5], "spell": {"Cast": 3}, "pet": {"Dog": {"age": 2}}, "factions": {"pirate ship": null}});
+        let expected = "(\n    factions: {\n        \"pirate ship\": None,\n    },\n    name: \"a\",\n    pet: Dog(\n        age: 2,\n    ),\n    spell: Cast(3),\n    stats: [\n        1,\n        2.5,\n    ],\n)";
+        assert_eq!(from_json(&value, PrettyConfig::default()).unwrap(), expected);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let value = json!({"a": [1, {"Rect": [1, 2]}], "b": {"Fire": "x"}, "c": true, "d": {"Dog": {"age": 2}}});
+        assert_eq!(to_json(&from_json(&value, PrettyConfig::default()).unwrap()).unwrap(), value);
+    }
+}
@@ -1,3 +1,150 @@
 #![forbid(unsafe_code)]
+#![allow(clippy::needless_return)]
 
-pub mod deserial;
\ No newline at end of file
+use std::fmt::Write;
+
+use deserial::{ErrorKind, RonDeserialize, RonDeserializer, RonError, RonEvent};
+use serial::{PrettyConfig, RonSerialize, RonSerializer};
+
+pub mod builder;
+pub mod codec;
+pub mod deserial;
+pub mod diff;
+pub mod document;
+#[cfg(any(feature = "toml", feature = "yaml"))]
+pub mod interop;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod merge;
+pub mod migration;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod query;
+pub mod schema;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod serial;
+pub mod testing;
+pub mod value;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+mod path;
+
+/// Deserializes a `T` from `src`, failing if anything but whitespace follows its value.
+pub fn from_str<'a, T: RonDeserialize<'a>>(src: &'a str) -> Result<T, RonError> {
+    let mut de = RonDeserializer::new(src);
+    let value = T::deserialize(&mut de)?;
+    return match de.next_event()? {
+        RonEvent::Eof => Ok(value),
+        _ => Err(de.error(ErrorKind::TrailingCharacters)),
+    };
+}
+
+/// Deserializes the value at `path` as a `T`, reading only as much of `src` as needed to reach it. Paths follow the
+/// syntax of [`query`], and nothing being there is an error.
+/// ```
+/// let src = "Config(window: (title: \"Game\"), settings: (resolution: (1920, 1080), vsync: true))";
+/// assert_eq!(light_ron::extract::<(u32, u32)>(src, "settings.resolution"), Ok((1920, 1080)));
+/// ```
+pub fn extract<'a, T: RonDeserialize<'a>>(src: &'a str, path: &str) -> Result<T, RonError> {
+    return match query::get_as(src, path)? {
+        Some(x) => Ok(x),
+        None => Err(RonError::new(format!("There's no value at '{path}'"))),
+    };
+}
+
+/// Serializes `value` with the default [`PrettyConfig`]. Fails if `value` can't be represented in RON, like a `u128`
+/// past `i128::MAX`.
+pub fn to_string<T: RonSerialize + ?Sized>(value: &T) -> Result<String, RonError> {
+    return to_string_pretty(value, PrettyConfig::default());
+}
+
+/// Serializes `value` with the given formatting rules. Fails like [`to_string`].
+pub fn to_string_pretty<T: RonSerialize + ?Sized>(value: &T, config: PrettyConfig) -> Result<String, RonError> {
+    let mut ser = RonSerializer::new(String::new(), config);
+    if value.serialize(&mut ser).is_err() {
+        return Err(RonError::new("The value can't be represented in RON"));
+    }
+    return Ok(ser.into_inner());
+}
+
+/// Rewrites `src` with every map sorted by key and every struct's fields sorted by name, using the default
+/// [`PrettyConfig`]. Documents that only differ in those orders, formatting or comments give the same output.
+pub fn canonicalize(src: &str) -> Result<String, RonError> {
+    let mut value: value::RonValue = from_str(src)?;
+    value.canonicalize();
+    return to_string(&value);
+}
+
+/// Pipes every event of `de` into `ser` until the end of the document, so the output follows `ser`'s formatting rules.
+///
+/// Fails on the first error of `de`, including `Error` events in recovery mode.
+pub fn transcode<W: Write>(de: &mut RonDeserializer, ser: &mut RonSerializer<W>) -> Result<(), RonError> {
+    loop {
+        match de.next_event()? {
+            RonEvent::Eof => return Ok(()),
+            RonEvent::Error(err) => return Err(err),
+            event => ser.write_event(event).map_err(|_| RonError::new("Failed to write the output"))?,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str_test() {
+        assert_eq!(from_str::<i32>("  -42 "), Ok(-42));
+        assert_eq!(from_str::<&str>("\"abc\""), Ok("abc"));
+        assert!(from_str::<i32>("1 2").is_err());
+        assert!(from_str::<i32>("").is_err());
+    }
+
+    #[test]
+    fn extract_test() {
+        let src = "(settings: (resolution: (1920, 1080), names: {\"a\": Some(\"b\")}), big: [1, 2, 3])";
+        assert_eq!(extract::<(u16, u16)>(src, "settings.resolution"), Ok((1920, 1080)));
+        assert_eq!(extract::<Option<&str>>(src, "settings.names.a"), Ok(Some("b")));
+        assert_eq!(extract::<i32>(src, "big[2]"), Ok(3));
+        assert_eq!(extract::<i32>(src, "settings.fullscreen").unwrap_err().message(), "There's no value at 'settings.fullscreen'");
+        assert_eq!(extract::<i32>(src, "settings.resolution").unwrap_err().span(), Some(deserial::Span::new(24, 25)));
+    }
+
+    #[test]
+    fn to_string_test() {
+        assert_eq!(to_string(&42u8), Ok(String::from("42")));
+        assert_eq!(to_string("abc"), Ok(String::from("\"abc\"")));
+        assert_eq!(to_string(&u64::MAX), Ok(String::from("18446744073709551615")));
+        assert_eq!(to_string(&u128::MAX).unwrap_err().message(), "The value can't be represented in RON");
+    }
+
+    #[test]
+    fn canonicalize_test() {
+        let a = canonicalize("Player(name: \"x\", factions: {\"pirates\": -100, \"crabs\": 30})").unwrap();
+        let b = canonicalize("// Same player\nPlayer(factions: {\"crabs\": 30, \"pirates\": -100}, name: \"x\",)").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a, "Player(\n    factions: {\n        \"crabs\": 30,\n        \"pirates\": -100,\n    },\n    name: \"x\",\n)");
+    }
+
+    #[test]
+    fn transcode_test() {
+        let mut de = RonDeserializer::new("Player(name: \"x\", pos: (1.0, 2.0), tags: [])");
+        let mut ser = RonSerializer::new(String::new(), PrettyConfig { indentor: String::from("  "), ..Default::default() });
+        transcode(&mut de, &mut ser).unwrap();
+        assert_eq!(ser.into_inner(), "Player(\n  name: \"x\",\n  pos: (1.0, 2.0),\n  tags: [],\n)");
+
+        let mut de = RonDeserializer::new("[1, 2");
+        let mut ser = RonSerializer::new(String::new(), PrettyConfig::default());
+        assert!(transcode(&mut de, &mut ser).is_err());
+    }
+
+    #[test]
+    fn round_trip_test() {
+        assert_eq!(from_str::<f64>(&to_string(&1.5f64).unwrap()), Ok(1.5));
+        assert_eq!(from_str::<String>(&to_string("Some text").unwrap()), Ok(String::from("Some text")));
+        assert_eq!(from_str::<u64>(&to_string(&u64::MAX).unwrap()), Ok(u64::MAX));
+        assert_eq!(from_str::<i128>(&to_string(&i128::MIN).unwrap()), Ok(i128::MIN));
+    }
+}
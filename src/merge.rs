@@ -0,0 +1,247 @@
+//! Layering documents over each other, like user settings over the defaults, and replaying patches made by
+//! [`diff`](crate::diff).
+//!
+//! The fields of structs and the entries of maps in the overlay replace the ones in the base with the same name or
+//! key, going down into the ones that are structs or maps themselves. Everything else in the overlay replaces what
+//! the base has, except lists, which can be appended instead.
+//! ```
+//! # use light_ron::merge::{merge_str, ListMerge, MergeOptions};
+//! let defaults = "(volume: 0.5, keys: {\"jump\": Space, \"crouch\": C}, mods: [Base])";
+//! let user = "(keys: {\"crouch\": Ctrl}, mods: [Extra])";
+//!
+//! let merged = merge_str(defaults, user, MergeOptions::default()).unwrap();
+//! assert_eq!(merged, light_ron::from_str("(volume: 0.5, keys: {\"jump\": Space, \"crouch\": Ctrl}, mods: [Extra])").unwrap());
+//!
+//! let merged = merge_str(defaults, user, MergeOptions { lists: ListMerge::Append }).unwrap();
+//! assert_eq!(merged.get("mods").unwrap().to_string(), "[\n    Base,\n    Extra,\n]");
+//! ```
+
+use crate::deserial::RonError;
+use crate::diff::Change;
+use crate::path::{self, Segment};
+use crate::value::RonValue;
+
+/// How lists in the overlay are combined with the ones in the base.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ListMerge {
+    /// The overlay's list replaces the base's.
+    #[default]
+    Replace,
+    /// The overlay's elements go after the base's.
+    Append,
+}
+
+/// Settings of [`merge`].
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    pub lists: ListMerge,
+}
+
+/// Parses both documents and layers `overlay` over `base`.
+pub fn merge_str(base: &str, overlay: &str, options: MergeOptions) -> Result<RonValue, RonError> {
+    return Ok(merge(crate::from_str(base)?, crate::from_str(overlay)?, &options));
+}
+
+/// Layers `overlay` over `base`.
+///
+/// Structs are only merged if they have the same name or one of them has none, and the overlay's name is kept if it
+/// has one. Structs with different names replace each other, since they're usually different enum variants.
+pub fn merge(base: RonValue, overlay: RonValue, options: &MergeOptions) -> RonValue {
+    return match (base, overlay) {
+        (RonValue::Some(a), RonValue::Some(b)) => RonValue::Some(Box::new(merge(*a, *b, options))),
+        (RonValue::Struct { name: n1, fields: mut a }, RonValue::Struct { name: n2, fields: b }) if n1.is_none() || n2.is_none() || n1 == n2 => {
+            for (name, y) in b {
+                match a.iter().position(|x| x.0 == name) {
+                    Some(i) => a[i].1 = merge(std::mem::replace(&mut a[i].1, RonValue::None), y, options),
+                    None => a.push((name, y)),
+                }
+            }
+            RonValue::Struct { name: n2.or(n1), fields: a }
+        },
+        (RonValue::Map(mut a), RonValue::Map(b)) => {
+            for (key, y) in b {
+                match a.iter().position(|x| x.0 == key) {
+                    Some(i) => a[i].1 = merge(std::mem::replace(&mut a[i].1, RonValue::None), y, options),
+                    None => a.push((key, y)),
+                }
+            }
+            RonValue::Map(a)
+        },
+        (RonValue::List(mut a), RonValue::List(b)) if options.lists == ListMerge::Append => {
+            a.extend(b);
+            RonValue::List(a)
+        },
+        (_, b) => b,
+    };
+}
+
+/// Applies changes made by [`diff`](crate::diff::diff), so applying `diff(a, b)` to `a` turns it into `b`.
+///
+/// The old values of the changes aren't checked. Map keys are the ones written in the paths: a string for a field like
+/// `keys.jump`, and the exact value for brackets like `keys["a.b"]` or `keys[1]`. Unlike [`query`](crate::query), a
+/// field doesn't select an identifier key like `{jump: ..}`. Only a change of the whole document can have an empty path.
+pub fn apply(value: &mut RonValue, changes: &[Change]) -> Result<(), RonError> {
+    // Removing list elements moves the ones after them, so removals go last and from the back.
+    for x in changes.iter().filter(|x| !matches!(x, Change::Removed { .. })) {
+        apply_one(value, x)?;
+    }
+    for x in changes.iter().rev().filter(|x| matches!(x, Change::Removed { .. })) {
+        apply_one(value, x)?;
+    }
+    return Ok(());
+}
+
+fn apply_one(value: &mut RonValue, change: &Change) -> Result<(), RonError> {
+    let path = change.path();
+    let missing = || RonError::new(format!("No value at '{path}'"));
+
+    let mut segments = path::parse(path)?;
+    let Some(last) = segments.pop() else {
+        return match change {
+            Change::Changed { new, .. } => {
+                *value = new.clone();
+                Ok(())
+            },
+            _ => Err(missing()),
+        };
+    };
+
+    let mut parent = value;
+    for x in segments {
+        parent = child_mut(parent, &x).ok_or_else(missing)?;
+    }
+
+    match change {
+        Change::Changed { new, .. } => *child_mut(parent, &last).ok_or_else(missing)? = new.clone(),
+        Change::Added { value, .. } => match (parent.inner_mut(), last) {
+            (RonValue::Struct { fields, .. }, Segment::Field(name)) => fields.push((String::from(name), value.clone())),
+            (RonValue::List(values), Segment::Index(i)) if i <= values.len() => values.insert(i, value.clone()),
+            (RonValue::Map(entries), last) => entries.push((map_key(last), value.clone())),
+            _ => return Err(missing()),
+        },
+        Change::Removed { .. } => match (parent.inner_mut(), last) {
+            (RonValue::Struct { fields, .. }, Segment::Field(name)) => {
+                let i = fields.iter().position(|x| x.0 == name).ok_or_else(missing)?;
+                fields.remove(i);
+            },
+            (RonValue::List(values), Segment::Index(i)) if i < values.len() => {
+                values.remove(i);
+            },
+            (RonValue::Map(entries), last) => {
+                let i = entries.iter().position(|x| last.selects_exact_key(&x.0)).ok_or_else(missing)?;
+                entries.remove(i);
+            },
+            _ => return Err(missing()),
+        },
+    }
+    return Ok(());
+}
+
+/// The child of `value` at `segment`, where map entries are selected by their exact key.
+fn child_mut<'a>(value: &'a mut RonValue, segment: &Segment) -> Option<&'a mut RonValue> {
+    return match value.inner_mut() {
+        RonValue::Map(entries) => entries.iter_mut().find(|x| segment.selects_exact_key(&x.0)).map(|x| &mut x.1),
+        x => x.child_mut(segment),
+    };
+}
+
+/// The key of a map entry added at `segment`.
+fn map_key(segment: Segment) -> RonValue {
+    return match segment {
+        Segment::Field(name) => RonValue::Str(String::from(name)),
+        Segment::Index(i) => RonValue::Int(i as i128),
+        Segment::Key(x) => x,
+    };
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::diff_str;
+
+    fn merged(base: &str, overlay: &str, lists: ListMerge) -> String {
+        return merge_str(base, overlay, MergeOptions { lists }).unwrap().to_string();
+    }
+
+    fn value(src: &str) -> RonValue {
+        return crate::from_str(src).unwrap();
+    }
+
+    #[test]
+    fn merge_test() {
+        let base = "Settings(audio: (volume: 0.5, muted: false), name: \"a\")";
+        let overlay = "(audio: (muted: true), extra: Some(1))";
+        assert_eq!(merged(base, overlay, ListMerge::Replace), value("Settings(audio: (volume: 0.5, muted: true), name: \"a\", extra: Some(1))").to_string());
+
+        assert_eq!(merged("Player(a: 1)", "Enemy(b: 2)", ListMerge::Replace), value("Enemy(b: 2)").to_string());
+        assert_eq!(merged("Some((a: 1, b: 2))", "Some((b: 3))", ListMerge::Replace), value("Some((a: 1, b: 3))").to_string());
+        assert_eq!(merged("(a: 1)", "5", ListMerge::Replace), "5");
+    }
+
+    #[test]
+    fn map_test() {
+        assert_eq!(merged("{1: (a: 1), 2: (a: 2)}", "{2: (b: 3), 4: (a: 4)}", ListMerge::Replace), value("{1: (a: 1), 2: (a: 2, b: 3), 4: (a: 4)}").to_string());
+    }
+
+    #[test]
+    fn list_test() {
+        assert_eq!(merged("(a: [1, 2])", "(a: [3])", ListMerge::Replace), value("(a: [3])").to_string());
+        assert_eq!(merged("(a: [1, 2])", "(a: [3])", ListMerge::Append), value("(a: [1, 2, 3])").to_string());
+        assert_eq!(merged("(a: (1, 2))", "(a: (3,))", ListMerge::Append), value("(a: (3,))").to_string());
+    }
+
+    #[test]
+    fn apply_test() {
+        let pairs = [
+            ("(a: 1, b: [1, 2, 3], c: {\"x\": 1, 2: 'y'})", "(a: 2, b: [1], c: {2: 'z', \"w\": 5}, d: None)"),
+            ("[1, 2]", "[0, 2, 3, 4]"),
+            ("Some((a: [1]))", "Some((a: [1, 2]))"),
+            ("(a: 1)", "Other(1)"),
+        ];
+        for (old, new) in pairs {
+            let changes = diff_str(old, new).unwrap();
+            let patch: Vec<Change> = crate::from_str(&crate::to_string(&changes).unwrap()).unwrap();
+
+            let mut value = value(old);
+            apply(&mut value, &patch).unwrap();
+            assert_eq!(value, crate::from_str(new).unwrap(), "{old} -> {new}");
+        }
+    }
+
+    #[test]
+    fn apply_keys_test() {
+        let pairs = [
+            ("{\"\": 1, \"1\": 2, \"true\": 3, \"a.b\": 4, \"x[0]\": 5, 1: 6, One: 7, \"plain\": 8}", "{\"\": 0, 1: 0, \"x[0]\": 0, \"a]\": 0, true: 0, Two: 0, \"new\": 0, 2: 0}"),
+            ("{One: 1, \"One\": 2}", "{One: 1, \"One\": 3}"),
+            ("{One: 1, \"Two\": 2}", "{\"One\": 1, Two: 2}"),
+        ];
+        for (old, new) in pairs {
+            let changes = diff_str(old, new).unwrap();
+            let patch: Vec<Change> = crate::from_str(&crate::to_string(&changes).unwrap()).unwrap();
+            assert_eq!(patch, changes);
+
+            // Added entries go at the end of the map, so the order is ignored.
+            let (mut value, mut new) = (value(old), value(new));
+            apply(&mut value, &patch).unwrap();
+            value.canonicalize();
+            new.canonicalize();
+            assert_eq!(value, new, "{old}");
+        }
+
+        // A field only selects a string key, even if an identifier key has the same text.
+        let mut value = value("{One: 1, \"One\": 2}");
+        apply(&mut value, &[Change::Changed { path: String::from("One"), old: RonValue::Int(2), new: RonValue::Int(3) }]).unwrap();
+        assert_eq!(value, crate::from_str("{One: 1, \"One\": 3}").unwrap());
+    }
+
+    #[test]
+    fn apply_error_test() {
+        let mut value = value("(a: [1])");
+        assert!(apply(&mut value, &[Change::Changed { path: String::from("b"), old: RonValue::None, new: RonValue::Int(1) }]).is_err());
+        assert!(apply(&mut value, &[Change::Added { path: String::from("a[3]"), value: RonValue::Int(1) }]).is_err());
+        assert!(apply(&mut value, &[Change::Removed { path: String::from("a.b"), value: RonValue::Int(1) }]).is_err());
+        assert!(apply(&mut value, &[Change::Removed { path: String::from(""), value: RonValue::Int(1) }]).is_err());
+        assert!(apply(&mut value, &[Change::Added { path: String::from(""), value: RonValue::Int(1) }]).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+//! Upgrading documents written by older versions of a program, like save files, before decoding them.
+//!
+//! Documents keep their version in a field of their top level struct, like `Save(version: 3, ...)`. Each registered
+//! step turns a document of one version into the next one by editing its [`RonValue`], and the upgraded tree is then
+//! decoded into the current type.
+//! ```
+//! # use light_ron::migration::Migrations;
+//! # use light_ron::value::RonValue;
+//! let migrations = Migrations::new("version", 2)
+//!     // Version 1 kept a single name, which became a list.
+//!     .step(0, |save| {
+//!         let RonValue::Struct { fields, .. } = save else { return Ok(()) };
+//!         let i = fields.iter().position(|x| x.0 == "name").unwrap();
+//!         let (_, name) = fields.remove(i);
+//!         fields.push((String::from("names"), RonValue::List(vec![name])));
+//!         return Ok(());
+//!     })
+//!     // Version 2 added the gold.
+//!     .step(1, |save| {
+//!         let RonValue::Struct { fields, .. } = save else { return Ok(()) };
+//!         fields.push((String::from("gold"), RonValue::Int(0)));
+//!         return Ok(());
+//!     });
+//!
+//! let save: RonValue = migrations.load("Save(version: 0, name: \"a\")").unwrap();
+//! assert_eq!(save, light_ron::from_str("Save(version: 2, names: [\"a\"], gold: 0)").unwrap());
+//! ```
+
+use std::collections::BTreeMap;
+
+use crate::deserial::{RonDeserialize, RonError};
+use crate::serial::PrettyConfig;
+use crate::value::RonValue;
+
+type Step = Box<dyn Fn(&mut RonValue) -> Result<(), RonError>>;
+
+/// The steps that upgrade documents up to the current version.
+pub struct Migrations {
+    field: String,
+    current: u64,
+    steps: BTreeMap<u64, Step>,
+}
+
+impl Migrations {
+    /// Migrations for documents that keep their version in `field`, where `current` is the version written by the
+    /// program now.
+    pub fn new(field: impl Into<String>, current: u64) -> Self {
+        return Self { field: field.into(), current, steps: BTreeMap::new() };
+    }
+
+    /// Registers the step that upgrades documents of version `from` to `from + 1`. The version field is updated after
+    /// it runs, so the step doesn't have to.
+    pub fn step(mut self, from: u64, step: impl Fn(&mut RonValue) -> Result<(), RonError> + 'static) -> Self {
+        self.steps.insert(from, Box::new(step));
+        return self;
+    }
+
+    /// The version of the document in `value`.
+    pub fn version(&self, value: &RonValue) -> Result<u64, RonError> {
+        let Some(version) = value.get(&self.field) else {
+            return Err(RonError::new(format!("The document doesn't have a '{}' field with its version", self.field)));
+        };
+        return match version.as_i64().and_then(|x| u64::try_from(x).ok()) {
+            Some(x) => Ok(x),
+            None => Err(RonError::new(format!("Expected the version to be a positive integer, found {}", version.type_name()))),
+        };
+    }
+
+    /// Runs every step from the version of `value` up to the current one. Documents from newer versions are errors.
+    pub fn upgrade(&self, value: &mut RonValue) -> Result<(), RonError> {
+        let mut version = self.version(value)?;
+        if version > self.current {
+            return Err(RonError::new(format!("The document is from version {version}, newer than the supported {}", self.current)));
+        }
+        while version < self.current {
+            let Some(step) = self.steps.get(&version) else {
+                return Err(RonError::new(format!("There's no migration from version {version}")));
+            };
+            step(value)?;
+            version += 1;
+            *value.get_mut(&self.field).ok_or_else(|| RonError::new(format!("A migration removed the '{}' field", self.field)))? = RonValue::Int(i128::from(version));
+        }
+        return Ok(());
+    }
+
+    /// Parses `src`, upgrades it, and decodes it as a `T`.
+    ///
+    /// Documents that are already at the current version are decoded straight from `src`. Errors decoding upgraded
+    /// ones point into the upgraded document, since `src` may not have what they refer to.
+    pub fn load<T: for<'a> RonDeserialize<'a>>(&self, src: &str) -> Result<T, RonError> {
+        let mut value: RonValue = crate::from_str(src)?;
+        if self.version(&value)? == self.current {
+            return crate::from_str(src);
+        }
+        self.upgrade(&mut value)?;
+        let upgraded = crate::to_string_pretty(&value, PrettyConfig::compact())?;
+        return crate::from_str(&upgraded);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrations() -> Migrations {
+        return Migrations::new("version", 2)
+            .step(0, |x| {
+                *x.get_mut("hp").unwrap() = RonValue::from(x["hp"].as_i64().unwrap() * 10);
+                return Ok(());
+            })
+            .step(1, |x| {
+                let RonValue::Struct { fields, .. } = x else { return Err(RonError::new("Expected a struct")) };
+                fields.push((String::from("mp"), RonValue::Int(5)));
+                return Ok(());
+            });
+    }
+
+    #[test]
+    fn upgrade_test() {
+        let mut value: RonValue = crate::from_str("Save(version: 0, hp: 3)").unwrap();
+        migrations().upgrade(&mut value).unwrap();
+        assert_eq!(value, crate::from_str("Save(version: 2, hp: 30, mp: 5)").unwrap());
+
+        let mut value: RonValue = crate::from_str("Save(version: 1, hp: 3)").unwrap();
+        migrations().upgrade(&mut value).unwrap();
+        assert_eq!(value, crate::from_str("Save(version: 2, hp: 3, mp: 5)").unwrap());
+    }
+
+    #[test]
+    fn load_test() {
+        let load = |src| migrations().load::<RonValue>(src).map(|x| (x["hp"].as_i64(), x["mp"].as_i64()));
+        assert_eq!(load("Save(version: 0, hp: 3)"), Ok((Some(30), Some(5))));
+        assert_eq!(load("Save(version: 2, hp: 3, mp: 1)"), Ok((Some(3), Some(1))));
+    }
+
+    #[test]
+    fn error_test() {
+        let load = |src| migrations().load::<RonValue>(src).unwrap_err().message();
+        assert_eq!(load("Save(hp: 3)"), "The document doesn't have a 'version' field with its version");
+        assert_eq!(load("Save(version: \"1\")"), "Expected the version to be a positive integer, found a string");
+        assert_eq!(load("Save(version: 3)"), "The document is from version 3, newer than the supported 2");
+        let err = Migrations::new("version", 1).load::<RonValue>("(version: 0)").unwrap_err();
+        assert_eq!(err.message(), "There's no migration from version 0");
+    }
+}
@@ -0,0 +1,203 @@
+//! Reading the elements of a big top level list on several threads, behind the `parallel` feature.
+//!
+//! The elements are found by a quick scan that only counts brackets and skips strings, chars and comments, and each
+//! one is then read by its own [`RonDeserializer`] on [rayon](https://docs.rs/rayon)'s thread pool. Syntax errors
+//! inside the elements are only found by the deserializers, and point at the right place of the whole document.
+//! ```
+//! let records: Vec<(u32, f32)> = light_ron::parallel::par_from_list("[(1, 0.5), (2, 1.5), (3, 2.5)]").unwrap();
+//! assert_eq!(records, [(1, 0.5), (2, 1.5), (3, 2.5)]);
+//! ```
+
+use rayon::prelude::*;
+
+use crate::deserial::{DeserializerOptions, ErrorKind, RonDeserialize, RonDeserializer, RonError, RonEvent, Span};
+
+/// Finds the span of each element of the list that makes up `src`, without reading them.
+/// ```
+/// # use light_ron::parallel::split_list;
+/// let src = "[A(x: \"],\"), /* , */ [1, 2],]";
+/// let elements: Vec<_> = split_list(src).unwrap().into_iter().map(|x| &src[x.start..x.end]).collect();
+/// assert_eq!(elements, ["A(x: \"],\")", "/* , */ [1, 2]"]);
+/// ```
+pub fn split_list(src: &str) -> Result<Vec<Span>, RonError> {
+    let bytes = src.as_bytes();
+    let mut pos = skip_blank(bytes, if src.starts_with('\u{feff}') { 3 } else { 0 });
+    if bytes.get(pos) != Some(&b'[') {
+        return Err(RonError::at(ErrorKind::Message(String::from("Expected a list")), Span::new(pos, pos + 1), src));
+    }
+    pos += 1;
+
+    let mut elements = Vec::new();
+    loop {
+        let start = skip_whitespace(bytes, pos);
+        let mut depth = 0usize;
+        pos = start;
+        let end = loop {
+            let Some(&byte) = bytes.get(pos) else {
+                return Err(RonError::at(ErrorKind::UnexpectedEof, Span::new(src.len(), src.len()), src));
+            };
+            match byte {
+                b'(' | b'[' | b'{' => depth += 1,
+                b']' | b',' if depth == 0 => break pos,
+                b')' | b']' | b'}' => depth = depth.saturating_sub(1),
+                b'"' | b'\'' => {
+                    pos = skip_quoted(bytes, pos);
+                    continue;
+                },
+                b'/' if matches!(bytes.get(pos + 1), Some(b'/' | b'*')) => {
+                    pos = skip_comment(bytes, pos);
+                    continue;
+                },
+                _ => {},
+            }
+            pos += 1;
+        };
+
+        let element = src[start..end].trim_end();
+        if bytes[end] == b',' || !element.is_empty() {
+            elements.push(Span::new(start, start + element.len()));
+        }
+        pos = end + 1;
+        if bytes[end] == b']' {
+            break;
+        }
+        // A trailing comma.
+        if bytes.get(skip_blank(bytes, pos)) == Some(&b']') {
+            pos = skip_blank(bytes, pos) + 1;
+            break;
+        }
+    }
+
+    let rest = skip_blank(bytes, pos);
+    if rest < bytes.len() {
+        return Err(RonError::at(ErrorKind::TrailingCharacters, Span::new(rest, rest + 1), src));
+    }
+    return Ok(elements);
+}
+
+/// Reads every element of the list that makes up `src` as a `T`, in parallel. If several elements have errors, any of
+/// them may be returned.
+pub fn par_from_list<'a, T: RonDeserialize<'a> + Send>(src: &'a str) -> Result<Vec<T>, RonError> {
+    return par_from_list_with_options(src, &DeserializerOptions::default());
+}
+
+/// Like [`par_from_list`], reading each element with the given options.
+pub fn par_from_list_with_options<'a, T: RonDeserialize<'a> + Send>(src: &'a str, options: &DeserializerOptions) -> Result<Vec<T>, RonError> {
+    let elements = split_list(src)?;
+    return elements.into_par_iter().map(|x| read_element(src, x, options)).collect();
+}
+
+fn read_element<'a, T: RonDeserialize<'a>>(src: &'a str, span: Span, options: &DeserializerOptions) -> Result<T, RonError> {
+    let mut de = RonDeserializer::with_options(&src[span.start..span.end], options.clone());
+    let read = |de: &mut RonDeserializer<'a>| {
+        let value = T::deserialize(de)?;
+        return match de.next_non_comment()? {
+            RonEvent::Eof => Ok(value),
+            _ => Err(de.error(ErrorKind::TrailingCharacters)),
+        };
+    };
+    return read(&mut de).map_err(|err| err.offset(span.start, src));
+}
+
+/// Skips whitespace and comments.
+fn skip_blank(bytes: &[u8], mut pos: usize) -> usize {
+    loop {
+        pos = skip_whitespace(bytes, pos);
+        if bytes.get(pos) == Some(&b'/') && matches!(bytes.get(pos + 1), Some(b'/' | b'*')) {
+            pos = skip_comment(bytes, pos);
+        } else {
+            return pos;
+        }
+    }
+}
+
+fn skip_whitespace(bytes: &[u8], pos: usize) -> usize {
+    return bytes[pos.min(bytes.len())..].iter().position(|x| !x.is_ascii_whitespace()).map_or(bytes.len(), |x| pos + x);
+}
+
+/// Skips the string or char that starts at `pos`, returning the position after its closing quote.
+fn skip_quoted(bytes: &[u8], mut pos: usize) -> usize {
+    let quote = bytes[pos];
+    pos += 1;
+    while let Some(&byte) = bytes.get(pos) {
+        match byte {
+            b'\\' => pos += 2,
+            _ if byte == quote => return pos + 1,
+            _ => pos += 1,
+        }
+    }
+    return bytes.len();
+}
+
+/// Skips the `//` or `/* */` comment that starts at `pos`. Block comments can be nested.
+fn skip_comment(bytes: &[u8], mut pos: usize) -> usize {
+    if bytes[pos + 1] == b'/' {
+        return bytes[pos..].iter().position(|x| *x == b'\n').map_or(bytes.len(), |x| pos + x + 1);
+    }
+    let mut depth = 0;
+    while pos < bytes.len() {
+        match bytes.get(pos..pos + 2) {
+            Some(b"/*") => {
+                depth += 1;
+                pos += 2;
+            },
+            Some(b"*/") => {
+                depth -= 1;
+                pos += 2;
+                if depth == 0 {
+                    return pos;
+                }
+            },
+            _ => pos += 1,
+        }
+    }
+    return bytes.len();
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(src: &str) -> Vec<&str> {
+        return split_list(src).unwrap().into_iter().map(|x| &src[x.start..x.end]).collect();
+    }
+
+    #[test]
+    fn split_test() {
+        assert_eq!(split("[]"), Vec::<&str>::new());
+        assert_eq!(split(" // list\n[ 1 ,2, ] "), ["1", "2"]);
+        assert_eq!(split("\u{feff}[1]"), ["1"]);
+        assert_eq!(split("[(a: [1, 2], b: {\"x\": (3)}), Some(\"a, b]\"), ',', '\\'', b\"]\"]"), ["(a: [1, 2], b: {\"x\": (3)})", "Some(\"a, b]\")", "','", "'\\''", "b\"]\""]);
+        assert_eq!(split("[1 /* a /* ] */ , */, 2 // ]\n]"), ["1 /* a /* ] */ , */", "2 // ]"]);
+    }
+
+    #[test]
+    fn split_error_test() {
+        assert_eq!(split_list("(1, 2)").unwrap_err().message(), "Expected a list");
+        assert_eq!(split_list("[1, (2, 3]").unwrap_err().kind(), &ErrorKind::UnexpectedEof);
+        assert_eq!(split_list("[1] 2").unwrap_err().span(), Some(Span::new(4, 5)));
+    }
+
+    #[test]
+    fn par_test() {
+        let src = format!("[{}]", (0..1000).map(|x| format!("(id: {x}, name: \"n{x}\")")).collect::<Vec<_>>().join(", "));
+        let records: Vec<crate::value::RonValue> = par_from_list(&src).unwrap();
+        assert_eq!(records.len(), 1000);
+        assert_eq!(records[999]["name"].as_str(), Some("n999"));
+
+        let names: Vec<&str> = par_from_list("[\"a\", \"b\"]").unwrap();
+        assert_eq!(names, ["a", "b"]);
+    }
+
+    #[test]
+    fn error_test() {
+        let src = "[\n    (1, 2),\n    (3, true),\n]";
+        let err = par_from_list::<(i32, i32)>(src).unwrap_err();
+        assert_eq!(err.span(), Some(Span::new(22, 26)));
+        assert_eq!(err.line_col(), Some((3, 9)));
+        assert!(err.to_string().contains("(3, true)"));
+        assert!(par_from_list::<i32>("[1, 2 3]").is_err());
+        assert!(par_from_list::<i32>("[1,, 2]").is_err());
+    }
+}
@@ -0,0 +1,104 @@
+use crate::deserial::RonError;
+use crate::value::{key_matches, RonValue};
+
+/// One step of a path like `player.powers[2].name` or `keys["a.b"]`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Segment<'a> {
+    /// A struct field, or the key of a map entry when the key is a string or an identifier.
+    Field(&'a str),
+
+    /// An element of a list or a tuple, or the entry of a map whose key is that integer.
+    Index(usize),
+
+    /// The entry of a map whose key is exactly this value, written as its RON text in brackets.
+    Key(RonValue),
+}
+
+impl Segment<'_> {
+    /// Whether this segment selects the map entry with `key`.
+    pub(crate) fn selects_key(&self, key: &RonValue) -> bool {
+        return match self {
+            Segment::Field(name) => key_matches(key, name),
+            Segment::Index(i) => *key == RonValue::Int(*i as i128),
+            Segment::Key(x) => key == x,
+        };
+    }
+
+    /// Like [`selects_key`](Segment::selects_key), but a field only selects the string key with that text, which is how
+    /// [`diff`](crate::diff) writes them.
+    pub(crate) fn selects_exact_key(&self, key: &RonValue) -> bool {
+        return match self {
+            Segment::Field(name) => matches!(key, RonValue::Str(x) if x == name),
+            x => x.selects_key(key),
+        };
+    }
+}
+
+/// Splits `path` into its segments. An empty path selects the whole document.
+pub(crate) fn parse(path: &str) -> Result<Vec<Segment<'_>>, RonError> {
+    let invalid = || RonError::new(format!("Invalid path '{path}'"));
+
+    let mut segments = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(tail) = rest.strip_prefix('[') {
+            let (segment, end) = bracket(tail).ok_or_else(invalid)?;
+            segments.push(segment);
+            rest = &tail[end + 1..];
+        } else {
+            // Fields after the first one are separated by a dot, indices aren't.
+            if !segments.is_empty() {
+                rest = rest.strip_prefix('.').ok_or_else(invalid)?;
+            }
+            let end = rest.find(['.', '[']).unwrap_or(rest.len());
+            if end == 0 {
+                return Err(invalid());
+            }
+            segments.push(Segment::Field(&rest[..end]));
+            rest = &rest[end..];
+        }
+    }
+    return Ok(segments);
+}
+
+/// Reads what's inside brackets, up to the `]` closing them: an index, or the RON text of a map key.
+fn bracket(text: &str) -> Option<(Segment<'_>, usize)> {
+    // Keys can hold a `]` themselves, like `["a]"]`, so the brackets close at the first `]` after a whole value.
+    for (end, _) in text.match_indices(']') {
+        let inner = &text[..end];
+        if let Ok(i) = inner.trim().parse() {
+            return Some((Segment::Index(i), end));
+        }
+        if let Ok(key) = crate::from_str(inner) {
+            return Some((Segment::Key(key), end));
+        }
+    }
+    return None;
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        assert_eq!(parse("").unwrap(), []);
+        assert_eq!(parse("player.name").unwrap(), [Segment::Field("player"), Segment::Field("name")]);
+        assert_eq!(parse("powers[2].a").unwrap(), [Segment::Field("powers"), Segment::Index(2), Segment::Field("a")]);
+        assert_eq!(parse("[0][1]").unwrap(), [Segment::Index(0), Segment::Index(1)]);
+        assert_eq!(parse("keys[\"a.b]\"].x").unwrap(), [Segment::Field("keys"), Segment::Key(RonValue::Str(String::from("a.b]"))), Segment::Field("x")]);
+        assert_eq!(parse("[\"\"][-1][One]").unwrap(), [Segment::Key(RonValue::Str(String::new())), Segment::Key(RonValue::Int(-1)), Segment::Key(RonValue::Enum(String::from("One")))]);
+    }
+
+    #[test]
+    fn invalid_test() {
+        assert!(parse("a..b").is_err());
+        assert!(parse(".a").is_err());
+        assert!(parse("a[x y]").is_err());
+        assert!(parse("a[1").is_err());
+        assert!(parse("a[1]b").is_err());
+        assert!(parse("a[]").is_err());
+        assert!(parse("a[\"b]").is_err());
+    }
+}
@@ -0,0 +1,134 @@
+//! Reading single values out of a document by their path, like `Player.factions.pirates` or `powers[2]`.
+//!
+//! Only the events up to the selected value are read, and everything else on the way is skipped without building it.
+//! ```
+//! # use light_ron::query;
+//! let src = r#"Player(factions: {"pirates": -100}, powers: [Fire, Water, Earth])"#;
+//! assert_eq!(query::get_as::<i32>(src, "Player.factions.pirates"), Ok(Some(-100)));
+//! assert_eq!(query::get_as::<i32>(src, "factions.crabs"), Ok(None));
+//! assert_eq!(query::get(src, "powers[2]").unwrap().unwrap().to_string(), "Earth");
+//! ```
+//!
+//! A field selects a struct field, or a map entry whose key is a string or an identifier with that text, or any other
+//! key with that RON text, like `1` or `'x'`. An index selects an element of a list or a tuple, or the map entry with
+//! that integer key. Any other RON value in brackets selects the map entry with exactly that key, like `["a.b"]` or
+//! `[Fire]`. `Some` is looked through. The path may start with the name of the struct at the top of the document.
+
+use crate::deserial::{RonDeserialize, RonDeserializer, RonError, RonEvent};
+use crate::path::{self, Segment};
+use crate::value::RonValue;
+
+/// The value at `path`, or `None` if there's nothing there.
+pub fn get(src: &str, path: &str) -> Result<Option<RonValue>, RonError> {
+    return get_as(src, path);
+}
+
+/// Deserializes the value at `path` as a `T`, or returns `None` if there's nothing there.
+pub fn get_as<'a, T: RonDeserialize<'a>>(src: &'a str, path: &str) -> Result<Option<T>, RonError> {
+    let segments = path::parse(path)?;
+    let mut de = RonDeserializer::new(src);
+
+    let mut segments = &segments[..];
+    if let Some(Segment::Field(x)) = segments.first() {
+        if let RonEvent::StructStart { name: Some(name) } | RonEvent::TupleStart { name: Some(name) } = de.peek_event()? {
+            if name == x {
+                segments = &segments[1..];
+            }
+        }
+    }
+
+    for x in segments {
+        if !seek(&mut de, x)? {
+            return Ok(None);
+        }
+    }
+    return T::deserialize(&mut de).map(Some);
+}
+
+/// Consumes events until the next one starts the child selected by `segment`, returning whether it exists.
+fn seek(de: &mut RonDeserializer, segment: &Segment) -> Result<bool, RonError> {
+    while *de.peek_event()? == RonEvent::OptionalSomeValue {
+        de.next_event()?;
+    }
+
+    match (de.peek_event()?, segment) {
+        (RonEvent::StructStart { .. }, Segment::Field(field)) => {
+            de.next_event()?;
+            loop {
+                match de.next_event()? {
+                    RonEvent::NamedField(x) if x == *field => return Ok(true),
+                    RonEvent::NamedField(_) => de.skip_value()?,
+                    _ => return Ok(false),
+                }
+            }
+        },
+        (RonEvent::MapStart, segment) => {
+            de.next_event()?;
+            while *de.peek_event()? != RonEvent::MapEnd {
+                if segment.selects_key(&RonValue::deserialize(de)?) {
+                    return Ok(true);
+                }
+                de.skip_value()?;
+            }
+            return Ok(false);
+        },
+        (RonEvent::ListStart | RonEvent::TupleStart { .. }, Segment::Index(i)) => {
+            de.next_event()?;
+            for _ in 0..*i {
+                if matches!(de.peek_event()?, RonEvent::ListEnd | RonEvent::TupleEnd { .. }) {
+                    return Ok(false);
+                }
+                de.skip_value()?;
+            }
+            return Ok(!matches!(de.peek_event()?, RonEvent::ListEnd | RonEvent::TupleEnd { .. }));
+        },
+        _ => return Ok(false),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SRC: &str = r#"Player(
+        name: "SomePlayer69",
+        pos: (0.0, 1.5, 0.0),
+        factions: {"pirates": -100, crabs: 30},
+        powers: [Fire, Some(Water), Inner(a: [(b: 1)])],
+    )"#;
+
+    #[test]
+    fn field_test() {
+        assert_eq!(get_as::<&str>(SRC, "name"), Ok(Some("SomePlayer69")));
+        assert_eq!(get_as::<&str>(SRC, "Player.name"), Ok(Some("SomePlayer69")));
+        assert_eq!(get_as::<i32>(SRC, "factions.crabs"), Ok(Some(30)));
+        assert_eq!(get_as::<i32>(SRC, "factions[\"pirates\"]"), Ok(Some(-100)));
+        assert_eq!(get_as::<i32>(SRC, "factions[crabs]"), Ok(Some(30)));
+        assert_eq!(get_as::<i32>(SRC, "factions[\"crabs\"]"), Ok(None));
+        assert_eq!(get_as::<i32>(SRC, "age"), Ok(None));
+        assert_eq!(get_as::<i32>(SRC, "name.first"), Ok(None));
+    }
+
+    #[test]
+    fn index_test() {
+        assert_eq!(get_as::<f64>(SRC, "pos[1]"), Ok(Some(1.5)));
+        assert_eq!(get(SRC, "powers[1]"), Ok(Some(RonValue::Some(Box::new(RonValue::Enum(String::from("Water")))))));
+        assert_eq!(get_as::<i32>(SRC, "powers[2].a[0].b"), Ok(Some(1)));
+        assert_eq!(get_as::<i32>(SRC, "powers[3]"), Ok(None));
+        assert_eq!(get_as::<i32>(SRC, "[0]"), Ok(None));
+    }
+
+    #[test]
+    fn whole_test() {
+        assert_eq!(get_as::<i32>("5", ""), Ok(Some(5)));
+        assert!(get(SRC, "").unwrap().is_some());
+    }
+
+    #[test]
+    fn error_test() {
+        assert!(get(SRC, "powers[").is_err());
+        assert!(get("[1, 2", "[5]").is_err());
+        assert!(get_as::<i32>(SRC, "name").is_err());
+    }
+}
@@ -0,0 +1,926 @@
+//! Describing the shape of documents: checking them against a [`RonSchema`], and generating Rust type definitions from
+//! an example document.
+//!
+//! For the generated types, every struct, named tuple and group of enum variants found in the document becomes a type.
+//! Values found in the same place, like the elements of a list, are merged: fields missing from some of them become
+//! `Option`s, integers mixed with floats become `f64`, and identifiers or named values with different names become the
+//! variants of an enum. Values that can't be merged, or whose type can't be known, like the elements of an empty list,
+//! become [`RonValue`]s.
+
+use std::fmt::{self, Write};
+
+use crate::deserial::{describe, ErrorKind, RonDeserialize, RonDeserializer, RonError, RonEvent, RonPrimitive, Span};
+use crate::diff::{field_path, key_path};
+use crate::serial::{RonSerialize, RonSerializer};
+use crate::value::RonValue;
+
+/// The expected shape of a document, checked with [`validate`].
+///
+/// Schemas can be written in RON themselves:
+/// ```
+/// # use light_ron::schema::{validate, RonSchema};
+/// let schema: RonSchema = light_ron::from_str(r#"Struct(
+///     name: "Player",
+///     fields: {
+///         "name": String,
+///         "pos": Tuple(Float, Float),
+///         "powers": List(Enum(["Fire", "Water"])),
+///         "pet": Option(String),
+///     },
+///     optional: ["pet"],
+/// )"#).unwrap();
+///
+/// let violations = validate(r#"Player(name: 1, pos: (0.0, 0.0), powers: [Fire, Earth])"#, &schema);
+/// assert_eq!(violations[0].to_string(), "name: Expected a string, found integer 1");
+/// assert_eq!(violations[1].to_string(), "powers[1]: Unknown variant Earth");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub enum RonSchema {
+    /// Any value is accepted.
+    Any,
+    Int,
+    /// Integers are accepted too.
+    Float,
+    Bool,
+    Char,
+    String,
+    Bytes,
+    Option(Box<RonSchema>),
+    List(Box<RonSchema>),
+    Map(Box<RonSchema>, Box<RonSchema>),
+    Tuple(Vec<RonSchema>),
+    Struct(StructSchema),
+    /// The accepted variants, each one with its name.
+    Enum(Vec<(String, VariantSchema)>),
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StructSchema {
+    /// The name the struct must have, if any.
+    pub name: Option<String>,
+    pub fields: Vec<FieldSchema>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldSchema {
+    pub name: String,
+    pub schema: RonSchema,
+    pub required: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VariantSchema {
+    /// A bare identifier, like `Fire`.
+    Unit,
+    /// A named tuple, like `Cast(3)`.
+    Tuple(Vec<RonSchema>),
+    /// A named struct, like `Summon(name: "x")`. The name of the schema is ignored.
+    Struct(StructSchema),
+}
+
+/// A place where a document doesn't follow its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    /// Path of the offending value, in the syntax of [`query`](crate::query).
+    pub path: String,
+    pub span: Span,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            return f.write_str(&self.message);
+        }
+        return write!(f, "{}: {}", self.path, self.message);
+    }
+}
+
+/// Checks the document in `src` against `schema`, returning every violation found in document order.
+///
+/// A syntax error stops the check, and is returned as the last violation.
+pub fn validate(src: &str, schema: &RonSchema) -> Vec<Violation> {
+    let mut validator = Validator { de: RonDeserializer::new(src), violations: Vec::new() };
+    let result = validator.check(schema, "").and_then(|_| match validator.de.next_event()? {
+        RonEvent::Eof => Ok(()),
+        _ => Err(validator.de.error(ErrorKind::TrailingCharacters)),
+    });
+    if let Err(err) = result {
+        validator.violations.push(Violation { path: String::new(), span: err.span().unwrap_or_default(), message: err.message() });
+    }
+    return validator.violations;
+}
+
+struct Validator<'a> {
+    de: RonDeserializer<'a>,
+    violations: Vec<Violation>,
+}
+
+impl Validator<'_> {
+    /// Checks the next value against `schema`, consuming all of its events. Returns the span of the whole value.
+    fn check(&mut self, schema: &RonSchema, path: &str) -> Result<Span, RonError> {
+        let event = self.de.next_event()?;
+        let span = self.de.last_span();
+        self.check_value(schema, event, span, path)?;
+        return Ok(span.to(self.de.last_span()));
+    }
+
+    /// Checks the value that starts with `event`, found at `span`.
+    fn check_value(&mut self, schema: &RonSchema, event: RonEvent, span: Span, path: &str) -> Result<(), RonError> {
+        let expected = match (schema, event) {
+            (RonSchema::Any, event) => return self.skip_rest(event),
+            (RonSchema::Int, RonEvent::Primitive(RonPrimitive::Int(_) | RonPrimitive::TypedInt(..))) => return Ok(()),
+            (RonSchema::Float, RonEvent::Primitive(RonPrimitive::Int(_) | RonPrimitive::Float(_) | RonPrimitive::TypedFloat(..))) => return Ok(()),
+            (RonSchema::Bool, RonEvent::Primitive(RonPrimitive::Bool(_))) => return Ok(()),
+            (RonSchema::Char, RonEvent::Primitive(RonPrimitive::Char(_))) => return Ok(()),
+            (RonSchema::String, RonEvent::Primitive(RonPrimitive::Str(_))) => return Ok(()),
+            (RonSchema::Bytes, RonEvent::Primitive(RonPrimitive::Bytes(_))) => return Ok(()),
+            (RonSchema::Option(_), RonEvent::Primitive(RonPrimitive::NoneOptValue)) => return Ok(()),
+            (RonSchema::Option(x), RonEvent::OptionalSomeValue) => return self.check(x, path).map(|_| ()),
+            (RonSchema::List(x), RonEvent::ListStart) => {
+                let mut i = 0;
+                while *self.de.peek_event()? != RonEvent::ListEnd {
+                    self.check(x, &format!("{path}[{i}]"))?;
+                    i += 1;
+                }
+                self.de.next_event()?;
+                return Ok(());
+            },
+            (RonSchema::Map(k, v), RonEvent::MapStart) => {
+                let mut entries = Vec::new();
+                while *self.de.peek_event()? != RonEvent::MapEnd {
+                    let key = self.check(k, path)?;
+                    let text = &self.de.src()[key.start..key.end];
+                    let key = crate::from_str(text).unwrap_or_else(|_| RonValue::Str(String::from(text)));
+                    let first = self.violations.len();
+                    let entry_path = key_path(path, &key, &[]);
+                    self.check(v, &entry_path)?;
+                    entries.push((key, entry_path, first..self.violations.len()));
+                }
+                self.de.next_event()?;
+
+                // A string key is only written like a field if no other key matches it, which is only known now.
+                let keys: Vec<_> = entries.iter().map(|x| &x.0).collect();
+                for (key, written, violations) in &entries {
+                    let exact = key_path(path, key, &keys);
+                    if exact != *written {
+                        self.violations[violations.clone()].iter_mut().for_each(|x| x.path.replace_range(..written.len(), &exact));
+                    }
+                }
+                return Ok(());
+            },
+            (RonSchema::Tuple(x), RonEvent::TupleStart { name: None }) => return self.check_tuple(x, span, path),
+            (RonSchema::Struct(x), RonEvent::StructStart { name }) => {
+                if let (Some(expected), Some(name)) = (&x.name, name) {
+                    if expected != name {
+                        self.violate(span, path, format!("Expected struct {expected}, found struct {name}"));
+                    }
+                }
+                return self.check_struct(x, path);
+            },
+            (RonSchema::Enum(variants), event) => {
+                let name = match &event {
+                    RonEvent::Primitive(RonPrimitive::Enum(x)) => Some(*x),
+                    RonEvent::TupleStart { name } | RonEvent::StructStart { name } => *name,
+                    _ => None,
+                };
+                if let Some(name) = name {
+                    let Some((_, variant)) = variants.iter().find(|x| x.0 == name) else {
+                        self.violate(span, path, format!("Unknown variant {name}"));
+                        return self.skip_rest(event);
+                    };
+                    match (variant, event) {
+                        (VariantSchema::Unit, RonEvent::Primitive(_)) => return Ok(()),
+                        (VariantSchema::Tuple(x), RonEvent::TupleStart { .. }) => return self.check_tuple(x, span, path),
+                        (VariantSchema::Struct(x), RonEvent::StructStart { .. }) => return self.check_struct(x, path),
+                        (_, event) => {
+                            self.violate(span, path, format!("Variant {name} has the wrong kind of value"));
+                            return self.skip_rest(event);
+                        },
+                    }
+                }
+                return self.mismatch(schema, event, span, path);
+            },
+            (_, event) => event,
+        };
+        return self.mismatch(schema, expected, span, path);
+    }
+
+    fn check_tuple(&mut self, schemas: &[RonSchema], span: Span, path: &str) -> Result<(), RonError> {
+        let mut i = 0;
+        while !matches!(self.de.peek_event()?, RonEvent::TupleEnd { .. }) {
+            match schemas.get(i) {
+                Some(x) => {
+                    self.check(x, &format!("{path}[{i}]"))?;
+                },
+                None => self.de.skip_value()?,
+            }
+            i += 1;
+        }
+        self.de.next_event()?;
+        if i != schemas.len() {
+            self.violate(span.to(self.de.last_span()), path, format!("Expected {} elements, found {i}", schemas.len()));
+        }
+        return Ok(());
+    }
+
+    fn check_struct(&mut self, schema: &StructSchema, path: &str) -> Result<(), RonError> {
+        let mut seen = Vec::new();
+        // The struct ends with the first event that isn't a field, which is always its end.
+        while let RonEvent::NamedField(name) = self.de.next_event()? {
+            let span = self.de.last_span();
+            let path = field_path(path, name);
+            if seen.contains(&name) {
+                self.violate(span, &path, format!("Duplicate field {name}"));
+            }
+            seen.push(name);
+            match schema.fields.iter().find(|x| x.name == name) {
+                Some(x) => {
+                    self.check(&x.schema, &path)?;
+                },
+                None => {
+                    self.violate(span, &path, format!("Unknown field {name}"));
+                    self.de.skip_value()?;
+                },
+            }
+        }
+
+        let span = self.de.last_span();
+        for x in &schema.fields {
+            if x.required && !seen.contains(&x.name.as_str()) {
+                self.violate(span, path, format!("Missing field {}", x.name));
+            }
+        }
+        return Ok(());
+    }
+
+    fn mismatch(&mut self, schema: &RonSchema, found: RonEvent, span: Span, path: &str) -> Result<(), RonError> {
+        self.violate(span, path, format!("Expected {}, found {}", schema.describe(), describe(&found)));
+        return self.skip_rest(found);
+    }
+
+    /// Consumes the rest of the value started by `event`.
+    fn skip_rest(&mut self, event: RonEvent) -> Result<(), RonError> {
+        let mut depth = 0usize;
+        let mut event = event;
+        loop {
+            match event {
+                RonEvent::OptionalSomeValue => return self.de.skip_value(),
+                RonEvent::StructStart { .. } | RonEvent::TupleStart { .. } | RonEvent::MapStart | RonEvent::ListStart => depth += 1,
+                RonEvent::StructEnd { .. } | RonEvent::TupleEnd { .. } | RonEvent::MapEnd | RonEvent::ListEnd => depth -= 1,
+                _ => {},
+            }
+            if depth == 0 {
+                return Ok(());
+            }
+            event = self.de.next_event()?;
+        }
+    }
+
+    fn violate(&mut self, span: Span, path: &str, message: String) {
+        self.violations.push(Violation { path: String::from(path), span, message });
+    }
+}
+
+impl RonSchema {
+    /// Short human readable description of the values the schema accepts, for error messages.
+    fn describe(&self) -> String {
+        return match self {
+            RonSchema::Any => String::from("a value"),
+            RonSchema::Int => String::from("an integer"),
+            RonSchema::Float => String::from("a number"),
+            RonSchema::Bool => String::from("a bool"),
+            RonSchema::Char => String::from("a char"),
+            RonSchema::String => String::from("a string"),
+            RonSchema::Bytes => String::from("a byte string"),
+            RonSchema::Option(_) => String::from("an option"),
+            RonSchema::List(_) => String::from("a list"),
+            RonSchema::Map(..) => String::from("a map"),
+            RonSchema::Tuple(_) => String::from("a tuple"),
+            RonSchema::Struct(StructSchema { name: Some(x), .. }) => format!("struct {x}"),
+            RonSchema::Struct(_) => String::from("a struct"),
+            RonSchema::Enum(_) => String::from("a variant"),
+        };
+    }
+
+    /// Builds a schema from its RON form.
+    fn from_value(value: RonValue) -> Result<Self, String> {
+        return match value {
+            RonValue::Enum(x) => match x.as_str() {
+                "Any" => Ok(RonSchema::Any),
+                "Int" => Ok(RonSchema::Int),
+                "Float" => Ok(RonSchema::Float),
+                "Bool" => Ok(RonSchema::Bool),
+                "Char" => Ok(RonSchema::Char),
+                "String" => Ok(RonSchema::String),
+                "Bytes" => Ok(RonSchema::Bytes),
+                _ => Err(format!("Unknown schema {x}")),
+            },
+            RonValue::Tuple { name: Some(name), values } => {
+                let mut values = values.into_iter();
+                let mut next = || values.next().ok_or_else(|| format!("Missing the value of {name}")).and_then(RonSchema::from_value);
+                match name.as_str() {
+                    "Option" => Ok(RonSchema::Option(Box::new(next()?))),
+                    "List" => Ok(RonSchema::List(Box::new(next()?))),
+                    "Map" => Ok(RonSchema::Map(Box::new(next()?), Box::new(next()?))),
+                    "Tuple" => values.map(RonSchema::from_value).collect::<Result<_, _>>().map(RonSchema::Tuple),
+                    "Enum" => {
+                        let variants = match values.next() {
+                            Some(RonValue::List(names)) => names.into_iter().map(|x| Ok((text(x)?, VariantSchema::Unit))).collect::<Result<_, String>>()?,
+                            Some(RonValue::Map(entries)) => entries.into_iter().map(|(k, v)| Ok((text(k)?, VariantSchema::from_value(v)?))).collect::<Result<_, String>>()?,
+                            _ => return Err(String::from("Expected a list or a map of variants in Enum")),
+                        };
+                        Ok(RonSchema::Enum(variants))
+                    },
+                    _ => Err(format!("Unknown schema {name}")),
+                }
+            },
+            RonValue::Struct { name: Some(name), fields } if name == "Struct" => Ok(RonSchema::Struct(StructSchema::from_fields(fields)?)),
+            x => Err(format!("Expected a schema, found {}", x.type_name())),
+        };
+    }
+
+    fn to_value(&self) -> RonValue {
+        let named = |name: &str, values: Vec<RonValue>| RonValue::Tuple { name: Some(String::from(name)), values };
+        return match self {
+            RonSchema::Any => RonValue::Enum(String::from("Any")),
+            RonSchema::Int => RonValue::Enum(String::from("Int")),
+            RonSchema::Float => RonValue::Enum(String::from("Float")),
+            RonSchema::Bool => RonValue::Enum(String::from("Bool")),
+            RonSchema::Char => RonValue::Enum(String::from("Char")),
+            RonSchema::String => RonValue::Enum(String::from("String")),
+            RonSchema::Bytes => RonValue::Enum(String::from("Bytes")),
+            RonSchema::Option(x) => named("Option", vec![x.to_value()]),
+            RonSchema::List(x) => named("List", vec![x.to_value()]),
+            RonSchema::Map(k, v) => named("Map", vec![k.to_value(), v.to_value()]),
+            RonSchema::Tuple(x) => named("Tuple", x.iter().map(RonSchema::to_value).collect()),
+            RonSchema::Struct(x) => x.to_value(),
+            RonSchema::Enum(variants) => {
+                let variants = variants.iter().map(|(name, x)| (RonValue::Str(name.clone()), x.to_value())).collect();
+                named("Enum", vec![RonValue::Map(variants)])
+            },
+        };
+    }
+}
+
+impl StructSchema {
+    fn from_fields(fields: Vec<(String, RonValue)>) -> Result<Self, String> {
+        let mut schema = StructSchema::default();
+        let mut optional = Vec::new();
+        for (field, value) in fields {
+            match (field.as_str(), value) {
+                ("name", RonValue::Str(x)) => schema.name = Some(x),
+                ("fields", RonValue::Map(entries)) => {
+                    for (k, v) in entries {
+                        schema.fields.push(FieldSchema { name: text(k)?, schema: RonSchema::from_value(v)?, required: true });
+                    }
+                },
+                ("optional", RonValue::List(names)) => optional = names.into_iter().map(text).collect::<Result<_, _>>()?,
+                (x, _) => return Err(format!("Unexpected field {x} in Struct")),
+            }
+        }
+        for x in &mut schema.fields {
+            x.required = !optional.contains(&x.name);
+        }
+        return Ok(schema);
+    }
+
+    fn to_value(&self) -> RonValue {
+        let mut fields = Vec::new();
+        if let Some(x) = &self.name {
+            fields.push((String::from("name"), RonValue::Str(x.clone())));
+        }
+        let entries = self.fields.iter().map(|x| (RonValue::Str(x.name.clone()), x.schema.to_value())).collect();
+        fields.push((String::from("fields"), RonValue::Map(entries)));
+        let optional: Vec<_> = self.fields.iter().filter(|x| !x.required).map(|x| RonValue::Str(x.name.clone())).collect();
+        if !optional.is_empty() {
+            fields.push((String::from("optional"), RonValue::List(optional)));
+        }
+        return RonValue::Struct { name: Some(String::from("Struct")), fields };
+    }
+}
+
+impl VariantSchema {
+    fn from_value(value: RonValue) -> Result<Self, String> {
+        return match value {
+            RonValue::Enum(x) if x == "Unit" => Ok(VariantSchema::Unit),
+            RonValue::Tuple { name: Some(name), values } if name == "Tuple" => {
+                values.into_iter().map(RonSchema::from_value).collect::<Result<_, _>>().map(VariantSchema::Tuple)
+            },
+            RonValue::Struct { name: Some(name), fields } if name == "Struct" => Ok(VariantSchema::Struct(StructSchema::from_fields(fields)?)),
+            x => Err(format!("Expected Unit, Tuple or Struct as a variant, found {}", x.type_name())),
+        };
+    }
+
+    fn to_value(&self) -> RonValue {
+        return match self {
+            VariantSchema::Unit => RonValue::Enum(String::from("Unit")),
+            VariantSchema::Tuple(x) => RonValue::Tuple { name: Some(String::from("Tuple")), values: x.iter().map(RonSchema::to_value).collect() },
+            VariantSchema::Struct(x) => x.to_value(),
+        };
+    }
+}
+
+/// The text of a name in a schema, written either as a string or as an identifier.
+fn text(value: RonValue) -> Result<String, String> {
+    return match value {
+        RonValue::Str(x) | RonValue::Enum(x) => Ok(x),
+        x => Err(format!("Expected a name, found {}", x.type_name())),
+    };
+}
+
+impl<'a> RonDeserialize<'a> for RonSchema {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        let value = RonValue::deserialize(de)?;
+        return RonSchema::from_value(value).map_err(|x| de.error(ErrorKind::InvalidValue(x)));
+    }
+}
+
+impl RonSerialize for RonSchema {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return self.to_value().serialize(ser);
+    }
+}
+
+/// Reads the document in `src` and returns the source of Rust types that can hold it.
+/// ```
+/// # use light_ron::schema::infer_schema;
+/// let types = infer_schema("Player(name: \"a\", powers: [Fire, Cast(3)])").unwrap();
+/// assert_eq!(types, "\
+/// #[derive(Debug, Clone, PartialEq)]
+/// pub struct Player {
+///     pub name: String,
+///     pub powers: Vec<Powers>,
+/// }
+///
+/// #[derive(Debug, Clone, PartialEq)]
+/// pub enum Powers {
+///     Fire,
+///     Cast(i64),
+/// }
+/// ");
+/// ```
+pub fn infer_schema(src: &str) -> Result<String, RonError> {
+    let shape = Shape::infer(&crate::from_str(src)?);
+
+    let mut defs = Vec::new();
+    collect(&shape, "Root", &mut defs);
+
+    let mut uses = Uses::default();
+    let mut body = String::new();
+    if defs.is_empty() {
+        body.push_str(&format!("pub type Root = {};\n", rust_type(&shape, "Root", &mut uses)));
+    }
+    for (name, shape) in &defs {
+        if !body.is_empty() {
+            body.push('\n');
+        }
+        body.push_str(&render(name, shape, &mut uses));
+    }
+
+    let mut out = String::new();
+    if uses.hash_map {
+        out.push_str("use std::collections::HashMap;\n");
+    }
+    if uses.value {
+        out.push_str("use light_ron::value::RonValue;\n");
+    }
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(&body);
+    return Ok(out);
+}
+
+/// The type of a value, merged with the types of the values found in the same place.
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    /// Nothing is known yet, like the elements of an empty list.
+    Unknown,
+    /// Values that can't be merged.
+    Any,
+    Int,
+    Float,
+    Bool,
+    Char,
+    Str,
+    Bytes,
+    Option(Box<Shape>),
+    List(Box<Shape>),
+    Map(Box<Shape>, Box<Shape>),
+    Tuple(Vec<Shape>),
+    Struct(Option<String>, Vec<(String, Shape)>),
+    NamedTuple(String, Vec<Shape>),
+    Enum(Vec<(String, Variant)>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Variant {
+    Unit,
+    Tuple(Vec<Shape>),
+    Struct(Vec<(String, Shape)>),
+}
+
+impl Shape {
+    fn infer(value: &RonValue) -> Self {
+        return match value {
+            RonValue::None => Shape::Option(Box::new(Shape::Unknown)),
+            RonValue::Some(x) => Shape::Option(Box::new(Shape::infer(x))),
+            RonValue::Int(_) => Shape::Int,
+            RonValue::Float(_) => Shape::Float,
+            RonValue::Bool(_) => Shape::Bool,
+            RonValue::Char(_) => Shape::Char,
+            RonValue::Str(_) => Shape::Str,
+            RonValue::Bytes(_) => Shape::Bytes,
+            RonValue::Enum(x) => Shape::Enum(vec![(x.clone(), Variant::Unit)]),
+            RonValue::List(values) => Shape::List(Box::new(values.iter().map(Shape::infer).fold(Shape::Unknown, merge))),
+            RonValue::Map(entries) => {
+                let key = entries.iter().map(|x| Shape::infer(&x.0)).fold(Shape::Unknown, merge);
+                let value = entries.iter().map(|x| Shape::infer(&x.1)).fold(Shape::Unknown, merge);
+                Shape::Map(Box::new(key), Box::new(value))
+            },
+            RonValue::Tuple { name: None, values } => Shape::Tuple(values.iter().map(Shape::infer).collect()),
+            RonValue::Tuple { name: Some(name), values } => Shape::NamedTuple(name.clone(), values.iter().map(Shape::infer).collect()),
+            RonValue::Struct { name, fields } => Shape::Struct(name.clone(), fields.iter().map(|(k, v)| (k.clone(), Shape::infer(v))).collect()),
+        };
+    }
+
+    /// The variants this shape stands for, if it can be part of an enum.
+    fn into_variants(self) -> Option<Vec<(String, Variant)>> {
+        return match self {
+            Shape::Enum(x) => Some(x),
+            Shape::NamedTuple(name, values) => Some(vec![(name, Variant::Tuple(values))]),
+            Shape::Struct(Some(name), fields) => Some(vec![(name, Variant::Struct(fields))]),
+            _ => None,
+        };
+    }
+}
+
+fn merge(a: Shape, b: Shape) -> Shape {
+    return match (a, b) {
+        (Shape::Unknown, x) | (x, Shape::Unknown) => x,
+        (a, b) if a == b => a,
+        (Shape::Option(a), Shape::Option(b)) => Shape::Option(Box::new(merge(*a, *b))),
+        (Shape::Option(a), x) | (x, Shape::Option(a)) => Shape::Option(Box::new(merge(*a, x))),
+        (Shape::Int, Shape::Float) | (Shape::Float, Shape::Int) => Shape::Float,
+        (Shape::List(a), Shape::List(b)) => Shape::List(Box::new(merge(*a, *b))),
+        (Shape::Map(k1, v1), Shape::Map(k2, v2)) => Shape::Map(Box::new(merge(*k1, *k2)), Box::new(merge(*v1, *v2))),
+        (Shape::Tuple(a), Shape::Tuple(b)) if a.len() == b.len() => Shape::Tuple(merge_all(a, b)),
+        (Shape::Struct(n1, a), Shape::Struct(n2, b)) if n1 == n2 => Shape::Struct(n1, merge_fields(a, b)),
+        (Shape::NamedTuple(n1, a), Shape::NamedTuple(n2, b)) if n1 == n2 && a.len() == b.len() => Shape::NamedTuple(n1, merge_all(a, b)),
+        (a, b) => match (a.into_variants(), b.into_variants()) {
+            (Some(a), Some(b)) => Shape::Enum(merge_variants(a, b)),
+            _ => Shape::Any,
+        },
+    };
+}
+
+fn merge_all(a: Vec<Shape>, b: Vec<Shape>) -> Vec<Shape> {
+    return a.into_iter().zip(b).map(|(a, b)| merge(a, b)).collect();
+}
+
+/// Merges the fields of two structs. Fields missing from one of them become optional.
+fn merge_fields(a: Vec<(String, Shape)>, mut b: Vec<(String, Shape)>) -> Vec<(String, Shape)> {
+    let mut fields = Vec::new();
+    for (name, shape) in a {
+        match b.iter().position(|x| x.0 == name) {
+            Some(i) => fields.push((name, merge(shape, b.remove(i).1))),
+            None => fields.push((name, optional(shape))),
+        }
+    }
+    fields.extend(b.into_iter().map(|(name, shape)| (name, optional(shape))));
+    return fields;
+}
+
+fn optional(shape: Shape) -> Shape {
+    return match shape {
+        Shape::Option(_) => shape,
+        x => Shape::Option(Box::new(x)),
+    };
+}
+
+fn merge_variants(mut a: Vec<(String, Variant)>, b: Vec<(String, Variant)>) -> Vec<(String, Variant)> {
+    for (name, variant) in b {
+        let Some(i) = a.iter().position(|x| x.0 == name) else {
+            a.push((name, variant));
+            continue;
+        };
+        // Variants that can't be merged keep the first shape found.
+        a[i].1 = match (std::mem::replace(&mut a[i].1, Variant::Unit), variant) {
+            (Variant::Tuple(x), Variant::Tuple(y)) if x.len() == y.len() => Variant::Tuple(merge_all(x, y)),
+            (Variant::Struct(x), Variant::Struct(y)) => Variant::Struct(merge_fields(x, y)),
+            (x, _) => x,
+        };
+    }
+    return a;
+}
+
+/// Name of the type defined for `shape`, if it needs one. `hint` is where the shape was found, for types without a name.
+fn def_name(shape: &Shape, hint: &str) -> Option<String> {
+    return match shape {
+        Shape::Struct(Some(name), _) | Shape::NamedTuple(name, _) => Some(name.clone()),
+        Shape::Struct(None, _) | Shape::Enum(_) => Some(pascal_case(hint)),
+        _ => None,
+    };
+}
+
+/// Finds every type that needs a definition, merging the ones that end up with the same name.
+fn collect(shape: &Shape, hint: &str, defs: &mut Vec<(String, Shape)>) {
+    if let Some(name) = def_name(shape, hint) {
+        match defs.iter_mut().find(|x| x.0 == name) {
+            Some(x) => x.1 = merge(x.1.clone(), shape.clone()),
+            None => defs.push((name, shape.clone())),
+        }
+    }
+
+    match shape {
+        Shape::Option(x) | Shape::List(x) => collect(x, hint, defs),
+        Shape::Map(k, v) => {
+            collect(k, &format!("{hint}Key"), defs);
+            collect(v, hint, defs);
+        },
+        Shape::Tuple(values) | Shape::NamedTuple(_, values) => {
+            for (i, x) in values.iter().enumerate() {
+                collect(x, &format!("{hint}{i}"), defs);
+            }
+        },
+        Shape::Struct(_, fields) => {
+            for (name, x) in fields {
+                collect(x, name, defs);
+            }
+        },
+        Shape::Enum(variants) => {
+            for (name, variant) in variants {
+                match variant {
+                    Variant::Unit => {},
+                    Variant::Tuple(values) => {
+                        for (i, x) in values.iter().enumerate() {
+                            collect(x, &format!("{name}{i}"), defs);
+                        }
+                    },
+                    Variant::Struct(fields) => {
+                        for (name, x) in fields {
+                            collect(x, name, defs);
+                        }
+                    },
+                }
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Imports needed by the generated source.
+#[derive(Default)]
+struct Uses {
+    hash_map: bool,
+    value: bool,
+}
+
+fn rust_type(shape: &Shape, hint: &str, uses: &mut Uses) -> String {
+    if let Some(name) = def_name(shape, hint) {
+        return name;
+    }
+    return match shape {
+        Shape::Unknown | Shape::Any => {
+            uses.value = true;
+            String::from("RonValue")
+        },
+        Shape::Int => String::from("i64"),
+        Shape::Float => String::from("f64"),
+        Shape::Bool => String::from("bool"),
+        Shape::Char => String::from("char"),
+        Shape::Str => String::from("String"),
+        Shape::Bytes => String::from("Vec<u8>"),
+        Shape::Option(x) => format!("Option<{}>", rust_type(x, hint, uses)),
+        Shape::List(x) => format!("Vec<{}>", rust_type(x, hint, uses)),
+        Shape::Map(k, v) => {
+            uses.hash_map = true;
+            format!("HashMap<{}, {}>", rust_type(k, &format!("{hint}Key"), uses), rust_type(v, hint, uses))
+        },
+        Shape::Tuple(values) => format!("({})", tuple_types(values, hint, uses)),
+        Shape::Struct(..) | Shape::NamedTuple(..) | Shape::Enum(_) => unreachable!(),
+    };
+}
+
+fn tuple_types(values: &[Shape], hint: &str, uses: &mut Uses) -> String {
+    let types: Vec<_> = values.iter().enumerate().map(|(i, x)| rust_type(x, &format!("{hint}{i}"), uses)).collect();
+    // A single element tuple needs its trailing comma.
+    if types.len() == 1 {
+        return format!("{},", types[0]);
+    }
+    return types.join(", ");
+}
+
+/// Writes the definition of the type `name`.
+fn render(name: &str, shape: &Shape, uses: &mut Uses) -> String {
+    let mut out = String::from("#[derive(Debug, Clone, PartialEq)]\n");
+    match shape {
+        Shape::Struct(_, fields) => {
+            out.push_str(&format!("pub struct {name} {{\n"));
+            for (field, x) in fields {
+                out.push_str(&format!("    pub {}: {},\n", field_name(field), rust_type(x, field, uses)));
+            }
+            out.push_str("}\n");
+        },
+        Shape::NamedTuple(_, values) => {
+            let types: Vec<_> = values.iter().enumerate().map(|(i, x)| format!("pub {}", rust_type(x, &format!("{name}{i}"), uses))).collect();
+            out.push_str(&format!("pub struct {name}({});\n", types.join(", ")));
+        },
+        Shape::Enum(variants) => {
+            out.push_str(&format!("pub enum {name} {{\n"));
+            for (variant, x) in variants {
+                match x {
+                    Variant::Unit => out.push_str(&format!("    {variant},\n")),
+                    Variant::Tuple(values) => {
+                        let types: Vec<_> = values.iter().enumerate().map(|(i, x)| rust_type(x, &format!("{variant}{i}"), uses)).collect();
+                        out.push_str(&format!("    {variant}({}),\n", types.join(", ")));
+                    },
+                    Variant::Struct(fields) => {
+                        let fields: Vec<_> = fields.iter().map(|(field, x)| format!("{}: {}", field_name(field), rust_type(x, field, uses))).collect();
+                        out.push_str(&format!("    {variant} {{ {} }},\n", fields.join(", ")));
+                    },
+                }
+            }
+            out.push_str("}\n");
+        },
+        // Names shared by values that couldn't be merged.
+        _ => {
+            uses.value = true;
+            return format!("pub type {name} = RonValue;\n");
+        },
+    }
+    return out;
+}
+
+/// Turns a field name like `max_hp` into a type name like `MaxHp`.
+fn pascal_case(name: &str) -> String {
+    return name
+        .split('_')
+        .map(|x| {
+            let mut chars = x.chars();
+            chars.next().map_or(String::new(), |c| c.to_uppercase().chain(chars).collect())
+        })
+        .collect();
+}
+
+/// Escapes field names that are Rust keywords.
+fn field_name(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in",
+        "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use",
+        "where", "while",
+    ];
+    if KEYWORDS.contains(&name) {
+        return format!("r#{name}");
+    }
+    return String::from(name);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn struct_test() {
+        let src = r#"Player(name: "a", max_hp: 10, pos: (0.0, 1), inner: (type: 'x', tags: [], pet: None), factions: {"pirates": -100})"#;
+        assert_eq!(infer_schema(src).unwrap(), "\
+use std::collections::HashMap;
+use light_ron::value::RonValue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    pub name: String,
+    pub max_hp: i64,
+    pub pos: (f64, i64),
+    pub inner: Inner,
+    pub factions: HashMap<String, i64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inner {
+    pub r#type: char,
+    pub tags: Vec<RonValue>,
+    pub pet: Option<RonValue>,
+}
+");
+    }
+
+    #[test]
+    fn merge_test() {
+        let src = "[(a: 1, b: Some(true)), (a: 2.5, c: \"x\"), (a: 3, b: None)]";
+        assert_eq!(infer_schema(src).unwrap(), "\
+#[derive(Debug, Clone, PartialEq)]
+pub struct Root {
+    pub a: f64,
+    pub b: Option<bool>,
+    pub c: Option<String>,
+}
+");
+    }
+
+    #[test]
+    fn enum_test() {
+        let src = "(powers: [Fire, Cast(3, Wind), Summon(name: \"x\"), Cast(4, Rain)], area: Rect(1, 2), mixed: [1, \"a\"])";
+        assert_eq!(infer_schema(src).unwrap(), "\
+use light_ron::value::RonValue;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Root {
+    pub powers: Vec<Powers>,
+    pub area: Rect,
+    pub mixed: Vec<RonValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Powers {
+    Fire,
+    Cast(i64, Cast1),
+    Summon { name: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cast1 {
+    Wind,
+    Rain,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rect(pub i64, pub i64);
+");
+    }
+
+    #[test]
+    fn primitive_test() {
+        assert_eq!(infer_schema("[(1, 'a')]").unwrap(), "pub type Root = Vec<(i64, char)>;\n");
+        assert_eq!(infer_schema("Some(1.5)").unwrap(), "pub type Root = Option<f64>;\n");
+        assert!(infer_schema("(a: ").is_err());
+    }
+
+    const SCHEMA: &str = r#"Struct(
+        name: "Player",
+        fields: {
+            "name": String,
+            "hp": Int,
+            "pos": Tuple(Float, Float),
+            "powers": List(Enum({"Fire": Unit, "Cast": Tuple(Int), "Summon": Struct(fields: {"name": String})})),
+            "factions": Map(String, Int),
+            "pet": Option(String),
+        },
+        optional: ["pet"],
+    )"#;
+
+    fn messages(src: &str) -> Vec<String> {
+        let schema: RonSchema = crate::from_str(SCHEMA).unwrap();
+        return validate(src, &schema).iter().map(|x| x.to_string()).collect::<Vec<_>>();
+    }
+
+    #[test]
+    fn valid_test() {
+        let src = r#"Player(name: "a", hp: 3, pos: (0, 1.5), powers: [Fire, Cast(2), Summon(name: "x")], factions: {}, pet: Some("dog"))"#;
+        assert_eq!(messages(src), Vec::<String>::new());
+    }
+
+    #[test]
+    fn violations_test() {
+        let src = r#"Enemy(name: 'a', pos: (0.0), powers: [Fire(1), Water, Cast("x")], factions: {"pirates": 1.5}, age: 3, pet: None, pet: None)"#;
+        assert_eq!(messages(src), [
+            "Expected struct Player, found struct Enemy",
+            "name: Expected a string, found char 'a'",
+            "pos: Expected 2 elements, found 1",
+            "powers[0]: Variant Fire has the wrong kind of value",
+            "powers[1]: Unknown variant Water",
+            "powers[2][0]: Expected an integer, found string \"x\"",
+            "factions.pirates: Expected an integer, found float 1.5",
+            "age: Unknown field age",
+            "pet: Duplicate field pet",
+            "Missing field hp",
+        ]);
+    }
+
+    #[test]
+    fn map_key_path_test() {
+        let schema = RonSchema::Map(Box::new(RonSchema::Any), Box::new(RonSchema::List(Box::new(RonSchema::Int))));
+        let src = r#"{"a.b": [1.5], "x\"y": [2.5], "": [0.5], "plain": [true], One: ['c'], "One": [()], 1: ["d"]}"#;
+        let paths: Vec<_> = validate(src, &schema).into_iter().map(|x| x.path).collect();
+        assert_eq!(paths, ["[\"a.b\"][0]", "[\"x\\\"y\"][0]", "[\"\"][0]", "plain[0]", "[One][0]", "[\"One\"][0]", "[1][0]"]);
+    }
+
+    #[test]
+    fn violation_span_test() {
+        let schema = RonSchema::List(Box::new(RonSchema::Int));
+        let violations = validate("[1, [2], 3", &schema);
+        assert_eq!(violations[0], Violation { path: String::from("[1]"), span: Span::new(4, 5), message: String::from("Expected an integer, found a list") });
+        assert_eq!(violations[1].message, "Expected ',' or ']', found the end of the document");
+        assert_eq!(violations.len(), 2);
+    }
+
+    #[test]
+    fn schema_round_trip_test() {
+        let schema: RonSchema = crate::from_str(SCHEMA).unwrap();
+        assert_eq!(crate::from_str::<RonSchema>(&crate::to_string(&schema).unwrap()), Ok(schema));
+        assert!(crate::from_str::<RonSchema>("Number").is_err());
+        assert!(crate::from_str::<RonSchema>("Struct(fields: {\"a\": Int}, extra: 1)").is_err());
+    }
+}
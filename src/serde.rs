@@ -0,0 +1,1067 @@
+//! A bridge to [serde](https://serde.rs), behind the `serde` feature, so types deriving `Serialize` and `Deserialize`
+//! can be read and written as RON, and RON can be streamed to and from other serde formats.
+//!
+//! Values are mapped to serde's data model like this:
+//!
+//! | serde                  | Written as                  | Also read from                            |
+//! |------------------------|-----------------------------|-------------------------------------------|
+//! | integers and floats    | `5`, `2.5`                  | numbers with suffixes, like `5u8`         |
+//! | `bool`, `char`, string | `true`, `'a'`, `"a"`        | identifiers for strings                   |
+//! | bytes                  | `b"ab"`                     |                                           |
+//! | option                 | `None`, `Some(x)`           |                                           |
+//! | unit                   | `()`                        | `Name()`                                  |
+//! | unit struct            | `Name()`                    | `()`, `Name`                              |
+//! | newtype struct         | `Name(x)`                   | `x`, when it isn't a tuple                |
+//! | tuple, tuple struct    | `(a, b)`, `Name(a, b)`      | lists                                     |
+//! | sequence               | `[a, b]`                    | tuples                                    |
+//! | map                    | `{k: v}`                    |                                           |
+//! | struct                 | `Name(field: x)`            | `(field: x)`, `{"field": x}`, `()`        |
+//! | unit variant           | `Variant`                   |                                           |
+//! | newtype variant        | `Variant(x)`                |                                           |
+//! | tuple variant          | `Variant(a, b)`             |                                           |
+//! | struct variant         | `Variant(field: x)`         |                                           |
+//!
+//! Struct names aren't checked when reading. Two options change the mapping of newtypes, on both
+//! [`DeserializerOptions`] and [`PrettyConfig`], the same as the extensions of the `ron` crate with the same names:
+//! - `unwrap_newtypes` writes and reads newtype structs as the value they wrap, like `5.0` for `Meters(5.0)`.
+//! - `unwrap_variant_newtypes` lets a newtype variant share its parentheses with the struct or tuple it wraps, like
+//!   `Square(side: 2)` instead of `Square((side: 2))`. Other values are still wrapped, like `Beam(2.0)`.
+//!
+//! When reading without a type to guide it, like when transcoding, structs are read as maps with their name dropped,
+//! named tuples as a map with a single entry from their name to their value (or their values when there's more than
+//! one), and bare identifiers as strings, the same as the [`json`](crate::json) module does.
+//! ```
+//! # use serde::{Deserialize, Serialize};
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! enum Spell { Fire, Beam(f32) }
+//!
+//! #[derive(Debug, PartialEq, Serialize, Deserialize)]
+//! struct Player { name: String, spells: Vec<Spell> }
+//!
+//! let player: Player = light_ron::serde::from_str("Player(name: \"a\", spells: [Fire, Beam(2.5)])").unwrap();
+//! assert_eq!(player, Player { name: String::from("a"), spells: vec![Spell::Fire, Spell::Beam(2.5)] });
+//! assert_eq!(light_ron::serde::to_string(&player, light_ron::serial::PrettyConfig::compact()).unwrap(), "Player(name:\"a\",spells:[Fire,Beam(2.5)])");
+//! ```
+
+use std::borrow::Cow;
+use std::fmt::{self, Display, Write};
+
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+use serde::{forward_to_deserialize_any, Deserialize};
+
+use crate::deserial::{unexpected, Checkpoint, DeserializerOptions, ErrorKind, RonDeserializer, RonError, RonEvent, RonPrimitive};
+use crate::serial::{f32_to_f64, PrettyConfig, RonSerializer};
+
+/// Deserializes a `T` from `src`, failing if anything but whitespace follows its value.
+pub fn from_str<'a, T: Deserialize<'a>>(src: &'a str) -> Result<T, RonError> {
+    return from_str_with_options(src, DeserializerOptions::default());
+}
+
+/// Like [`from_str`], reading with the given options.
+pub fn from_str_with_options<'a, T: Deserialize<'a>>(src: &'a str, options: DeserializerOptions) -> Result<T, RonError> {
+    let mut de = RonDeserializer::with_options(src, options);
+    // Errors made by serde's own impls, like integers out of range, point at the last event read.
+    let value = T::deserialize(&mut de).map_err(|err| if err.span().is_some() { err } else { de.error(err.kind().clone()) })?;
+    return match next(&mut de)? {
+        RonEvent::Eof => Ok(value),
+        _ => Err(de.error(ErrorKind::TrailingCharacters)),
+    };
+}
+
+/// Serializes `value` with the given formatting rules. Fails if it can't be represented in RON, like a `u128` past
+/// `i128::MAX`.
+pub fn to_string<T: Serialize + ?Sized>(value: &T, config: PrettyConfig) -> Result<String, RonError> {
+    let mut ser = RonSerializer::new(String::new(), config);
+    value.serialize(&mut ser)?;
+    return Ok(ser.into_inner());
+}
+
+/// Streams the RON document in `src` into another format's serializer, without building anything in between.
+/// ```
+/// let mut json = Vec::new();
+/// light_ron::serde::transcode_to("Player(name: \"a\", pos: (1, 2))", &mut serde_json::Serializer::new(&mut json)).unwrap();
+/// assert_eq!(String::from_utf8(json).unwrap(), r#"{"name":"a","pos":[1,2]}"#);
+/// ```
+#[cfg(feature = "transcode")]
+pub fn transcode_to<S: ser::Serializer>(src: &str, ser: S) -> Result<S::Ok, S::Error> {
+    use ser::Error;
+
+    let mut de = RonDeserializer::new(src);
+    let value = serde_transcode::transcode(&mut de, ser)?;
+    return match next(&mut de) {
+        Ok(RonEvent::Eof) => Ok(value),
+        Ok(_) => Err(S::Error::custom(de.error(ErrorKind::TrailingCharacters))),
+        Err(err) => Err(S::Error::custom(err)),
+    };
+}
+
+/// Streams a document read by another format's deserializer into RON text with the given formatting rules.
+/// ```
+/// # use light_ron::serial::PrettyConfig;
+/// let mut de = serde_json::Deserializer::from_str(r#"{"name": "a", "pos": [1, 2]}"#);
+/// assert_eq!(light_ron::serde::transcode_from(&mut de, PrettyConfig::compact()).unwrap(), r#"{"name":"a","pos":[1,2]}"#);
+/// ```
+#[cfg(feature = "transcode")]
+pub fn transcode_from<'de, D: de::Deserializer<'de>>(de: D, config: PrettyConfig) -> Result<String, RonError> {
+    let mut ser = RonSerializer::new(String::new(), config);
+    serde_transcode::transcode(de, &mut ser)?;
+    return Ok(ser.into_inner());
+}
+
+impl de::Error for RonError {
+    fn custom<T: Display>(msg: T) -> Self {
+        return RonError::new(msg.to_string());
+    }
+}
+
+impl ser::Error for RonError {
+    fn custom<T: Display>(msg: T) -> Self {
+        return RonError::new(msg.to_string());
+    }
+}
+
+/// The next event that isn't a comment, turning `Error` events into errors.
+fn next<'a>(de: &mut RonDeserializer<'a>) -> Result<RonEvent<'a>, RonError> {
+    return match de.next_non_comment()? {
+        RonEvent::Error(err) => Err(err),
+        x => Ok(x),
+    };
+}
+
+/// Whether the named tuple that was just started holds a single value, looking ahead without consuming anything.
+fn has_single_value(de: &mut RonDeserializer) -> Result<bool, RonError> {
+    if matches!(de.peek_non_comment()?, RonEvent::TupleEnd { .. }) {
+        return Ok(false);
+    }
+    let checkpoint = de.checkpoint();
+    de.skip_value()?;
+    let single = matches!(de.peek_non_comment()?, RonEvent::TupleEnd { .. });
+    de.restore(&checkpoint);
+    return Ok(single);
+}
+
+/// Gives an integer to `visitor` as the narrowest of `i64`, `u64` and `i128` it fits in, since most visitors only
+/// accept the first two.
+fn visit_int<'de, V: Visitor<'de>>(visitor: V, x: i128) -> Result<V::Value, RonError> {
+    if let Ok(x) = i64::try_from(x) {
+        return visitor.visit_i64(x);
+    }
+    if let Ok(x) = u64::try_from(x) {
+        return visitor.visit_u64(x);
+    }
+    return visitor.visit_i128(x);
+}
+
+impl<'de> de::Deserializer<'de> for &mut RonDeserializer<'de> {
+    type Error = RonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RonError> {
+        return match next(self)? {
+            RonEvent::OptionalSomeValue => visitor.visit_some(self),
+            RonEvent::Primitive(x) => match x {
+                RonPrimitive::NoneOptValue => visitor.visit_none(),
+                RonPrimitive::Int(x) | RonPrimitive::TypedInt(x, _) => visit_int(visitor, x),
+                RonPrimitive::Float(x) | RonPrimitive::TypedFloat(x, _) => visitor.visit_f64(x),
+                RonPrimitive::Bool(x) => visitor.visit_bool(x),
+                RonPrimitive::Char(x) => visitor.visit_char(x),
+                RonPrimitive::Str(Cow::Borrowed(x)) | RonPrimitive::Enum(x) => visitor.visit_borrowed_str(x),
+                RonPrimitive::Str(Cow::Owned(x)) => visitor.visit_string(x),
+                RonPrimitive::Bytes(Cow::Borrowed(x)) => visitor.visit_borrowed_bytes(x),
+                RonPrimitive::Bytes(Cow::Owned(x)) => visitor.visit_byte_buf(x),
+            },
+            RonEvent::StructStart { .. } => visitor.visit_map(Fields { de: self }),
+            RonEvent::TupleStart { name: Some(name) } => visitor.visit_map(Tagged { de: self, name: Some(name) }),
+            RonEvent::TupleStart { name: None } | RonEvent::ListStart => visit_elements(self, visitor),
+            RonEvent::MapStart => visitor.visit_map(Entries { de: self }),
+            x => Err(unexpected(self, "a value", x)),
+        };
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RonError> {
+        return match self.peek_non_comment()? {
+            RonEvent::Primitive(RonPrimitive::NoneOptValue) => {
+                self.next_event()?;
+                visitor.visit_none()
+            },
+            RonEvent::OptionalSomeValue => {
+                self.next_event()?;
+                visitor.visit_some(self)
+            },
+            _ => self.deserialize_any(visitor),
+        };
+    }
+
+    /// Reads `()`, or a named tuple without values.
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RonError> {
+        if !matches!(self.peek_non_comment()?, RonEvent::TupleStart { .. }) {
+            return self.deserialize_any(visitor);
+        }
+        self.next_event()?;
+        return match next(self)? {
+            RonEvent::TupleEnd { .. } => visitor.visit_unit(),
+            x => Err(unexpected(self, "the end of a unit", x)),
+        };
+    }
+
+    /// Reads `()`, `Name()` or `Name`.
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, RonError> {
+        if let RonEvent::Primitive(RonPrimitive::Enum(_)) = self.peek_non_comment()? {
+            self.next_event()?;
+            return visitor.visit_unit();
+        }
+        return self.deserialize_unit(visitor);
+    }
+
+    /// Reads `Name(x)`, or `x` when it isn't a tuple. With `unwrap_newtypes`, only `x`.
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, RonError> {
+        if self.options().unwrap_newtypes || !matches!(self.peek_non_comment()?, RonEvent::TupleStart { .. }) {
+            return visitor.visit_newtype_struct(self);
+        }
+        self.next_event()?;
+        let value = visitor.visit_newtype_struct(&mut *self)?;
+        return match next(self)? {
+            RonEvent::TupleEnd { .. } => Ok(value),
+            x => Err(unexpected(self, "the end of a newtype", x)),
+        };
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RonError> {
+        return match next(self)? {
+            RonEvent::ListStart | RonEvent::TupleStart { .. } => visit_elements(self, visitor),
+            x => Err(unexpected(self, "a list", x)),
+        };
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, RonError> {
+        return self.deserialize_seq(visitor);
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, _len: usize, visitor: V) -> Result<V::Value, RonError> {
+        return self.deserialize_seq(visitor);
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, RonError> {
+        return match next(self)? {
+            RonEvent::StructStart { .. } => visitor.visit_map(Fields { de: self }),
+            RonEvent::MapStart => visitor.visit_map(Entries { de: self }),
+            // `()` is a struct without fields.
+            RonEvent::TupleStart { .. } if matches!(self.peek_non_comment()?, RonEvent::TupleEnd { .. }) => visitor.visit_map(Fields { de: self }),
+            x => Err(unexpected(self, "a struct", x)),
+        };
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, RonError> {
+        return visitor.visit_enum(Variant { de: self });
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RonError> {
+        self.skip_value()?;
+        return visitor.visit_unit();
+    }
+
+    fn is_human_readable(&self) -> bool {
+        return true;
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf map identifier
+    }
+}
+
+/// Gives the elements of the list or tuple whose start was just read to `visitor`. Visitors of tuples stop after
+/// their last element, so the end is checked afterwards.
+fn visit_elements<'de, V: Visitor<'de>>(de: &mut RonDeserializer<'de>, visitor: V) -> Result<V::Value, RonError> {
+    let mut elements = Elements { de, ended: false };
+    let value = visitor.visit_seq(&mut elements)?;
+    if !elements.ended {
+        return match next(elements.de)? {
+            RonEvent::ListEnd | RonEvent::TupleEnd { .. } => Ok(value),
+            x => Err(unexpected(elements.de, "the end of the tuple", x)),
+        };
+    }
+    return Ok(value);
+}
+
+/// The elements of a list or tuple, whose start was already read.
+struct Elements<'d, 'de> {
+    de: &'d mut RonDeserializer<'de>,
+    ended: bool,
+}
+
+impl<'de> de::SeqAccess<'de> for Elements<'_, 'de> {
+    type Error = RonError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, RonError> {
+        if self.ended {
+            return Ok(None);
+        }
+        if let RonEvent::ListEnd | RonEvent::TupleEnd { .. } = self.de.peek_non_comment()? {
+            self.de.next_event()?;
+            self.ended = true;
+            return Ok(None);
+        }
+        return seed.deserialize(&mut *self.de).map(Some);
+    }
+}
+
+/// The entries of a map, whose start was already read.
+struct Entries<'d, 'de> {
+    de: &'d mut RonDeserializer<'de>,
+}
+
+impl<'de> de::MapAccess<'de> for Entries<'_, 'de> {
+    type Error = RonError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, RonError> {
+        if let RonEvent::MapEnd = self.de.peek_non_comment()? {
+            self.de.next_event()?;
+            return Ok(None);
+        }
+        return seed.deserialize(&mut *self.de).map(Some);
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, RonError> {
+        return seed.deserialize(&mut *self.de);
+    }
+}
+
+/// The fields of a struct whose start was already read, given as a map from their names.
+struct Fields<'d, 'de> {
+    de: &'d mut RonDeserializer<'de>,
+}
+
+impl<'de> de::MapAccess<'de> for Fields<'_, 'de> {
+    type Error = RonError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, RonError> {
+        return match next(self.de)? {
+            RonEvent::NamedField(name) => seed.deserialize(BorrowedStrDeserializer::new(name)).map(Some),
+            RonEvent::StructEnd { .. } | RonEvent::TupleEnd { .. } => Ok(None),
+            x => Err(unexpected(self.de, "a field", x)),
+        };
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, RonError> {
+        return seed.deserialize(&mut *self.de);
+    }
+}
+
+/// A named tuple read without a type, as a map with a single entry from its name to its value or values.
+struct Tagged<'d, 'de> {
+    de: &'d mut RonDeserializer<'de>,
+    /// Taken once the key has been read.
+    name: Option<&'de str>,
+}
+
+impl<'de> de::MapAccess<'de> for Tagged<'_, 'de> {
+    type Error = RonError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, RonError> {
+        return match self.name.take() {
+            Some(name) => seed.deserialize(BorrowedStrDeserializer::new(name)).map(Some),
+            None => Ok(None),
+        };
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, RonError> {
+        if !has_single_value(self.de)? {
+            return seed.deserialize(ElementsDeserializer { de: self.de });
+        }
+        let value = seed.deserialize(&mut *self.de)?;
+        next(self.de)?;
+        return Ok(value);
+    }
+}
+
+/// Gives the elements of a tuple whose start was already read as a sequence.
+struct ElementsDeserializer<'d, 'de> {
+    de: &'d mut RonDeserializer<'de>,
+}
+
+impl<'de> de::Deserializer<'de> for ElementsDeserializer<'_, 'de> {
+    type Error = RonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RonError> {
+        return visit_elements(self.de, visitor);
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct seq tuple tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// An enum variant, read from the name of a named struct or tuple, or from a bare identifier.
+struct Variant<'d, 'de> {
+    de: &'d mut RonDeserializer<'de>,
+}
+
+/// The data of a variant, whose start was already read.
+struct VariantData<'d, 'de> {
+    de: &'d mut RonDeserializer<'de>,
+    start: RonEvent<'de>,
+    /// Right before the start, to read it again as the start of a newtype variant's value with `unwrap_variant_newtypes`.
+    before: Option<Checkpoint<'de>>,
+}
+
+impl<'d, 'de> de::EnumAccess<'de> for Variant<'d, 'de> {
+    type Error = RonError;
+    type Variant = VariantData<'d, 'de>;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant), RonError> {
+        self.de.peek_non_comment()?;
+        let before = self.de.options().unwrap_variant_newtypes.then(|| self.de.checkpoint());
+        let start = next(self.de)?;
+        let name = match start {
+            RonEvent::Primitive(RonPrimitive::Enum(name)) | RonEvent::StructStart { name: Some(name) } | RonEvent::TupleStart { name: Some(name) } => name,
+            x => return Err(unexpected(self.de, "an enum variant", x)),
+        };
+        let value = seed.deserialize(name.into_deserializer()).map_err(|err: RonError| self.de.error(err.kind().clone()))?;
+        return Ok((value, VariantData { de: self.de, start, before }));
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantData<'_, 'de> {
+    type Error = RonError;
+
+    fn unit_variant(self) -> Result<(), RonError> {
+        return match self.start {
+            RonEvent::Primitive(RonPrimitive::Enum(_)) => Ok(()),
+            x => Err(unexpected(self.de, "a variant without data", x)),
+        };
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, RonError> {
+        if let (RonEvent::TupleStart { .. } | RonEvent::StructStart { .. }, Some(before)) = (&self.start, &self.before) {
+            self.de.restore(before);
+            return seed.deserialize(UnwrappedNewtype { de: self.de });
+        }
+        match self.start {
+            RonEvent::TupleStart { .. } => {},
+            x => return Err(unexpected(self.de, "a variant with a value", x)),
+        }
+        let value = seed.deserialize(&mut *self.de)?;
+        return match next(self.de)? {
+            RonEvent::TupleEnd { .. } => Ok(value),
+            x => Err(unexpected(self.de, "the end of the variant", x)),
+        };
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, RonError> {
+        return match self.start {
+            RonEvent::TupleStart { .. } => visit_elements(self.de, visitor),
+            x => Err(unexpected(self.de, "a variant with unnamed fields", x)),
+        };
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, RonError> {
+        return match self.start {
+            RonEvent::StructStart { .. } => visitor.visit_map(Fields { de: self.de }),
+            x => Err(unexpected(self.de, "a variant with named fields", x)),
+        };
+    }
+}
+
+/// The value of a newtype variant read with `unwrap_variant_newtypes`, positioned at the start of the variant. Structs
+/// and tuples take the variant's parentheses as their own, and anything else is read from inside them.
+struct UnwrappedNewtype<'d, 'de> {
+    de: &'d mut RonDeserializer<'de>,
+}
+
+impl<'de> UnwrappedNewtype<'_, 'de> {
+    fn wrapped<T>(self, read: impl FnOnce(&mut RonDeserializer<'de>) -> Result<T, RonError>) -> Result<T, RonError> {
+        next(self.de)?;
+        let value = read(self.de)?;
+        return match next(self.de)? {
+            RonEvent::TupleEnd { .. } => Ok(value),
+            x => Err(unexpected(self.de, "the end of the variant", x)),
+        };
+    }
+}
+
+macro_rules! forward_unwrapped {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {$(
+        fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, RonError> {
+            return self.de.$method($($arg,)* visitor);
+        }
+    )*};
+}
+
+macro_rules! forward_wrapped {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {$(
+        fn $method<V: Visitor<'de>>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, RonError> {
+            return self.wrapped(|de| de.$method($($arg,)* visitor));
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for UnwrappedNewtype<'_, 'de> {
+    type Error = RonError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, RonError> {
+        if let RonEvent::StructStart { .. } = self.de.peek_non_comment()? {
+            return self.de.deserialize_any(visitor);
+        }
+        next(self.de)?;
+        if has_single_value(self.de)? {
+            let value = self.de.deserialize_any(visitor)?;
+            next(self.de)?;
+            return Ok(value);
+        }
+        return visit_elements(self.de, visitor);
+    }
+
+    forward_unwrapped! {
+        deserialize_unit_struct(name: &'static str)
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+    }
+
+    forward_wrapped! {
+        deserialize_bool() deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64() deserialize_i128()
+        deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64() deserialize_u128() deserialize_f32()
+        deserialize_f64() deserialize_char() deserialize_str() deserialize_string() deserialize_bytes()
+        deserialize_byte_buf() deserialize_option() deserialize_unit() deserialize_seq() deserialize_map()
+        deserialize_identifier() deserialize_ignored_any()
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+    }
+}
+
+/// Writes an event, turning formatting errors into `RonError`s.
+fn write<W: Write>(ser: &mut RonSerializer<W>, event: RonEvent) -> Result<(), RonError> {
+    return ser.write_event(event).map_err(|_: fmt::Error| RonError::new("Failed to write the output"));
+}
+
+fn int<T: TryInto<i128> + Display + Copy>(x: T) -> Result<RonEvent<'static>, RonError> {
+    let Ok(int) = x.try_into() else {
+        return Err(RonError::new(format!("{x} doesn't fit in an i128")));
+    };
+    return Ok(RonEvent::Primitive(RonPrimitive::Int(int)));
+}
+
+impl<'s, W: Write> ser::Serializer for &'s mut RonSerializer<W> {
+    type Ok = ();
+    type Error = RonError;
+    type SerializeSeq = Compound<'s, W>;
+    type SerializeTuple = Compound<'s, W>;
+    type SerializeTupleStruct = Compound<'s, W>;
+    type SerializeTupleVariant = Compound<'s, W>;
+    type SerializeMap = Compound<'s, W>;
+    type SerializeStruct = Compound<'s, W>;
+    type SerializeStructVariant = Compound<'s, W>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), RonError> {
+        return write(self, RonEvent::Primitive(RonPrimitive::Bool(v)));
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), RonError> {
+        return write(self, int(v)?);
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), RonError> {
+        return write(self, int(v)?);
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), RonError> {
+        return write(self, int(v)?);
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), RonError> {
+        return write(self, int(v)?);
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<(), RonError> {
+        return write(self, int(v)?);
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), RonError> {
+        return write(self, int(v)?);
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), RonError> {
+        return write(self, int(v)?);
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), RonError> {
+        return write(self, int(v)?);
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), RonError> {
+        return write(self, int(v)?);
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<(), RonError> {
+        return write(self, int(v)?);
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), RonError> {
+        return write(self, RonEvent::Primitive(RonPrimitive::Float(f32_to_f64(v))));
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), RonError> {
+        return write(self, RonEvent::Primitive(RonPrimitive::Float(v)));
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), RonError> {
+        return write(self, RonEvent::Primitive(RonPrimitive::Char(v)));
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), RonError> {
+        return write(self, RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed(v))));
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), RonError> {
+        return write(self, RonEvent::Primitive(RonPrimitive::Bytes(Cow::Borrowed(v))));
+    }
+
+    fn serialize_none(self) -> Result<(), RonError> {
+        return write(self, RonEvent::Primitive(RonPrimitive::NoneOptValue));
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), RonError> {
+        write(self, RonEvent::OptionalSomeValue)?;
+        return value.serialize(self);
+    }
+
+    fn serialize_unit(self) -> Result<(), RonError> {
+        write(self, RonEvent::TupleStart { name: None })?;
+        return write(self, RonEvent::TupleEnd { name: None });
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), RonError> {
+        write(self, RonEvent::TupleStart { name: Some(name) })?;
+        return write(self, RonEvent::TupleEnd { name: Some(name) });
+    }
+
+    fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<(), RonError> {
+        return write(self, RonEvent::Primitive(RonPrimitive::Enum(variant)));
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, name: &'static str, value: &T) -> Result<(), RonError> {
+        if self.config().unwrap_newtypes {
+            return value.serialize(self);
+        }
+        write(self, RonEvent::TupleStart { name: Some(name) })?;
+        value.serialize(&mut *self)?;
+        return write(self, RonEvent::TupleEnd { name: Some(name) });
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, variant: &'static str, value: &T) -> Result<(), RonError> {
+        if self.config().unwrap_variant_newtypes {
+            return value.serialize(UnwrappedVariant { ser: self, variant });
+        }
+        write(self, RonEvent::TupleStart { name: Some(variant) })?;
+        value.serialize(&mut *self)?;
+        return write(self, RonEvent::TupleEnd { name: Some(variant) });
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Compound<'s, W>, RonError> {
+        write(self, RonEvent::ListStart)?;
+        return Ok(Compound { ser: self, outer_end: None, end: RonEvent::ListEnd });
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Compound<'s, W>, RonError> {
+        write(self, RonEvent::TupleStart { name: None })?;
+        return Ok(Compound { ser: self, outer_end: None, end: RonEvent::TupleEnd { name: None } });
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, _len: usize) -> Result<Compound<'s, W>, RonError> {
+        write(self, RonEvent::TupleStart { name: Some(name) })?;
+        return Ok(Compound { ser: self, outer_end: None, end: RonEvent::TupleEnd { name: Some(name) } });
+    }
+
+    fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize) -> Result<Compound<'s, W>, RonError> {
+        return self.serialize_tuple_struct(variant, len);
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Compound<'s, W>, RonError> {
+        write(self, RonEvent::MapStart)?;
+        return Ok(Compound { ser: self, outer_end: None, end: RonEvent::MapEnd });
+    }
+
+    fn serialize_struct(self, name: &'static str, _len: usize) -> Result<Compound<'s, W>, RonError> {
+        write(self, RonEvent::StructStart { name: Some(name) })?;
+        return Ok(Compound { ser: self, outer_end: None, end: RonEvent::StructEnd { name: Some(name) } });
+    }
+
+    fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, len: usize) -> Result<Compound<'s, W>, RonError> {
+        return self.serialize_struct(variant, len);
+    }
+}
+
+/// Writes the value of a newtype variant with `unwrap_variant_newtypes`. Structs and tuples are named after the
+/// variant and take its place, and anything else is wrapped in it.
+struct UnwrappedVariant<'s, W: Write> {
+    ser: &'s mut RonSerializer<W>,
+    variant: &'static str,
+}
+
+impl<'s, W: Write> UnwrappedVariant<'s, W> {
+    fn wrapped<T>(self, inner: impl FnOnce(&'s mut RonSerializer<W>) -> Result<T, RonError>) -> Result<T, RonError> {
+        write(self.ser, RonEvent::TupleStart { name: Some(self.variant) })?;
+        return inner(self.ser);
+    }
+
+    fn wrapped_value(self, inner: impl FnOnce(&mut RonSerializer<W>) -> Result<(), RonError>) -> Result<(), RonError> {
+        let variant = self.variant;
+        let ser = self.ser;
+        write(ser, RonEvent::TupleStart { name: Some(variant) })?;
+        inner(ser)?;
+        return write(ser, RonEvent::TupleEnd { name: Some(variant) });
+    }
+
+    fn wrapped_compound(self, inner: impl FnOnce(&'s mut RonSerializer<W>) -> Result<Compound<'s, W>, RonError>) -> Result<Compound<'s, W>, RonError> {
+        let variant = self.variant;
+        let mut compound = self.wrapped(inner)?;
+        compound.outer_end = Some(RonEvent::TupleEnd { name: Some(variant) });
+        return Ok(compound);
+    }
+}
+
+macro_rules! serialize_wrapped {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {$(
+        fn $method(self, $($arg: $ty),*) -> Result<(), RonError> {
+            return self.wrapped_value(|ser| ser.$method($($arg),*));
+        }
+    )*};
+}
+
+impl<'s, W: Write> ser::Serializer for UnwrappedVariant<'s, W> {
+    type Ok = ();
+    type Error = RonError;
+    type SerializeSeq = Compound<'s, W>;
+    type SerializeTuple = Compound<'s, W>;
+    type SerializeTupleStruct = Compound<'s, W>;
+    type SerializeTupleVariant = Compound<'s, W>;
+    type SerializeMap = Compound<'s, W>;
+    type SerializeStruct = Compound<'s, W>;
+    type SerializeStructVariant = Compound<'s, W>;
+
+    serialize_wrapped! {
+        serialize_bool(v: bool) serialize_i8(v: i8) serialize_i16(v: i16) serialize_i32(v: i32) serialize_i64(v: i64)
+        serialize_i128(v: i128) serialize_u8(v: u8) serialize_u16(v: u16) serialize_u32(v: u32) serialize_u64(v: u64)
+        serialize_u128(v: u128) serialize_f32(v: f32) serialize_f64(v: f64) serialize_char(v: char) serialize_str(v: &str)
+        serialize_bytes(v: &[u8]) serialize_none() serialize_unit()
+        serialize_unit_variant(name: &'static str, index: u32, variant: &'static str)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), RonError> {
+        return self.wrapped_value(|ser| ser.serialize_some(value));
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(self, name: &'static str, value: &T) -> Result<(), RonError> {
+        return self.wrapped_value(|ser| ser.serialize_newtype_struct(name, value));
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(self, name: &'static str, index: u32, variant: &'static str, value: &T) -> Result<(), RonError> {
+        return self.wrapped_value(|ser| ser.serialize_newtype_variant(name, index, variant, value));
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), RonError> {
+        return self.ser.serialize_unit_struct(self.variant);
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'s, W>, RonError> {
+        return self.ser.serialize_tuple_struct(self.variant, len);
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<Compound<'s, W>, RonError> {
+        return self.ser.serialize_tuple_struct(self.variant, len);
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Compound<'s, W>, RonError> {
+        return self.ser.serialize_struct(self.variant, len);
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Compound<'s, W>, RonError> {
+        return self.wrapped_compound(|ser| ser.serialize_seq(len));
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Compound<'s, W>, RonError> {
+        return self.wrapped_compound(|ser| ser.serialize_map(len));
+    }
+
+    fn serialize_tuple_variant(self, name: &'static str, index: u32, variant: &'static str, len: usize) -> Result<Compound<'s, W>, RonError> {
+        return self.wrapped_compound(|ser| ser.serialize_tuple_variant(name, index, variant, len));
+    }
+
+    fn serialize_struct_variant(self, name: &'static str, index: u32, variant: &'static str, len: usize) -> Result<Compound<'s, W>, RonError> {
+        return self.wrapped_compound(|ser| ser.serialize_struct_variant(name, index, variant, len));
+    }
+}
+
+/// Writes the contents of a container, and its end event once it's done.
+pub struct Compound<'s, W: Write> {
+    ser: &'s mut RonSerializer<W>,
+    end: RonEvent<'static>,
+    /// The end of the newtype variant the container is in, with `unwrap_variant_newtypes`.
+    outer_end: Option<RonEvent<'static>>,
+}
+
+impl<W: Write> Compound<'_, W> {
+    fn element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RonError> {
+        return value.serialize(&mut *self.ser);
+    }
+
+    fn field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), RonError> {
+        write(self.ser, RonEvent::NamedField(key))?;
+        return value.serialize(&mut *self.ser);
+    }
+
+    fn finish(self) -> Result<(), RonError> {
+        write(self.ser, self.end)?;
+        if let Some(end) = self.outer_end {
+            write(self.ser, end)?;
+        }
+        return Ok(());
+    }
+}
+
+impl<W: Write> ser::SerializeSeq for Compound<'_, W> {
+    type Ok = ();
+    type Error = RonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RonError> {
+        return self.element(value);
+    }
+
+    fn end(self) -> Result<(), RonError> {
+        return self.finish();
+    }
+}
+
+impl<W: Write> ser::SerializeTuple for Compound<'_, W> {
+    type Ok = ();
+    type Error = RonError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RonError> {
+        return self.element(value);
+    }
+
+    fn end(self) -> Result<(), RonError> {
+        return self.finish();
+    }
+}
+
+impl<W: Write> ser::SerializeTupleStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = RonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RonError> {
+        return self.element(value);
+    }
+
+    fn end(self) -> Result<(), RonError> {
+        return self.finish();
+    }
+}
+
+impl<W: Write> ser::SerializeTupleVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = RonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RonError> {
+        return self.element(value);
+    }
+
+    fn end(self) -> Result<(), RonError> {
+        return self.finish();
+    }
+}
+
+impl<W: Write> ser::SerializeMap for Compound<'_, W> {
+    type Ok = ();
+    type Error = RonError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), RonError> {
+        return self.element(key);
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), RonError> {
+        return self.element(value);
+    }
+
+    fn end(self) -> Result<(), RonError> {
+        return self.finish();
+    }
+}
+
+impl<W: Write> ser::SerializeStruct for Compound<'_, W> {
+    type Ok = ();
+    type Error = RonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), RonError> {
+        return self.field(key, value);
+    }
+
+    fn end(self) -> Result<(), RonError> {
+        return self.finish();
+    }
+}
+
+impl<W: Write> ser::SerializeStructVariant for Compound<'_, W> {
+    type Ok = ();
+    type Error = RonError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), RonError> {
+        return self.field(key, value);
+    }
+
+    fn end(self) -> Result<(), RonError> {
+        return self.finish();
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Meters(f32);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Marker;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Spell {
+        Fire,
+        Water { depth: i32 },
+        Beam(f32),
+        Bolt(i32, i32),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Player<'a> {
+        name: &'a str,
+        pos: (i32, i32),
+        height: Meters,
+        spells: Vec<Spell>,
+        pet: Option<String>,
+        stats: BTreeMap<String, u8>,
+        marker: Marker,
+        unit: (),
+        #[serde(with = "serde_bytes_as_slice")]
+        icon: Vec<u8>,
+    }
+
+    /// Writes bytes with `serialize_bytes` instead of as a list, the way `serde_bytes` does.
+    mod serde_bytes_as_slice {
+        pub fn serialize<S: serde::Serializer>(bytes: &[u8], ser: S) -> Result<S::Ok, S::Error> {
+            return ser.serialize_bytes(bytes);
+        }
+
+        pub fn deserialize<'de, D: serde::Deserializer<'de>>(de: D) -> Result<Vec<u8>, D::Error> {
+            return serde::Deserialize::deserialize(de).map(|x: &[u8]| x.to_vec());
+        }
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let src = r#"Player(
+    name: "a",
+    pos: (1, -2),
+    height: Meters(1.8),
+    spells: [
+        Fire,
+        Water(
+            depth: 3,
+        ),
+        Beam(0.1),
+        Bolt(1, 2),
+    ],
+    pet: Some("Rex"),
+    stats: {
+        "hp": 3,
+    },
+    marker: Marker(),
+    unit: (),
+    icon: b"ab",
+)"#;
+        let player: Player = from_str(src).unwrap();
+        assert_eq!(player.spells, [Spell::Fire, Spell::Water { depth: 3 }, Spell::Beam(0.1), Spell::Bolt(1, 2)]);
+        assert_eq!(player.icon, b"ab");
+        assert_eq!(to_string(&player, PrettyConfig::default()).unwrap(), src);
+    }
+
+    #[test]
+    fn lenient_read_test() {
+        assert_eq!(from_str::<Marker>("Marker"), Ok(Marker));
+        assert_eq!(from_str::<Marker>("()"), Ok(Marker));
+        assert_eq!(from_str::<Meters>("2.5"), Ok(Meters(2.5)));
+        assert_eq!(from_str::<Spell>("/* first */ Fire // spell"), Ok(Spell::Fire));
+        assert_eq!(from_str::<(i32, i32)>("[1, 2]"), Ok((1, 2)));
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Square {
+        side: u8,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Pair(i32, i32);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Square(Square),
+        Pair(Pair),
+        Scale(Meters),
+        Points(Vec<i32>),
+    }
+
+    #[test]
+    fn unwrap_newtypes_test() {
+        let options = DeserializerOptions { unwrap_newtypes: true, ..Default::default() };
+        let config = PrettyConfig { unwrap_newtypes: true, ..PrettyConfig::compact() };
+        assert_eq!(from_str_with_options::<Vec<Meters>>("[1.5, 2.0]", options.clone()), Ok(vec![Meters(1.5), Meters(2.0)]));
+        assert_eq!(to_string(&Shape::Scale(Meters(1.5)), config).unwrap(), "Scale(1.5)");
+        assert!(from_str_with_options::<Meters>("Meters(1.5)", options).is_err());
+        // Without the option both are read.
+        assert_eq!(from_str::<Vec<Meters>>("[1.5, Meters(2.0)]"), Ok(vec![Meters(1.5), Meters(2.0)]));
+    }
+
+    #[test]
+    fn unwrap_variant_newtypes_test() {
+        let options = DeserializerOptions { unwrap_variant_newtypes: true, ..Default::default() };
+        let config = PrettyConfig { unwrap_variant_newtypes: true, ..PrettyConfig::compact() };
+        let shapes = vec![Shape::Square(Square { side: 2 }), Shape::Pair(Pair(1, 2)), Shape::Scale(Meters(0.5)), Shape::Points(vec![1])];
+
+        let src = to_string(&shapes, config).unwrap();
+        assert_eq!(src, "[Square(side:2),Pair(1,2),Scale(Meters(0.5)),Points([1])]");
+        assert_eq!(from_str_with_options::<Vec<Shape>>(&src, options), Ok(shapes));
+        assert_eq!(to_string(&Shape::Square(Square { side: 2 }), PrettyConfig::compact()).unwrap(), "Square(Square(side:2))");
+    }
+
+    #[test]
+    fn error_test() {
+        let err = from_str::<Spell>("Ice").unwrap_err();
+        assert_eq!(err.message(), "unknown variant `Ice`, expected one of `Fire`, `Water`, `Beam`, `Bolt`");
+        assert_eq!(err.span(), Some(crate::deserial::Span::new(0, 3)));
+        assert!(from_str::<Spell>("Fire(1)").is_err());
+        assert!(from_str::<u8>("300").is_err());
+        assert!(from_str::<i32>("1 2").is_err());
+        assert!(to_string(&u128::MAX, PrettyConfig::default()).is_err());
+    }
+    #[cfg(feature = "transcode")]
+    #[test]
+    fn transcode_test() {
+        let src = r#"Player(name: "a", pos: (1, -2), spells: [Fire, Beam(0.5)], pet: None, stats: {"hp": 3})"#;
+        let mut json = Vec::new();
+        transcode_to(src, &mut serde_json::Serializer::new(&mut json)).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(json, r#"{"name":"a","pos":[1,-2],"spells":["Fire",{"Beam":0.5}],"pet":null,"stats":{"hp":3}}"#);
+
+        let mut de = serde_json::Deserializer::from_str(&json);
+        let ron = transcode_from(&mut de, PrettyConfig::compact()).unwrap();
+        assert_eq!(ron, r#"{"name":"a","pos":[1,-2],"spells":["Fire",{"Beam":0.5}],"pet":(),"stats":{"hp":3}}"#);
+        assert!(transcode_to("1 2", &mut serde_json::Serializer::new(Vec::new())).is_err());
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt::{self, Write};
+
+/// Writes `text` between `quote`s, escaping backslashes, the quote, and control chars so it reads back as the same
+/// text. Other chars outside of ASCII are only escaped if `escape_non_ascii` is set.
+pub(crate) fn write_quoted<W: Write>(out: &mut W, text: &str, quote: char, escape_non_ascii: bool) -> fmt::Result {
+    out.write_char(quote)?;
+    let mut rest = text;
+    while let Some(i) = rest.find(|c| needs_escape(c, quote, escape_non_ascii)) {
+        out.write_str(&rest[..i])?;
+        let c = rest[i..].chars().next().unwrap();
+        write_escape(out, c)?;
+        rest = &rest[i + c.len_utf8()..];
+    }
+    out.write_str(rest)?;
+    return out.write_char(quote);
+}
+
+fn needs_escape(c: char, quote: char, escape_non_ascii: bool) -> bool {
+    return c == quote || c == '\\' || c.is_control() || (escape_non_ascii && !c.is_ascii());
+}
+
+fn write_escape<W: Write>(out: &mut W, c: char) -> fmt::Result {
+    return match c {
+        '\\' => out.write_str("\\\\"),
+        '"' => out.write_str("\\\""),
+        '\'' => out.write_str("\\'"),
+        '\n' => out.write_str("\\n"),
+        '\r' => out.write_str("\\r"),
+        '\t' => out.write_str("\\t"),
+        '\0' => out.write_str("\\0"),
+        _ => write!(out, "\\u{{{:x}}}", c as u32),
+    };
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quoted(text: &str, quote: char, escape_non_ascii: bool) -> String {
+        let mut out = String::new();
+        write_quoted(&mut out, text, quote, escape_non_ascii).unwrap();
+        return out;
+    }
+
+    #[test]
+    fn escape_test() {
+        assert_eq!(quoted("plain text", '"', false), "\"plain text\"");
+        assert_eq!(quoted("a\"b'c\\d", '"', false), r#""a\"b'c\\d""#);
+        assert_eq!(quoted("'", '\'', false), r"'\''");
+        assert_eq!(quoted("\n\r\t\0\u{1b}\u{7f}\u{85}", '"', false), r#""\n\r\t\0\u{1b}\u{7f}\u{85}""#);
+        assert_eq!(quoted("ñandú 🦀", '"', false), "\"ñandú 🦀\"");
+        assert_eq!(quoted("ñandú 🦀", '"', true), r#""\u{f1}and\u{fa} \u{1f980}""#);
+    }
+}
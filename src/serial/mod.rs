@@ -0,0 +1,687 @@
+use std::fmt::{self, Write};
+
+use crate::deserial::lexer::is_plain_ident;
+use crate::deserial::{NumberSuffix, RonEvent, RonPrimitive};
+
+pub use serialize::{RonSerialize, SerializeFields};
+#[cfg(feature = "derive")]
+pub use light_ron_derive::RonSerialize;
+
+mod escape;
+mod serialize;
+
+/// Formatting rules used by a [`RonSerializer`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrettyConfig {
+    /// Written once per nesting level at the start of each line.
+    pub indentor: String,
+
+    /// Written to break lines.
+    pub new_line: String,
+
+    /// Whether chars outside of ASCII in strings and char literals are written as `\u{..}` escapes, for files that
+    /// have to stay ASCII.
+    pub escape_non_ascii: bool,
+
+    /// Writes everything in a single line with no spaces or trailing commas, and leaves out comments, for sending
+    /// documents over the network. `indentor` isn't used, and `new_line` only separates documents.
+    pub compact: bool,
+
+    /// Whether the names of structs and tuples are written. Without them `Vec2(x: 1)` is written as `(x: 1)`, which
+    /// only reads back into types that don't check the name, so enum variants with data need them.
+    pub struct_names: bool,
+
+    /// How the fields of structs are laid out. Ignored in compact output, like the other layouts.
+    pub structs: Layout,
+
+    /// How the elements of tuples, and the fields of named tuples, are laid out.
+    pub tuples: Layout,
+
+    /// How the entries of maps are laid out.
+    pub maps: Layout,
+
+    /// How the elements of lists are laid out.
+    pub lists: Layout,
+
+    /// Write newtype structs as the value they wrap. Only used by the [`serde`](crate::serde) bridge, see
+    /// [`DeserializerOptions::unwrap_newtypes`](crate::deserial::DeserializerOptions::unwrap_newtypes).
+    pub unwrap_newtypes: bool,
+
+    /// Write newtype variants that wrap a struct or tuple sharing the parentheses with it. Only used by the
+    /// [`serde`](crate::serde) bridge, see
+    /// [`DeserializerOptions::unwrap_variant_newtypes`](crate::deserial::DeserializerOptions::unwrap_variant_newtypes).
+    pub unwrap_variant_newtypes: bool,
+}
+
+impl PrettyConfig {
+    /// The smallest output that keeps struct names.
+    pub fn compact() -> Self {
+        return Self { compact: true, ..Default::default() };
+    }
+}
+
+impl Default for PrettyConfig {
+    fn default() -> Self {
+        return Self {
+            indentor: String::from("    "),
+            new_line: String::from("\n"),
+            escape_non_ascii: false,
+            compact: false,
+            struct_names: true,
+            structs: Layout::Expanded,
+            tuples: Layout::Inline,
+            maps: Layout::Expanded,
+            lists: Layout::Expanded,
+            unwrap_newtypes: false,
+            unwrap_variant_newtypes: false,
+        };
+    }
+}
+
+/// How the elements of a kind of container are laid out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    /// One element per line, with trailing commas.
+    Expanded,
+    /// All elements in the same line, like `(1, 2)`.
+    Inline,
+    /// In the same line if there are at most this many elements and none of them is another container or an option,
+    /// and expanded otherwise. Keeps `[1, 2, 3]` short while a list of structs still gets a line per struct.
+    InlineUpTo(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Struct,
+    Tuple,
+    Map,
+    List,
+    OptionalSomeValue,
+}
+
+struct Frame {
+    kind: Kind,
+    /// Number of elements so far, counting comments that got their own line.
+    len: usize,
+    /// For structs and maps, whether a value comes next instead of a field or a key.
+    value_next: bool,
+    /// Whether the elements go one per line.
+    expanded: bool,
+    /// The elements of a container with [`Layout::InlineUpTo`] while it can still be written in a single line.
+    pending: Option<Pending>,
+}
+
+struct Pending {
+    max: usize,
+    /// Each element already written as text, like `x: 1` for a field.
+    items: Vec<String>,
+}
+
+impl Frame {
+    /// Whether the container can still be written in a single line after `event`.
+    fn fits(&self, event: &RonEvent) -> bool {
+        let Some(pending) = &self.pending else {
+            return true;
+        };
+        return match event {
+            RonEvent::Primitive(_) => self.value_next || pending.items.len() < pending.max,
+            RonEvent::NamedField(_) => pending.items.len() < pending.max,
+            RonEvent::StructEnd { .. } | RonEvent::TupleEnd { .. } | RonEvent::MapEnd | RonEvent::ListEnd => true,
+            _ => false,
+        };
+    }
+}
+
+/// Writes RON text from the same events a [`RonDeserializer`](crate::deserial::RonDeserializer) produces.
+///
+/// By default structs, maps and lists are written one element per line with trailing commas, and tuples are kept in a
+/// single line. The [`Layout`] of each kind of container can be changed in the [`PrettyConfig`]. With
+/// [`PrettyConfig::compact`] everything goes in a single line.
+pub struct RonSerializer<W: Write> {
+    out: W,
+    config: PrettyConfig,
+    stack: Vec<Frame>,
+    /// Whether anything has been written yet, so top level comments know if they start a line.
+    started: bool,
+}
+
+impl<W: Write> RonSerializer<W> {
+    pub fn new(out: W, config: PrettyConfig) -> Self {
+        return Self { out, config, stack: Vec::new(), started: false };
+    }
+
+    pub fn into_inner(self) -> W {
+        return self.out;
+    }
+
+    pub fn config(&self) -> &PrettyConfig {
+        return &self.config;
+    }
+
+    /// Writes a single event. `Error` events from recovery mode can't be written and fail with `fmt::Error`.
+    ///
+    /// # Panics
+    /// If the events don't describe a valid document, like an end event without its start event.
+    pub fn write_event(&mut self, event: RonEvent) -> fmt::Result {
+        let started = self.started;
+        self.started = true;
+        if self.stack.last().is_some_and(|x| !x.fits(&event)) {
+            self.expand()?;
+        }
+        if self.add_pending(&event)? {
+            return Ok(());
+        }
+
+        match event {
+            RonEvent::OptionalSomeValue => {
+                self.begin_value()?;
+                self.out.write_str("Some(")?;
+                self.stack.push(Frame { kind: Kind::OptionalSomeValue, len: 0, value_next: false, expanded: false, pending: None });
+            },
+            RonEvent::Primitive(x) => {
+                self.begin_value()?;
+                write_primitive(&mut self.out, &x, self.config.escape_non_ascii)?;
+                self.end_value()?;
+            },
+            RonEvent::StructStart { name } => self.open(Kind::Struct, name, '(')?,
+            RonEvent::NamedField(name) => {
+                let Some(Frame { kind: Kind::Struct, .. }) = self.stack.last() else {
+                    panic!("Got a named field outside of a struct!");
+                };
+                self.begin_value()?;
+                write_ident(&mut self.out, name)?;
+                self.out.write_str(if self.config.compact { ":" } else { ": " })?;
+                self.stack.last_mut().unwrap().value_next = true;
+            },
+            RonEvent::StructEnd { .. } => self.close(Kind::Struct, ')', "struct")?,
+            RonEvent::TupleStart { name } => self.open(Kind::Tuple, name, '(')?,
+            RonEvent::TupleEnd { .. } => self.close(Kind::Tuple, ')', "tuple")?,
+            RonEvent::MapStart => self.open(Kind::Map, None, '{')?,
+            RonEvent::MapEnd => self.close(Kind::Map, '}', "map")?,
+            RonEvent::ListStart => self.open(Kind::List, None, '[')?,
+            RonEvent::ListEnd => self.close(Kind::List, ']', "list")?,
+            RonEvent::Comment(text) => self.write_comment(text, started)?,
+            RonEvent::Error(_) => return Err(fmt::Error),
+            RonEvent::DocumentEnd => {
+                assert!(self.stack.is_empty(), "Got the end of a document inside a value!");
+                self.out.write_str(&self.config.new_line)?;
+            },
+            RonEvent::Eof => {},
+        }
+        return Ok(());
+    }
+
+    /// Writes the start of a container, choosing its layout from the config.
+    fn open(&mut self, kind: Kind, name: Option<&str>, delimiter: char) -> fmt::Result {
+        self.begin_value()?;
+        self.write_name(name)?;
+        self.out.write_char(delimiter)?;
+        let layout = match kind {
+            _ if self.config.compact => Layout::Inline,
+            Kind::Struct => self.config.structs,
+            Kind::Tuple => self.config.tuples,
+            Kind::Map => self.config.maps,
+            _ => self.config.lists,
+        };
+        let (expanded, pending) = match layout {
+            Layout::Expanded => (true, None),
+            Layout::Inline => (false, None),
+            Layout::InlineUpTo(max) => (false, Some(Pending { max, items: Vec::new() })),
+        };
+        self.stack.push(Frame { kind, len: 0, value_next: false, expanded, pending });
+        return Ok(());
+    }
+
+    /// Writes the end of the current container, which has to be a `kind` one.
+    fn close(&mut self, kind: Kind, delimiter: char, what: &str) -> fmt::Result {
+        let Some(frame) = self.stack.pop().filter(|x| x.kind == kind) else {
+            panic!("Got the end of a {what} without its start!");
+        };
+        if let Some(pending) = frame.pending {
+            self.out.write_str(&pending.items.join(", "))?;
+        } else if frame.expanded && frame.len > 0 {
+            self.new_line(self.depth())?;
+        }
+        self.out.write_char(delimiter)?;
+        return self.end_value();
+    }
+
+    /// Keeps the field or primitive in `event` as text if the current container may still be written in a single
+    /// line, returning whether it did.
+    fn add_pending(&mut self, event: &RonEvent) -> Result<bool, fmt::Error> {
+        let Some(frame @ Frame { pending: Some(_), .. }) = self.stack.last_mut() else {
+            return Ok(false);
+        };
+        let items = &mut frame.pending.as_mut().unwrap().items;
+        match event {
+            RonEvent::Primitive(x) => {
+                if !frame.value_next {
+                    items.push(String::new());
+                }
+                let item = items.last_mut().unwrap();
+                write_primitive(item, x, self.config.escape_non_ascii)?;
+                // Keys of maps are followed by their value, in the same element.
+                frame.value_next = frame.kind == Kind::Map && !frame.value_next;
+                if frame.value_next {
+                    item.push_str(": ");
+                }
+            },
+            RonEvent::NamedField(name) => {
+                let mut item = String::new();
+                write_ident(&mut item, name)?;
+                item.push_str(": ");
+                items.push(item);
+                frame.value_next = true;
+            },
+            _ => return Ok(false),
+        }
+        return Ok(true);
+    }
+
+    /// Writes the elements kept for the current container one per line, and expands it from then on.
+    fn expand(&mut self) -> fmt::Result {
+        let frame = self.stack.last_mut().unwrap();
+        let items = frame.pending.take().unwrap().items;
+        frame.expanded = true;
+        frame.len = items.len();
+        // The last element may be a field or key still waiting for its value.
+        let complete = if frame.value_next { items.len().saturating_sub(1) } else { items.len() };
+        let depth = self.depth();
+        for (i, item) in items.iter().enumerate() {
+            self.new_line(depth)?;
+            self.out.write_str(item)?;
+            if i < complete {
+                self.out.write_char(',')?;
+            }
+        }
+        return Ok(());
+    }
+
+    /// Writes the separator needed before the next value of the current container.
+    fn begin_value(&mut self) -> fmt::Result {
+        let Some(frame) = self.stack.last_mut() else {
+            return Ok(());
+        };
+        if frame.value_next || frame.kind == Kind::OptionalSomeValue {
+            return Ok(());
+        }
+        frame.len += 1;
+        if frame.expanded {
+            return self.new_line(self.depth());
+        }
+        if frame.len > 1 {
+            self.out.write_str(if self.config.compact { "," } else { ", " })?;
+        }
+        return Ok(());
+    }
+
+    /// Updates the current container after one of its values has been fully written.
+    fn end_value(&mut self) -> fmt::Result {
+        let Some(frame) = self.stack.last_mut() else {
+            return Ok(());
+        };
+        match frame.kind {
+            Kind::OptionalSomeValue => {
+                self.stack.pop();
+                self.out.write_char(')')?;
+                return self.end_value();
+            },
+            Kind::Map if !frame.value_next => {
+                frame.value_next = true;
+                return self.out.write_str(if self.config.compact { ":" } else { ": " });
+            },
+            _ => {},
+        }
+        frame.value_next = false;
+        // Inline containers put commas before elements instead, so there are no trailing ones.
+        if frame.expanded {
+            self.out.write_char(',')?;
+        }
+        return Ok(());
+    }
+
+    /// Writes a comment in its own line when it sits between the elements of an expanded container, or inline otherwise.
+    fn write_comment(&mut self, text: &str, started: bool) -> fmt::Result {
+        if self.config.compact {
+            return Ok(());
+        }
+        let depth = self.depth();
+        match self.stack.last_mut() {
+            None => {
+                if started {
+                    self.out.write_str(&self.config.new_line)?;
+                }
+                self.out.write_str(text)?;
+                return self.out.write_str(&self.config.new_line);
+            },
+            Some(frame) if frame.expanded && !frame.value_next => {
+                frame.len += 1;
+                self.new_line(depth)?;
+                return self.out.write_str(text);
+            },
+            _ => {},
+        }
+        let after_element = self.stack.last().is_some_and(|x| x.kind != Kind::OptionalSomeValue && !x.value_next && x.len > 0);
+        if after_element {
+            self.out.write_char(' ')?;
+        }
+
+        self.out.write_str(text)?;
+        // A line comment takes the rest of its line, so whatever comes next needs a new one.
+        if text.starts_with("//") {
+            return self.new_line(depth);
+        }
+        // An element is followed by a comma or the end of its container, which go right after the comment.
+        if after_element {
+            return Ok(());
+        }
+        return self.out.write_char(' ');
+    }
+
+    /// Breaks the line and indents it to `depth`.
+    fn new_line(&mut self, depth: usize) -> fmt::Result {
+        self.out.write_str(&self.config.new_line)?;
+        for _ in 0..depth {
+            self.out.write_str(&self.config.indentor)?;
+        }
+        return Ok(());
+    }
+
+    /// Number of expanded containers currently open.
+    fn depth(&self) -> usize {
+        return self.stack.iter().filter(|x| x.expanded).count();
+    }
+
+    /// Writes the name of a struct or tuple, unless names are left out.
+    fn write_name(&mut self, name: Option<&str>) -> fmt::Result {
+        return match name {
+            Some(name) if self.config.struct_names => write_ident(&mut self.out, name),
+            _ => Ok(()),
+        };
+    }
+}
+
+
+/// Writes an identifier, as a raw one if it would be read back as something else, like `r#true`.
+fn write_ident<W: Write>(out: &mut W, name: &str) -> fmt::Result {
+    if !name.is_empty() && !is_plain_ident(name) {
+        out.write_str("r#")?;
+    }
+    return out.write_str(name);
+}
+
+/// Writes the shortest digits that read back as `x`, with a `.0` if they don't have a point. Infinities and NaN are
+/// written as `inf`, `-inf` and `NaN`.
+fn write_float<W: Write>(out: &mut W, x: f64) -> fmt::Result {
+    let text = x.to_string();
+    if x.is_finite() && !text.contains('.') {
+        return write!(out, "{text}.0");
+    }
+    return out.write_str(&text);
+}
+
+fn write_primitive<W: Write>(out: &mut W, primitive: &RonPrimitive, escape_non_ascii: bool) -> fmt::Result {
+    return match *primitive {
+        RonPrimitive::NoneOptValue => out.write_str("None"),
+        RonPrimitive::Int(x) => write!(out, "{x}"),
+        RonPrimitive::Float(x) => write_float(out, x),
+        RonPrimitive::TypedInt(x, suffix) => write!(out, "{x}{suffix}"),
+        // Suffixes can't follow `inf` and `NaN`, so those are read back without one.
+        RonPrimitive::TypedFloat(x, _) if !x.is_finite() => write_float(out, x),
+        RonPrimitive::TypedFloat(x, suffix) => {
+            // Only the digits an f32 needs, so `0.1f32` isn't written as `0.10000000149011612f32`.
+            write_float(out, if suffix == NumberSuffix::F32 { f32_to_f64(x as f32) } else { x })?;
+            out.write_str(suffix.as_str())
+        },
+        RonPrimitive::Bool(x) => write!(out, "{x}"),
+        RonPrimitive::Char(x) => escape::write_quoted(out, x.encode_utf8(&mut [0; 4]), '\'', escape_non_ascii),
+        RonPrimitive::Str(ref x) => escape::write_quoted(out, x, '"', escape_non_ascii),
+        RonPrimitive::Enum(x) => write_ident(out, x),
+        RonPrimitive::Bytes(ref x) => {
+            out.write_str("b\"")?;
+            for &byte in x.iter() {
+                match byte {
+                    b'"' => out.write_str("\\\"")?,
+                    b'\\' => out.write_str("\\\\")?,
+                    b'\n' => out.write_str("\\n")?,
+                    b'\r' => out.write_str("\\r")?,
+                    b'\t' => out.write_str("\\t")?,
+                    b' '..=b'~' => out.write_char(byte as char)?,
+                    _ => write!(out, "\\x{byte:02x}")?,
+                }
+            }
+            out.write_char('"')
+        },
+    };
+}
+
+
+/// The f64 with the shortest digits that read back as `x`, which are usually much shorter than the ones of `x as f64`.
+pub(crate) fn f32_to_f64(x: f32) -> f64 {
+    return x.to_string().parse().unwrap_or(x as f64);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::*;
+    use crate::deserial::{DeserializerOptions, RonDeserializer};
+
+    fn write_events(events: &[RonEvent]) -> String {
+        let mut ser = RonSerializer::new(String::new(), PrettyConfig::default());
+        for event in events {
+            ser.write_event(event.clone()).unwrap();
+        }
+        return ser.into_inner();
+    }
+
+    /// Parses `src` and writes all of its events back.
+    fn reformat(src: &str) -> String {
+        let mut parser = RonDeserializer::new(src);
+        let mut ser = RonSerializer::new(String::new(), PrettyConfig::default());
+        loop {
+            let event = parser.next_event().unwrap();
+            if event == RonEvent::Eof {
+                return ser.into_inner();
+            }
+            ser.write_event(event).unwrap();
+        }
+    }
+
+    #[test]
+    fn primitive_test() {
+        assert_eq!(write_events(&[RonEvent::Primitive(RonPrimitive::Int(-5))]), "-5");
+        assert_eq!(write_events(&[RonEvent::Primitive(RonPrimitive::Float(2.0))]), "2.0");
+        assert_eq!(write_events(&[RonEvent::Primitive(RonPrimitive::Enum("Fire"))]), "Fire");
+        assert_eq!(write_events(&[RonEvent::Primitive(RonPrimitive::Enum("inf"))]), "r#inf");
+        assert_eq!(write_events(&[RonEvent::Primitive(RonPrimitive::TypedFloat(f64::NEG_INFINITY, NumberSuffix::F32))]), "-inf");
+        assert_eq!(write_events(&[RonEvent::Primitive(RonPrimitive::NoneOptValue)]), "None");
+    }
+
+    #[test]
+    fn some_test() {
+        assert_eq!(reformat("Some(Some(1))"), "Some(Some(1))");
+    }
+
+    #[test]
+    fn tuple_test() {
+        assert_eq!(reformat("Named( 1,2 , 3 )"), "Named(1, 2, 3)");
+        assert_eq!(reformat("()"), "()");
+    }
+
+    #[test]
+    fn empty_containers_test() {
+        assert_eq!(reformat("[]"), "[]");
+        assert_eq!(reformat("{}"), "{}");
+    }
+
+    #[test]
+    fn nested_test() {
+        let src = r#"Player(name: "SomePlayer69", pos: (0.0, 0.0, 0.0), factions: {"pirates": -100, "crabs": 30}, powers: [Fire, Some(Water)], inner: Inner(a: [(b: 1)]))"#;
+        let expected = r#"Player(
+    name: "SomePlayer69",
+    pos: (0.0, 0.0, 0.0),
+    factions: {
+        "pirates": -100,
+        "crabs": 30,
+    },
+    powers: [
+        Fire,
+        Some(Water),
+    ],
+    inner: Inner(
+        a: [
+            (
+                b: 1,
+            ),
+        ],
+    ),
+)"#;
+        assert_eq!(reformat(src), expected);
+        assert_eq!(reformat(expected), expected);
+    }
+
+    #[test]
+    fn config_test() {
+        let config = PrettyConfig { indentor: String::from("\t"), new_line: String::from("\r\n"), ..Default::default() };
+        let mut ser = RonSerializer::new(String::new(), config);
+        ser.write_event(RonEvent::ListStart).unwrap();
+        ser.write_event(RonEvent::Primitive(RonPrimitive::Int(1))).unwrap();
+        ser.write_event(RonEvent::ListEnd).unwrap();
+        assert_eq!(ser.into_inner(), "[\r\n\t1,\r\n]");
+    }
+
+    #[test]
+    fn compact_test() {
+        let src = "// Header\nPlayer(name: \"a b\", pos: Vec2(1, 2.5), tags: [A, B], stats: {\"hp\": 3, \"mp\": 0}, pet: Some(Dog(age: 2)), empty: [], // end\n)";
+        let write = |config: PrettyConfig| {
+            let mut parser = RonDeserializer::with_options(src, DeserializerOptions { comments: true, ..Default::default() });
+            let mut ser = RonSerializer::new(String::new(), config);
+            loop {
+                match parser.next_event().unwrap() {
+                    RonEvent::Eof => return ser.into_inner(),
+                    event => ser.write_event(event).unwrap(),
+                }
+            }
+        };
+
+        let compact = write(PrettyConfig::compact());
+        assert_eq!(compact, "Player(name:\"a b\",pos:Vec2(1,2.5),tags:[A,B],stats:{\"hp\":3,\"mp\":0},pet:Some(Dog(age:2)),empty:[])");
+        assert_eq!(reformat(&compact), reformat(src));
+
+        let unnamed = write(PrettyConfig { struct_names: false, ..PrettyConfig::compact() });
+        assert_eq!(unnamed, "(name:\"a b\",pos:(1,2.5),tags:[A,B],stats:{\"hp\":3,\"mp\":0},pet:Some((age:2)),empty:[])");
+    }
+
+    #[test]
+    fn layout_test() {
+        let src = r#"Level(name: "a", size: (4, 2), tiles: [1, 2, 3], spawns: [(x: 1, y: 2)], tags: {"hp": 3}, empty: [], big: [1, 2, 3, 4, 5])"#;
+        let config = PrettyConfig { tuples: Layout::Expanded, maps: Layout::Inline, lists: Layout::InlineUpTo(3), ..Default::default() };
+        let mut parser = RonDeserializer::new(src);
+        let mut ser = RonSerializer::new(String::new(), config);
+        crate::transcode(&mut parser, &mut ser).unwrap();
+        let expected = r#"Level(
+    name: "a",
+    size: (
+        4,
+        2,
+    ),
+    tiles: [1, 2, 3],
+    spawns: [
+        (
+            x: 1,
+            y: 2,
+        ),
+    ],
+    tags: {"hp": 3},
+    empty: [],
+    big: [
+        1,
+        2,
+        3,
+        4,
+        5,
+    ],
+)"#;
+        assert_eq!(ser.into_inner(), expected);
+
+        let config = PrettyConfig { structs: Layout::InlineUpTo(2), maps: Layout::InlineUpTo(1), ..Default::default() };
+        let mut parser = RonDeserializer::new(r#"[Vec2(x: 1, y: 2), Vec3(x: 1, y: 2, z: 3), Pet(name: Some("Rex")), {"a": 1, "b": 2}]"#);
+        let mut ser = RonSerializer::new(String::new(), config);
+        crate::transcode(&mut parser, &mut ser).unwrap();
+        let expected = r#"[
+    Vec2(x: 1, y: 2),
+    Vec3(
+        x: 1,
+        y: 2,
+        z: 3,
+    ),
+    Pet(
+        name: Some("Rex"),
+    ),
+    {
+        "a": 1,
+        "b": 2,
+    },
+]"#;
+        assert_eq!(ser.into_inner(), expected);
+    }
+
+    #[test]
+    fn comments_test() {
+        let src = "// Header\nPlayer(/* id */ a: 1, b: /* inline */ [2, // two\n], c: (1 /* x */, 2 /* w */)) // end";
+        let expected = "// Header\nPlayer(\n    /* id */\n    a: 1,\n    b: /* inline */ [\n        2,\n        // two\n    ],\n    c: (1 /* x */, 2 /* w */),\n)\n// end\n";
+
+        let reformat = |src: &str| {
+            let mut parser = RonDeserializer::with_options(src, DeserializerOptions { comments: true, ..Default::default() });
+            let mut ser = RonSerializer::new(String::new(), PrettyConfig::default());
+            crate::transcode(&mut parser, &mut ser).unwrap();
+            return ser.into_inner();
+        };
+        assert_eq!(reformat(src), expected);
+        assert_eq!(reformat(expected), expected);
+    }
+
+    #[test]
+    fn documents_test() {
+        let mut parser = RonDeserializer::with_options("(1, 2) Some(3)\n[]", DeserializerOptions { documents: true, ..Default::default() });
+        let mut ser = RonSerializer::new(String::new(), PrettyConfig::default());
+        crate::transcode(&mut parser, &mut ser).unwrap();
+        assert_eq!(ser.into_inner(), "(1, 2)\nSome(3)\n[]\n");
+    }
+
+    #[test]
+    fn raw_ident_test() {
+        let events = [
+            RonEvent::StructStart { name: Some("true") },
+            RonEvent::NamedField("max-hp"),
+            RonEvent::Primitive(RonPrimitive::Enum("None")),
+            RonEvent::NamedField("ñ"),
+            RonEvent::Primitive(RonPrimitive::Enum("Fire")),
+            RonEvent::StructEnd { name: Some("true") },
+        ];
+        let src = write_events(&events);
+        assert_eq!(src, "r#true(\n    r#max-hp: r#None,\n    ñ: Fire,\n)");
+        assert_eq!(reformat(&src), src);
+    }
+
+    #[test]
+    fn number_suffix_test() {
+        assert_eq!(reformat("(42u8, -1i128, 1f32, 2.5f64)"), "(42u8, -1i128, 1.0f32, 2.5f64)");
+        assert_eq!(reformat("(0.1f32, 0.1f64, 0.1)"), "(0.1f32, 0.1f64, 0.1)");
+    }
+
+    #[test]
+    fn escape_test() {
+        let src = r#"("say \"hi\"\n", "C:\\", "ñ\u{1b}")"#;
+        assert_eq!(reformat(src), src);
+        assert_eq!(write_events(&[RonEvent::Primitive(RonPrimitive::Char('\''))]), r"'\''");
+
+        let config = PrettyConfig { escape_non_ascii: true, ..Default::default() };
+        let mut ser = RonSerializer::new(String::new(), config);
+        ser.write_event(RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed("ñandú")))).unwrap();
+        assert_eq!(ser.into_inner(), r#""\u{f1}and\u{fa}""#);
+    }
+}
@@ -0,0 +1,305 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt::{self, Write};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::deserial::{RonEvent, RonPrimitive};
+
+use super::RonSerializer;
+
+/// A type that can be written as events into a [`RonSerializer`].
+///
+/// Implementations write exactly the events of one value, so they can be composed to encode bigger types.
+pub trait RonSerialize {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result;
+}
+
+/// The named fields of a type, written without the start and end of their struct. Implemented by the `RonSerialize`
+/// derive for structs with named fields, so other structs can flatten them into their own fields.
+pub trait SerializeFields {
+    fn serialize_fields<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result;
+}
+
+macro_rules! impl_int {
+    ($($ty:ty),*) => {$(
+        impl RonSerialize for $ty {
+            /// Fails if the value doesn't fit in an `i128`, which only happens for the biggest `u128`s.
+            fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+                let x = i128::try_from(*self).map_err(|_| fmt::Error)?;
+                return ser.write_event(RonEvent::Primitive(RonPrimitive::Int(x)));
+            }
+        }
+    )*};
+}
+
+impl_int!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+impl RonSerialize for f32 {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return ser.write_event(RonEvent::Primitive(RonPrimitive::Float(super::f32_to_f64(*self))));
+    }
+}
+
+impl RonSerialize for f64 {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return ser.write_event(RonEvent::Primitive(RonPrimitive::Float(*self)));
+    }
+}
+
+impl RonSerialize for bool {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return ser.write_event(RonEvent::Primitive(RonPrimitive::Bool(*self)));
+    }
+}
+
+impl RonSerialize for char {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return ser.write_event(RonEvent::Primitive(RonPrimitive::Char(*self)));
+    }
+}
+
+impl RonSerialize for str {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return ser.write_event(RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed(self))));
+    }
+}
+
+impl RonSerialize for String {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return self.as_str().serialize(ser);
+    }
+}
+
+impl<T: RonSerialize + ?Sized> RonSerialize for &T {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return (**self).serialize(ser);
+    }
+}
+
+impl<T: RonSerialize + ?Sized> RonSerialize for &mut T {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return (**self).serialize(ser);
+    }
+}
+
+impl<T: RonSerialize + ?Sized> RonSerialize for Box<T> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return (**self).serialize(ser);
+    }
+}
+
+impl<T: RonSerialize + ?Sized> RonSerialize for Rc<T> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return (**self).serialize(ser);
+    }
+}
+
+impl<T: RonSerialize + ?Sized> RonSerialize for Arc<T> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return (**self).serialize(ser);
+    }
+}
+
+impl<T: RonSerialize + ToOwned + ?Sized> RonSerialize for Cow<'_, T> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return (**self).serialize(ser);
+    }
+}
+
+impl<T: RonSerialize> RonSerialize for Option<T> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        let Some(x) = self else {
+            return ser.write_event(RonEvent::Primitive(RonPrimitive::NoneOptValue));
+        };
+        ser.write_event(RonEvent::OptionalSomeValue)?;
+        return x.serialize(ser);
+    }
+}
+
+/// Writes every item as an element of a list, which lets every collection share it.
+fn write_list<W: Write, T: RonSerialize>(ser: &mut RonSerializer<W>, items: impl IntoIterator<Item = T>) -> fmt::Result {
+    ser.write_event(RonEvent::ListStart)?;
+    for x in items {
+        x.serialize(ser)?;
+    }
+    return ser.write_event(RonEvent::ListEnd);
+}
+
+/// Writes every pair as a map entry, which lets every map type share it.
+fn write_map<W: Write, K: RonSerialize, V: RonSerialize>(ser: &mut RonSerializer<W>, entries: impl IntoIterator<Item = (K, V)>) -> fmt::Result {
+    ser.write_event(RonEvent::MapStart)?;
+    for (k, v) in entries {
+        k.serialize(ser)?;
+        v.serialize(ser)?;
+    }
+    return ser.write_event(RonEvent::MapEnd);
+}
+
+impl<T: RonSerialize> RonSerialize for [T] {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return write_list(ser, self);
+    }
+}
+
+impl<T: RonSerialize> RonSerialize for Vec<T> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return write_list(ser, self);
+    }
+}
+
+/// Arrays are written as tuples, like the `ron` crate does.
+impl<T: RonSerialize, const N: usize> RonSerialize for [T; N] {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        ser.write_event(RonEvent::TupleStart { name: None })?;
+        for x in self {
+            x.serialize(ser)?;
+        }
+        return ser.write_event(RonEvent::TupleEnd { name: None });
+    }
+}
+
+impl<T: RonSerialize, S> RonSerialize for HashSet<T, S> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return write_list(ser, self);
+    }
+}
+
+impl<T: RonSerialize> RonSerialize for BTreeSet<T> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return write_list(ser, self);
+    }
+}
+
+impl<K: RonSerialize, V: RonSerialize, S> RonSerialize for HashMap<K, V, S> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return write_map(ser, self);
+    }
+}
+
+impl<K: RonSerialize, V: RonSerialize> RonSerialize for BTreeMap<K, V> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return write_map(ser, self);
+    }
+}
+
+macro_rules! impl_tuple {
+    ($(($($name:ident $idx:tt),*))*) => {$(
+        impl<$($name: RonSerialize),*> RonSerialize for ($($name,)*) {
+            fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+                ser.write_event(RonEvent::TupleStart { name: None })?;
+                $(self.$idx.serialize(ser)?;)*
+                return ser.write_event(RonEvent::TupleEnd { name: None });
+            }
+        }
+    )*};
+}
+
+impl_tuple! {
+    ()
+    (T0 0)
+    (T0 0, T1 1)
+    (T0 0, T1 1, T2 2)
+    (T0 0, T1 1, T2 2, T3 3)
+    (T0 0, T1 1, T2 2, T3 3, T4 4)
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5)
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6)
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7)
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8)
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9)
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10)
+    (T0 0, T1 1, T2 2, T3 3, T4 4, T5 5, T6 6, T7 7, T8 8, T9 9, T10 10, T11 11)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::PrettyConfig;
+
+    fn write<T: RonSerialize + ?Sized>(value: &T) -> Result<String, fmt::Error> {
+        let mut ser = RonSerializer::new(String::new(), PrettyConfig::default());
+        value.serialize(&mut ser)?;
+        return Ok(ser.into_inner());
+    }
+
+    #[test]
+    fn int_test() {
+        assert_eq!(write(&-12i8), Ok(String::from("-12")));
+        assert_eq!(write(&u64::MAX), Ok(String::from("18446744073709551615")));
+        assert_eq!(write(&i128::MIN), Ok(i128::MIN.to_string()));
+        assert_eq!(write(&u128::MAX), Err(fmt::Error));
+    }
+
+    #[test]
+    fn float_test() {
+        assert_eq!(write(&1.0f64), Ok(String::from("1.0")));
+        assert_eq!(write(&0.1f64), Ok(String::from("0.1")));
+        assert_eq!(write(&0.5f32), Ok(String::from("0.5")));
+        assert_eq!(write(&0.1f32), Ok(String::from("0.1")));
+        assert_eq!(write(&0.1f64), Ok(String::from("0.1")));
+        assert_eq!(write(&1e-7f64), Ok(String::from("0.0000001")));
+    }
+
+    #[test]
+    fn non_finite_float_test() {
+        assert_eq!(write(&f64::INFINITY), Ok(String::from("inf")));
+        assert_eq!(write(&f32::NEG_INFINITY), Ok(String::from("-inf")));
+        assert_eq!(crate::from_str::<f64>(&write(&f64::NEG_INFINITY).unwrap()), Ok(f64::NEG_INFINITY));
+        assert_eq!(crate::from_str::<f32>(&write(&f32::INFINITY).unwrap()), Ok(f32::INFINITY));
+        assert!(crate::from_str::<f64>(&write(&f64::NAN).unwrap()).unwrap().is_nan());
+        assert_eq!(crate::from_str::<Vec<f64>>(&write(&vec![1.5, f64::INFINITY]).unwrap()), Ok(vec![1.5, f64::INFINITY]));
+    }
+
+    #[test]
+    fn str_test() {
+        assert_eq!(write("abc"), Ok(String::from("\"abc\"")));
+        assert_eq!(write(&String::from("abc")), Ok(String::from("\"abc\"")));
+        assert_eq!(write(&'a'), Ok(String::from("'a'")));
+        assert_eq!(write(&true), Ok(String::from("true")));
+    }
+
+    #[test]
+    fn option_test() {
+        assert_eq!(write(&None::<i32>), Ok(String::from("None")));
+        assert_eq!(write(&Some(Some(5))), Ok(String::from("Some(Some(5))")));
+    }
+
+    #[test]
+    fn list_test() {
+        assert_eq!(write(&vec![1, 2]), Ok(String::from("[\n    1,\n    2,\n]")));
+        assert_eq!(write(&Vec::<i32>::new()), Ok(String::from("[]")));
+        assert_eq!(write(&[1, 2][..]), Ok(String::from("[\n    1,\n    2,\n]")));
+        assert_eq!(write(&BTreeSet::from([2, 1])), Ok(String::from("[\n    1,\n    2,\n]")));
+    }
+
+    #[test]
+    fn array_tuple_test() {
+        assert_eq!(write(&[1.0, 2.5]), Ok(String::from("(1.0, 2.5)")));
+        assert_eq!(write(&()), Ok(String::from("()")));
+        assert_eq!(write(&(1, "a", [true])), Ok(String::from("(1, \"a\", (true))")));
+    }
+
+    #[test]
+    fn map_test() {
+        let map = BTreeMap::from([("crabs", 30), ("pirates", -100)]);
+        assert_eq!(write(&map), Ok(String::from("{\n    \"crabs\": 30,\n    \"pirates\": -100,\n}")));
+    }
+
+    #[test]
+    fn reference_test() {
+        let value = String::from("a");
+        assert_eq!(write(&&&value), Ok(String::from("\"a\"")));
+        assert_eq!(write(&Box::new(1)), Ok(String::from("1")));
+        assert_eq!(write(&Cow::Borrowed("a")), Ok(String::from("\"a\"")));
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let value: BTreeMap<String, (Vec<Option<i32>>, [f64; 2])> = BTreeMap::from([
+            (String::from("a"), (vec![Some(1), None], [0.5, -1.0])),
+            (String::from("b"), (vec![], [0.0, 2.0])),
+        ]);
+        assert_eq!(crate::from_str(&crate::to_string(&value).unwrap()), Ok(value));
+    }
+}
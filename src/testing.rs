@@ -0,0 +1,142 @@
+//! Assertions for tests that compare RON documents by their values instead of their text.
+//!
+//! Whitespace, comments, trailing commas and the order of map entries never matter. The order of struct fields does
+//! unless it's ignored in the [`CompareOptions`]. Failures list the [changes](crate::diff) between the documents.
+//! ```should_panic
+//! # use light_ron::testing::assert_ron_eq;
+//! assert_ron_eq("(hp: 3, name: \"a\",)", "( hp: 3, /* same */ name: \"a\" )");
+//! // Panics with:
+//! // The documents differ:
+//! //     ~ hp: 3 -> 5
+//! assert_ron_eq("(hp: 3)", "(hp: 5)");
+//! ```
+
+use std::fmt::Write;
+
+use crate::diff::{diff, field_path, key_path, Change};
+use crate::serial::RonSerialize;
+use crate::value::RonValue;
+
+/// How documents are compared.
+#[derive(Debug, Clone, Default)]
+pub struct CompareOptions {
+    /// Accept the same fields in a different order.
+    pub ignore_field_order: bool,
+}
+
+/// Panics if the documents `left` and `right` have different values, or if either can't be parsed.
+#[track_caller]
+pub fn assert_ron_eq(left: &str, right: &str) {
+    assert_ron_eq_with(left, right, &CompareOptions::default());
+}
+
+/// Like [`assert_ron_eq`], comparing with the given options.
+#[track_caller]
+pub fn assert_ron_eq_with(left: &str, right: &str, options: &CompareOptions) {
+    let left = parse(left, "left");
+    let right = parse(right, "right");
+    check(&left, &right, options);
+}
+
+/// Panics if `value` doesn't serialize to a document with the same value as `expected`.
+#[track_caller]
+pub fn assert_serializes_to<T: RonSerialize + ?Sized>(value: &T, expected: &str) {
+    let value = match crate::to_string(value) {
+        Ok(x) => parse(&x, "serialized"),
+        Err(err) => panic!("Failed to serialize the value: {err}"),
+    };
+    check(&value, &parse(expected, "expected"), &CompareOptions::default());
+}
+
+/// The changes that turn `left` into `right`, following `options`. Structs with the same fields in another order are
+/// a change of the whole struct.
+pub fn differences(left: &RonValue, right: &RonValue, options: &CompareOptions) -> Vec<Change> {
+    let mut changes = diff(left, right);
+    if changes.is_empty() && !options.ignore_field_order {
+        field_order(left, right, "", &mut changes);
+    }
+    return changes;
+}
+
+#[track_caller]
+fn parse(src: &str, which: &str) -> RonValue {
+    return match crate::from_str(src) {
+        Ok(x) => x,
+        Err(err) => panic!("Failed to parse the {which} document: {err}"),
+    };
+}
+
+#[track_caller]
+fn check(left: &RonValue, right: &RonValue, options: &CompareOptions) {
+    let changes = differences(left, right, options);
+    if changes.is_empty() {
+        return;
+    }
+    let mut message = String::from("The documents differ:");
+    for change in &changes {
+        // Values written over several lines stay indented under their change.
+        write!(message, "\n    {}", change.to_string().replace('\n', "\n    ")).unwrap();
+    }
+    panic!("{message}");
+}
+
+/// Finds the structs whose fields are in another order, for documents with the same values otherwise.
+fn field_order(left: &RonValue, right: &RonValue, path: &str, changes: &mut Vec<Change>) {
+    match (left, right) {
+        (RonValue::Some(a), RonValue::Some(b)) => field_order(a, b, path, changes),
+        (RonValue::Struct { fields: a, .. }, RonValue::Struct { fields: b, .. }) => {
+            if a.iter().zip(b).any(|(x, y)| x.0 != y.0) {
+                changes.push(Change::Changed { path: String::from(path), old: left.clone(), new: right.clone() });
+                return;
+            }
+            for ((name, x), (_, y)) in a.iter().zip(b) {
+                field_order(x, y, &field_path(path, name), changes);
+            }
+        },
+        (RonValue::Map(a), RonValue::Map(b)) => {
+            let keys: Vec<_> = a.iter().chain(b).map(|x| &x.0).collect();
+            for (key, x) in a {
+                if let Some((_, y)) = b.iter().find(|y| y.0 == *key) {
+                    field_order(x, y, &key_path(path, key, &keys), changes);
+                }
+            }
+        },
+        (RonValue::List(a), RonValue::List(b)) | (RonValue::Tuple { values: a, .. }, RonValue::Tuple { values: b, .. }) => {
+            for (i, (x, y)) in a.iter().zip(b).enumerate() {
+                field_order(x, y, &format!("{path}[{i}]"), changes);
+            }
+        },
+        _ => {},
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(f: impl FnOnce() + std::panic::UnwindSafe) -> String {
+        let payload = std::panic::catch_unwind(f).unwrap_err();
+        return payload.downcast::<String>().map(|x| *x).unwrap();
+    }
+
+    #[test]
+    fn equal_test() {
+        assert_ron_eq("Player(hp: 3, items: [Sword, Shield,],)", "Player( hp: 3, // health\n items: [Sword, Shield] )");
+        assert_ron_eq("{\"a\": 1, \"b\": 2}", "{\"b\": 2, \"a\": 1}");
+        assert_ron_eq_with("(a: 1, b: (c: 2, d: 3))", "(b: (d: 3, c: 2), a: 1)", &CompareOptions { ignore_field_order: true });
+        assert_serializes_to(&vec![Some(1), None], "[Some(1), None]");
+    }
+
+    #[test]
+    fn differ_test() {
+        assert_eq!(message(|| assert_ron_eq("(a: 1, b: [2])", "(a: 2, b: [2, 3])")), "The documents differ:\n    ~ a: 1 -> 2\n    + b[1]: 3");
+        assert_eq!(message(|| assert_ron_eq("[(a: 1, b: 2)]", "[(b: 2, a: 1)]")), "The documents differ:\n    ~ [0]: (\n        a: 1,\n        b: 2,\n    ) -> (\n        b: 2,\n        a: 1,\n    )");
+        assert_eq!(message(|| assert_serializes_to("a", "\"b\"")), "The documents differ:\n    ~ \"a\" -> \"b\"");
+    }
+
+    #[test]
+    fn parse_error_test() {
+        assert!(message(|| assert_ron_eq("[1]", "[1")).starts_with("Failed to parse the right document: "));
+    }
+}
@@ -0,0 +1,648 @@
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt::{self, Write};
+use std::ops::Index;
+
+use crate::deserial::{unexpected, RonDeserialize, RonDeserializer, RonError, RonEvent, RonPrimitive};
+use crate::path::{self, Segment};
+use crate::serial::{RonSerialize, RonSerializer};
+
+/// Any RON value, owned, for documents whose shape isn't known in advance.
+///
+/// Maps keep their entries in document order, and keys can be any value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RonValue {
+    None,
+    Some(Box<RonValue>),
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    Char(char),
+    Str(String),
+    /// A bare identifier, like an enum variant without data.
+    Enum(String),
+    /// A byte string, like `b"\x00ab"`.
+    Bytes(Vec<u8>),
+    List(Vec<RonValue>),
+    Map(Vec<(RonValue, RonValue)>),
+    Tuple { name: Option<String>, values: Vec<RonValue> },
+    Struct { name: Option<String>, fields: Vec<(String, RonValue)> },
+}
+
+/// Whether a map key is selected by a path field: strings and identifiers by their text, and any other key by its RON text.
+pub(crate) fn key_matches(key: &RonValue, name: &str) -> bool {
+    return match key {
+        RonValue::Str(x) | RonValue::Enum(x) => x == name,
+        x => x.to_string() == name,
+    };
+}
+
+/// The text of a map key where only strings can be keys, like JSON and TOML: its content for a string, and its RON text
+/// for anything else.
+#[cfg(any(feature = "json", feature = "toml", feature = "wasm"))]
+pub(crate) fn key_text(key: &RonValue) -> String {
+    return match key {
+        RonValue::Str(x) => x.clone(),
+        x => x.to_string(),
+    };
+}
+
+/// Whether a key looks like the name of a type, which makes an object with only that key a tagged value when converting
+/// from other formats.
+#[cfg(any(feature = "json", feature = "toml", feature = "wasm"))]
+pub(crate) fn is_type_name(key: &str) -> bool {
+    return crate::deserial::lexer::is_plain_ident(key) && key.starts_with(|x: char| x.is_ascii_uppercase());
+}
+
+fn cmp_all(a: &[RonValue], b: &[RonValue]) -> Ordering {
+    return a.iter().zip(b).map(|(a, b)| a.total_cmp(b)).fold(Ordering::Equal, Ordering::then).then(a.len().cmp(&b.len()));
+}
+
+/// Returned when indexing something that isn't there.
+static NONE: RonValue = RonValue::None;
+
+impl RonValue {
+    /// `None` for integers that don't fit in an `i64`.
+    pub fn as_i64(&self) -> Option<i64> {
+        return match self.inner() {
+            RonValue::Int(x) => i64::try_from(*x).ok(),
+            _ => None,
+        };
+    }
+
+    /// Integers are converted too.
+    pub fn as_f64(&self) -> Option<f64> {
+        return match self.inner() {
+            RonValue::Float(x) => Some(*x),
+            RonValue::Int(x) => Some(*x as f64),
+            _ => None,
+        };
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        return match self.inner() {
+            RonValue::Bool(x) => Some(*x),
+            _ => None,
+        };
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        return match self.inner() {
+            RonValue::Str(x) => Some(x),
+            _ => None,
+        };
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        return match self.inner() {
+            RonValue::Bytes(x) => Some(x),
+            _ => None,
+        };
+    }
+
+    /// The value at `path`, in the syntax of [`query`](crate::query), or `None` if there's nothing there or the path is
+    /// invalid.
+    pub fn get(&self, path: &str) -> Option<&RonValue> {
+        let mut value = self;
+        for x in path::parse(path).ok()? {
+            value = value.child(&x)?;
+        }
+        return Some(value);
+    }
+
+    /// Like `get`, for changing the value.
+    pub fn get_mut(&mut self, path: &str) -> Option<&mut RonValue> {
+        let mut value = self;
+        for x in path::parse(path).ok()? {
+            value = value.child_mut(&x)?;
+        }
+        return Some(value);
+    }
+
+    /// Sorts the entries of every map by key and the fields of every struct by name, so values that only differ in
+    /// those orders become equal.
+    pub fn canonicalize(&mut self) {
+        match self {
+            RonValue::Some(x) => x.canonicalize(),
+            RonValue::List(values) | RonValue::Tuple { values, .. } => values.iter_mut().for_each(RonValue::canonicalize),
+            RonValue::Map(entries) => {
+                for (k, v) in entries.iter_mut() {
+                    k.canonicalize();
+                    v.canonicalize();
+                }
+                entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+            },
+            RonValue::Struct { fields, .. } => {
+                fields.iter_mut().for_each(|x| x.1.canonicalize());
+                fields.sort_by(|a, b| a.0.cmp(&b.0));
+            },
+            _ => {},
+        }
+    }
+
+    /// A total order of values: by kind first, in the order of the variants, and then by contents.
+    pub fn total_cmp(&self, other: &RonValue) -> Ordering {
+        return match (self, other) {
+            (RonValue::Some(a), RonValue::Some(b)) => a.total_cmp(b),
+            (RonValue::Int(a), RonValue::Int(b)) => a.cmp(b),
+            (RonValue::Float(a), RonValue::Float(b)) => a.total_cmp(b),
+            (RonValue::Bool(a), RonValue::Bool(b)) => a.cmp(b),
+            (RonValue::Char(a), RonValue::Char(b)) => a.cmp(b),
+            (RonValue::Str(a), RonValue::Str(b)) | (RonValue::Enum(a), RonValue::Enum(b)) => a.cmp(b),
+            (RonValue::Bytes(a), RonValue::Bytes(b)) => a.cmp(b),
+            (RonValue::List(a), RonValue::List(b)) => cmp_all(a, b),
+            (RonValue::Map(a), RonValue::Map(b)) => {
+                let pairs = a.iter().zip(b).map(|(a, b)| a.0.total_cmp(&b.0).then_with(|| a.1.total_cmp(&b.1)));
+                pairs.fold(Ordering::Equal, Ordering::then).then(a.len().cmp(&b.len()))
+            },
+            (RonValue::Tuple { name: n1, values: a }, RonValue::Tuple { name: n2, values: b }) => n1.cmp(n2).then_with(|| cmp_all(a, b)),
+            (RonValue::Struct { name: n1, fields: a }, RonValue::Struct { name: n2, fields: b }) => {
+                let pairs = a.iter().zip(b).map(|(a, b)| a.0.cmp(&b.0).then_with(|| a.1.total_cmp(&b.1)));
+                n1.cmp(n2).then_with(|| pairs.fold(Ordering::Equal, Ordering::then)).then(a.len().cmp(&b.len()))
+            },
+            (a, b) => a.rank().cmp(&b.rank()),
+        };
+    }
+
+    fn rank(&self) -> u8 {
+        return match self {
+            RonValue::None => 0,
+            RonValue::Some(_) => 1,
+            RonValue::Int(_) => 2,
+            RonValue::Float(_) => 3,
+            RonValue::Bool(_) => 4,
+            RonValue::Char(_) => 5,
+            RonValue::Str(_) => 6,
+            RonValue::Enum(_) => 7,
+            RonValue::Bytes(_) => 8,
+            RonValue::List(_) => 9,
+            RonValue::Map(_) => 10,
+            RonValue::Tuple { .. } => 11,
+            RonValue::Struct { .. } => 12,
+        };
+    }
+
+    /// The value inside any number of `Some`s.
+    fn inner(&self) -> &RonValue {
+        let mut value = self;
+        while let RonValue::Some(x) = value {
+            value = x;
+        }
+        return value;
+    }
+
+    /// Like `inner`, for changing the value.
+    pub(crate) fn inner_mut(&mut self) -> &mut RonValue {
+        let mut value = self;
+        while let RonValue::Some(x) = value {
+            value = x;
+        }
+        return value;
+    }
+
+    fn child(&self, segment: &Segment) -> Option<&RonValue> {
+        return match (self.inner(), segment) {
+            (RonValue::Struct { fields, .. }, Segment::Field(name)) => fields.iter().find(|x| x.0 == *name).map(|x| &x.1),
+            (RonValue::Map(entries), segment) => entries.iter().find(|x| segment.selects_key(&x.0)).map(|x| &x.1),
+            (RonValue::List(values) | RonValue::Tuple { values, .. }, Segment::Index(i)) => values.get(*i),
+            _ => None,
+        };
+    }
+
+    pub(crate) fn child_mut(&mut self, segment: &Segment) -> Option<&mut RonValue> {
+        return match (self.inner_mut(), segment) {
+            (RonValue::Struct { fields, .. }, Segment::Field(name)) => fields.iter_mut().find(|x| x.0 == *name).map(|x| &mut x.1),
+            (RonValue::Map(entries), segment) => entries.iter_mut().find(|x| segment.selects_key(&x.0)).map(|x| &mut x.1),
+            (RonValue::List(values) | RonValue::Tuple { values, .. }, Segment::Index(i)) => values.get_mut(*i),
+            _ => None,
+        };
+    }
+
+    /// Short human readable description of the kind of value, for error messages.
+    pub fn type_name(&self) -> &'static str {
+        return match self {
+            RonValue::None | RonValue::Some(_) => "an option",
+            RonValue::Int(_) => "an integer",
+            RonValue::Float(_) => "a float",
+            RonValue::Bool(_) => "a bool",
+            RonValue::Char(_) => "a char",
+            RonValue::Str(_) => "a string",
+            RonValue::Enum(_) => "an identifier",
+            RonValue::Bytes(_) => "a byte string",
+            RonValue::List(_) => "a list",
+            RonValue::Map(_) => "a map",
+            RonValue::Tuple { .. } => "a tuple",
+            RonValue::Struct { .. } => "a struct",
+        };
+    }
+}
+
+/// Looks up a struct field, or a map entry whose key is a string or an identifier with that text, or any other key with
+/// that RON text. Gives `RonValue::None` if there's none.
+impl Index<&str> for RonValue {
+    type Output = RonValue;
+
+    fn index(&self, index: &str) -> &RonValue {
+        return self.child(&Segment::Field(index)).unwrap_or(&NONE);
+    }
+}
+
+/// Looks up an element of a list or a tuple. Gives `RonValue::None` if there's none.
+impl Index<usize> for RonValue {
+    type Output = RonValue;
+
+    fn index(&self, index: usize) -> &RonValue {
+        return self.child(&Segment::Index(index)).unwrap_or(&NONE);
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($ty:ty),*) => {$(
+        impl From<$ty> for RonValue {
+            fn from(x: $ty) -> Self {
+                return RonValue::Int(i128::from(x));
+            }
+        }
+    )*};
+}
+
+impl_from_int!(i8, i16, i32, i64, i128, u8, u16, u32, u64);
+
+impl From<f32> for RonValue {
+    /// With the shortest digits that read back as `x`, like when an `f32` is serialized.
+    fn from(x: f32) -> Self {
+        return RonValue::Float(crate::serial::f32_to_f64(x));
+    }
+}
+
+impl From<f64> for RonValue {
+    fn from(x: f64) -> Self {
+        return RonValue::Float(x);
+    }
+}
+
+impl From<bool> for RonValue {
+    fn from(x: bool) -> Self {
+        return RonValue::Bool(x);
+    }
+}
+
+impl From<char> for RonValue {
+    fn from(x: char) -> Self {
+        return RonValue::Char(x);
+    }
+}
+
+impl From<&str> for RonValue {
+    fn from(x: &str) -> Self {
+        return RonValue::Str(String::from(x));
+    }
+}
+
+impl From<String> for RonValue {
+    fn from(x: String) -> Self {
+        return RonValue::Str(x);
+    }
+}
+
+impl<T: Into<RonValue>> From<Option<T>> for RonValue {
+    fn from(x: Option<T>) -> Self {
+        return match x {
+            Some(x) => RonValue::Some(Box::new(x.into())),
+            None => RonValue::None,
+        };
+    }
+}
+
+impl<T: Into<RonValue>> From<Vec<T>> for RonValue {
+    fn from(x: Vec<T>) -> Self {
+        return RonValue::List(x.into_iter().map(Into::into).collect());
+    }
+}
+
+impl<'a> RonDeserialize<'a> for RonValue {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        let event = de.next_non_comment()?;
+        return read_value(de, event);
+    }
+}
+
+/// Builds the value that starts with `event`, consuming the rest of its events.
+fn read_value(de: &mut RonDeserializer, event: RonEvent) -> Result<RonValue, RonError> {
+    return Ok(match event {
+        RonEvent::OptionalSomeValue => RonValue::Some(Box::new(RonValue::deserialize(de)?)),
+        RonEvent::Primitive(x) => match x {
+            RonPrimitive::NoneOptValue => RonValue::None,
+            RonPrimitive::Int(x) | RonPrimitive::TypedInt(x, _) => RonValue::Int(x),
+            RonPrimitive::Float(x) | RonPrimitive::TypedFloat(x, _) => RonValue::Float(x),
+            RonPrimitive::Bool(x) => RonValue::Bool(x),
+            RonPrimitive::Char(x) => RonValue::Char(x),
+            RonPrimitive::Str(x) => RonValue::Str(x.into_owned()),
+            RonPrimitive::Enum(x) => RonValue::Enum(String::from(x)),
+            RonPrimitive::Bytes(x) => RonValue::Bytes(x.into_owned()),
+        },
+        RonEvent::StructStart { name } => {
+            let mut fields = Vec::new();
+            loop {
+                match de.next_non_comment()? {
+                    RonEvent::NamedField(field) => fields.push((String::from(field), RonValue::deserialize(de)?)),
+                    RonEvent::StructEnd { .. } => break,
+                    x => return Err(unexpected(de, "a field", x)),
+                }
+            }
+            RonValue::Struct { name: name.map(String::from), fields }
+        },
+        RonEvent::TupleStart { name } => {
+            let mut values = Vec::new();
+            loop {
+                match de.next_non_comment()? {
+                    RonEvent::TupleEnd { .. } => break,
+                    x => values.push(read_value(de, x)?),
+                }
+            }
+            RonValue::Tuple { name: name.map(String::from), values }
+        },
+        RonEvent::ListStart => {
+            let mut values = Vec::new();
+            loop {
+                match de.next_non_comment()? {
+                    RonEvent::ListEnd => break,
+                    x => values.push(read_value(de, x)?),
+                }
+            }
+            RonValue::List(values)
+        },
+        RonEvent::MapStart => {
+            let mut entries = Vec::new();
+            loop {
+                match de.next_non_comment()? {
+                    RonEvent::MapEnd => break,
+                    x => {
+                        let key = read_value(de, x)?;
+                        entries.push((key, RonValue::deserialize(de)?));
+                    },
+                }
+            }
+            RonValue::Map(entries)
+        },
+        x => return Err(unexpected(de, "a value", x)),
+    });
+}
+
+impl RonSerialize for RonValue {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return match self {
+            RonValue::None => ser.write_event(RonEvent::Primitive(RonPrimitive::NoneOptValue)),
+            RonValue::Some(x) => {
+                ser.write_event(RonEvent::OptionalSomeValue)?;
+                x.serialize(ser)
+            },
+            RonValue::Int(x) => ser.write_event(RonEvent::Primitive(RonPrimitive::Int(*x))),
+            RonValue::Float(x) => ser.write_event(RonEvent::Primitive(RonPrimitive::Float(*x))),
+            RonValue::Bool(x) => ser.write_event(RonEvent::Primitive(RonPrimitive::Bool(*x))),
+            RonValue::Char(x) => ser.write_event(RonEvent::Primitive(RonPrimitive::Char(*x))),
+            RonValue::Str(x) => ser.write_event(RonEvent::Primitive(RonPrimitive::Str(Cow::Borrowed(x)))),
+            RonValue::Enum(x) => ser.write_event(RonEvent::Primitive(RonPrimitive::Enum(x))),
+            RonValue::Bytes(x) => ser.write_event(RonEvent::Primitive(RonPrimitive::Bytes(Cow::Borrowed(x)))),
+            RonValue::List(values) => {
+                ser.write_event(RonEvent::ListStart)?;
+                for x in values {
+                    x.serialize(ser)?;
+                }
+                ser.write_event(RonEvent::ListEnd)
+            },
+            RonValue::Map(entries) => {
+                ser.write_event(RonEvent::MapStart)?;
+                for (k, v) in entries {
+                    k.serialize(ser)?;
+                    v.serialize(ser)?;
+                }
+                ser.write_event(RonEvent::MapEnd)
+            },
+            RonValue::Tuple { name, values } => {
+                ser.write_event(RonEvent::TupleStart { name: name.as_deref() })?;
+                for x in values {
+                    x.serialize(ser)?;
+                }
+                ser.write_event(RonEvent::TupleEnd { name: name.as_deref() })
+            },
+            RonValue::Struct { name, fields } => {
+                ser.write_event(RonEvent::StructStart { name: name.as_deref() })?;
+                for (field, x) in fields {
+                    ser.write_event(RonEvent::NamedField(field))?;
+                    x.serialize(ser)?;
+                }
+                ser.write_event(RonEvent::StructEnd { name: name.as_deref() })
+            },
+        };
+    }
+}
+
+impl fmt::Display for RonValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ser = RonSerializer::new(f, Default::default());
+        return self.serialize(&mut ser);
+    }
+}
+
+/// A value kept as its source text, for parsing it later or passing it along untouched.
+///
+/// Serializing it writes its events again, so its formatting follows the serializer's rules.
+/// ```
+/// # use light_ron::value::RawValue;
+/// let (kind, data): (&str, RawValue) = light_ron::from_str("(\"spawn\", Enemy(hp: 3))").unwrap();
+/// assert_eq!(data.as_str(), "Enemy(hp: 3)");
+/// assert_eq!(data.parse::<light_ron::value::RonValue>().unwrap()["hp"].as_i64(), Some(3));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawValue<'a> {
+    src: &'a str,
+}
+
+impl<'a> RawValue<'a> {
+    /// Checks that `src` is a single value.
+    pub fn new(src: &'a str) -> Result<Self, RonError> {
+        return crate::from_str(src);
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        return self.src;
+    }
+
+    pub fn parse<T: RonDeserialize<'a>>(&self) -> Result<T, RonError> {
+        return crate::from_str(self.src);
+    }
+}
+
+impl<'a> RonDeserialize<'a> for RawValue<'a> {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        return Ok(Self { src: de.next_raw_value()? });
+    }
+}
+
+impl RonSerialize for RawValue<'_> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return crate::transcode(&mut RonDeserializer::new(self.src), ser).map_err(|_| fmt::Error);
+    }
+}
+
+impl fmt::Display for RawValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return f.write_str(self.src);
+    }
+}
+
+/// Bytes read from and written as a byte string, like `b"\x00ab"`, instead of a list of integers.
+/// ```
+/// # use light_ron::value::Bytes;
+/// let hash: Bytes = light_ron::from_str(r#"b"\x1f\x8b""#).unwrap();
+/// assert_eq!(*hash.0, [0x1f, 0x8b]);
+/// assert_eq!(light_ron::to_string(&Bytes::from(b"a\n".as_slice())).unwrap(), r#"b"a\n""#);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Bytes<'a>(pub Cow<'a, [u8]>);
+
+impl<'a> From<&'a [u8]> for Bytes<'a> {
+    fn from(x: &'a [u8]) -> Self {
+        return Self(Cow::Borrowed(x));
+    }
+}
+
+impl From<Vec<u8>> for Bytes<'_> {
+    fn from(x: Vec<u8>) -> Self {
+        return Self(Cow::Owned(x));
+    }
+}
+
+impl<'a> RonDeserialize<'a> for Bytes<'a> {
+    fn deserialize(de: &mut RonDeserializer<'a>) -> Result<Self, RonError> {
+        return match de.next_non_comment()? {
+            RonEvent::Primitive(RonPrimitive::Bytes(x)) => Ok(Self(x)),
+            x => Err(unexpected(de, "a byte string", x)),
+        };
+    }
+}
+
+impl RonSerialize for Bytes<'_> {
+    fn serialize<W: Write>(&self, ser: &mut RonSerializer<W>) -> fmt::Result {
+        return ser.write_event(RonEvent::Primitive(RonPrimitive::Bytes(Cow::Borrowed(&self.0))));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_test() {
+        let value: RonValue = crate::from_str(r#"Player(name: "a", pos: (1, 2.5), tags: [Fire, Some('x')], map: {1: None})"#).unwrap();
+        assert_eq!(value, RonValue::Struct {
+            name: Some(String::from("Player")),
+            fields: vec![
+                (String::from("name"), RonValue::Str(String::from("a"))),
+                (String::from("pos"), RonValue::Tuple { name: None, values: vec![RonValue::Int(1), RonValue::Float(2.5)] }),
+                (String::from("tags"), RonValue::List(vec![RonValue::Enum(String::from("Fire")), RonValue::Some(Box::new(RonValue::Char('x')))])),
+                (String::from("map"), RonValue::Map(vec![(RonValue::Int(1), RonValue::None)])),
+            ],
+        });
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let src = "Named(\n    a: [\n        (1, true),\n    ],\n    b: {\n        \"k\": Some(-1.0),\n    },\n)";
+        let value: RonValue = crate::from_str(src).unwrap();
+        assert_eq!(value.to_string(), src);
+        assert_eq!(crate::to_string(&value).unwrap(), src);
+    }
+
+    #[test]
+    fn error_test() {
+        assert!(crate::from_str::<RonValue>("[1, ").is_err());
+        assert!(crate::from_str::<RonValue>("").is_err());
+    }
+
+    #[test]
+    fn index_test() {
+        let value: RonValue = crate::from_str(r#"Player(name: "a", pos: (1, 2.5), factions: {"pirates": -100, crabs: 30}, pet: Some((alive: true)))"#).unwrap();
+        assert_eq!(value["name"].as_str(), Some("a"));
+        assert_eq!(value["pos"][0].as_i64(), Some(1));
+        assert_eq!(value["pos"][0].as_f64(), Some(1.0));
+        assert_eq!(value["pos"][1].as_f64(), Some(2.5));
+        assert_eq!(value["pos"][1].as_i64(), None);
+        assert_eq!(value["factions"]["crabs"].as_i64(), Some(30));
+        assert_eq!(value["pet"]["alive"].as_bool(), Some(true));
+        assert_eq!(value["missing"][3]["deeper"], RonValue::None);
+    }
+
+    #[test]
+    fn get_test() {
+        let value: RonValue = crate::from_str(r#"(powers: [Fire, Some(Cast(3))], map: {"a": ["x"]})"#).unwrap();
+        assert_eq!(value.get("powers[1][0]"), Some(&RonValue::Int(3)));
+        assert_eq!(value.get("map.a[0]").and_then(RonValue::as_str), Some("x"));
+        assert_eq!(value.get(""), Some(&value));
+        assert_eq!(value.get("powers[2]"), None);
+        assert_eq!(value.get("powers["), None);
+
+        let mut value: RonValue = crate::from_str("{1: [0], \"a\": 2}").unwrap();
+        *value.get_mut("1[0]").unwrap() = RonValue::Int(5);
+        assert_eq!(value["1"][0], RonValue::Int(5));
+    }
+
+    #[test]
+    fn from_test() {
+        assert_eq!(RonValue::from(3u8), RonValue::Int(3));
+        assert_eq!(RonValue::from(u64::MAX), RonValue::Int(u64::MAX as i128));
+        assert_eq!(crate::from_str::<RonValue>("18446744073709551615").unwrap(), RonValue::from(u64::MAX));
+        assert_eq!(RonValue::from(u64::MAX).as_i64(), None);
+        assert_eq!(RonValue::from(0.5f32), RonValue::Float(0.5));
+        assert_eq!(RonValue::from(0.1f32), RonValue::Float(0.1));
+        assert_eq!(RonValue::from("a"), RonValue::Str(String::from("a")));
+        assert_eq!(RonValue::from(Some('x')), RonValue::Some(Box::new(RonValue::Char('x'))));
+        assert_eq!(RonValue::from(vec![true]), RonValue::List(vec![RonValue::Bool(true)]));
+    }
+
+    #[test]
+    fn canonicalize_test() {
+        let mut a: RonValue = crate::from_str(r#"(b: {"z": 1, "a": [{2: 0, 1: 0}]}, a: Some((y: 1, x: 2)))"#).unwrap();
+        let mut b: RonValue = crate::from_str(r#"(a: Some((x: 2, y: 1)), b: {"a": [{1: 0, 2: 0}], "z": 1})"#).unwrap();
+        assert_ne!(a, b);
+        a.canonicalize();
+        b.canonicalize();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn total_cmp_test() {
+        let mut values = vec![RonValue::Str(String::from("b")), RonValue::Float(-0.5), RonValue::Int(3), RonValue::None, RonValue::Int(-1), RonValue::Str(String::from("a"))];
+        values.sort_by(RonValue::total_cmp);
+        assert_eq!(values, [RonValue::None, RonValue::Int(-1), RonValue::Int(3), RonValue::Float(-0.5), RonValue::Str(String::from("a")), RonValue::Str(String::from("b"))]);
+        assert_eq!(RonValue::List(vec![RonValue::Int(1)]).total_cmp(&RonValue::List(vec![RonValue::Int(1), RonValue::Int(0)])), Ordering::Less);
+    }
+
+    #[test]
+    fn raw_value_test() {
+        let raw: Vec<RawValue> = crate::from_str("[Some( 1 ), (a: [2])]").unwrap();
+        assert_eq!(raw.iter().map(RawValue::as_str).collect::<Vec<_>>(), ["Some( 1 )", "(a: [2])"]);
+        assert_eq!(crate::to_string(&raw).unwrap(), "[\n    Some(1),\n    (\n        a: [\n            2,\n        ],\n    ),\n]");
+        assert_eq!(raw[0].parse::<Option<i32>>(), Ok(Some(1)));
+
+        assert_eq!(RawValue::new(" [1] ").map(|x| x.as_str()), Ok("[1]"));
+        assert!(RawValue::new("[1] 2").is_err());
+        assert!(RawValue::new("[1").is_err());
+    }
+
+    #[test]
+    fn bytes_test() {
+        let value: RonValue = crate::from_str(r#"{b"k\x00": [b"", b"\xff\"x"]}"#).unwrap();
+        assert_eq!(value, RonValue::Map(vec![(RonValue::Bytes(vec![b'k', 0]), RonValue::List(vec![RonValue::Bytes(vec![]), RonValue::Bytes(vec![0xff, b'"', b'x'])]))]));
+        assert_eq!(value[0].as_bytes(), None);
+        assert_eq!(crate::from_str::<RonValue>(&value.to_string()), Ok(value));
+
+        let bytes: Vec<Bytes> = crate::from_str(r#"[b"ab", b"\tc"]"#).unwrap();
+        assert!(matches!(bytes[0].0, Cow::Borrowed(b"ab")));
+        assert!(matches!(bytes[1].0, Cow::Owned(_)));
+        assert_eq!(crate::to_string(&Bytes::from(vec![0, b'\\', 0x7f, b'~'])).unwrap(), r#"b"\x00\\\x7f~""#);
+        assert!(crate::from_str::<Bytes>("\"ab\"").is_err());
+    }
+}
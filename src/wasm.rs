@@ -0,0 +1,199 @@
+//! Conversions between RON documents and JavaScript values, behind the `wasm` feature, so editors running in the
+//! browser can work on plain JS objects.
+//!
+//! Values are converted with the same rules as the [`json`](crate::json) module uses, except that:
+//! - Numbers stay numbers even when they aren't finite, and integers past 2<sup>53</sup> lose precision.
+//! - Byte strings become `Uint8Array`s.
+//! - Going back, numbers without a fractional part become integers, `undefined` becomes `None` like `null`, and
+//!   `Map`s become maps whose keys are converted like any other value.
+//!
+//! [`RonError`] converts into a JS `Error`, so it can be returned from `#[wasm_bindgen]` functions:
+//! ```no_run
+//! # use wasm_bindgen::JsValue;
+//! # use light_ron::serial::PrettyConfig;
+//! // #[wasm_bindgen]
+//! pub fn format_config(src: &str) -> Result<String, JsValue> {
+//!     let value = light_ron::wasm::to_js(src)?;
+//!     return Ok(light_ron::wasm::from_js(&value, PrettyConfig::default())?);
+//! }
+//! ```
+
+use js_sys::{Array, Object, Reflect, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::deserial::lexer::is_plain_ident;
+use crate::deserial::RonError;
+use crate::serial::PrettyConfig;
+use crate::value::{is_type_name, key_text, RonValue};
+
+/// Parses a RON document into a JS value.
+pub fn to_js(src: &str) -> Result<JsValue, RonError> {
+    return Ok(value_to_js(&crate::from_str(src)?));
+}
+
+/// Writes a JS value as a RON document with the given formatting rules.
+pub fn from_js(value: &JsValue, config: PrettyConfig) -> Result<String, RonError> {
+    return crate::to_string_pretty(&js_to_value(value)?, config);
+}
+
+pub fn value_to_js(value: &RonValue) -> JsValue {
+    return match value {
+        RonValue::None => JsValue::NULL,
+        RonValue::Some(x) => value_to_js(x),
+        RonValue::Int(x) => JsValue::from_f64(*x as f64),
+        RonValue::Float(x) => JsValue::from_f64(*x),
+        RonValue::Bool(x) => JsValue::from_bool(*x),
+        RonValue::Char(x) => JsValue::from_str(&x.to_string()),
+        RonValue::Str(x) | RonValue::Enum(x) => JsValue::from_str(x),
+        RonValue::Bytes(x) => Uint8Array::from(x.as_slice()).into(),
+        RonValue::List(values) | RonValue::Tuple { name: None, values } => array(values),
+        RonValue::Tuple { name: Some(name), values } => {
+            let inner = if values.len() == 1 { value_to_js(&values[0]) } else { array(values) };
+            object([(name.as_str(), inner)])
+        },
+        RonValue::Map(entries) => {
+            let keys: Vec<String> = entries.iter().map(|x| key_text(&x.0)).collect();
+            object(keys.iter().zip(entries).map(|(k, (_, v))| (k.as_str(), value_to_js(v))))
+        },
+        RonValue::Struct { name, fields } => {
+            let inner = object(fields.iter().map(|(k, v)| (k.as_str(), value_to_js(v))));
+            match name {
+                Some(name) => object([(name.as_str(), inner)]),
+                None => inner,
+            }
+        },
+    };
+}
+
+/// Converts a JS value back. Functions, symbols and bigints can't be converted.
+pub fn js_to_value(value: &JsValue) -> Result<RonValue, RonError> {
+    if value.is_null() || value.is_undefined() {
+        return Ok(RonValue::None);
+    }
+    if let Some(x) = value.as_bool() {
+        return Ok(RonValue::Bool(x));
+    }
+    if let Some(x) = value.as_f64() {
+        // Past 2^63 the cast would saturate, so those stay floats.
+        if x.fract() == 0.0 && x.abs() < 9.2e18 {
+            return Ok(RonValue::Int(i128::from(x as i64)));
+        }
+        return Ok(RonValue::Float(x));
+    }
+    if let Some(x) = value.as_string() {
+        return Ok(RonValue::Str(x));
+    }
+    if let Some(x) = value.dyn_ref::<Uint8Array>() {
+        return Ok(RonValue::Bytes(x.to_vec()));
+    }
+    if let Some(x) = value.dyn_ref::<Array>() {
+        return Ok(RonValue::List(x.iter().map(|x| js_to_value(&x)).collect::<Result<_, _>>()?));
+    }
+    if let Some(x) = value.dyn_ref::<js_sys::Map>() {
+        let mut entries = Vec::new();
+        for entry in x.entries() {
+            let entry: Array = entry.map_err(js_error)?.unchecked_into();
+            entries.push((js_to_value(&entry.get(0))?, js_to_value(&entry.get(1))?));
+        }
+        return Ok(RonValue::Map(entries));
+    }
+    let Some(entries) = object_entries(value) else {
+        return Err(RonError::new(format!("Can't convert {value:?} to RON")));
+    };
+    if let [(name, inner)] = &entries[..] {
+        if is_type_name(name) {
+            return tagged(name, inner);
+        }
+    }
+    return fields(entries, None);
+}
+
+/// Reads the value of an object tagged with `name`.
+fn tagged(name: &str, inner: &JsValue) -> Result<RonValue, RonError> {
+    let name = Some(String::from(name));
+    if let Some(entries) = object_entries(inner).filter(|x| x.iter().all(|x| is_plain_ident(&x.0))) {
+        return fields(entries, name);
+    }
+    return match js_to_value(inner)? {
+        RonValue::List(values) => Ok(RonValue::Tuple { name, values }),
+        x => Ok(RonValue::Tuple { name, values: vec![x] }),
+    };
+}
+
+/// The entries of a plain object, or `None` for anything else. Arrays, maps and byte arrays have to be checked first.
+fn object_entries(value: &JsValue) -> Option<Vec<(String, JsValue)>> {
+    if value.is_function() || value.is_symbol() || value.is_bigint() || !value.is_object() {
+        return None;
+    }
+    let entries = Object::entries(value.unchecked_ref());
+    let entries = entries.iter().map(|entry| {
+        let entry: Array = entry.unchecked_into();
+        return (entry.get(0).as_string().unwrap_or_default(), entry.get(1));
+    });
+    return Some(entries.collect());
+}
+
+/// Builds a struct from the entries of an object when all its keys are identifiers, or a map otherwise.
+fn fields(entries: Vec<(String, JsValue)>, name: Option<String>) -> Result<RonValue, RonError> {
+    if entries.iter().all(|x| is_plain_ident(&x.0)) {
+        let fields = entries.into_iter().map(|(k, v)| Ok((k, js_to_value(&v)?))).collect::<Result<_, RonError>>()?;
+        return Ok(RonValue::Struct { name, fields });
+    }
+    let entries = entries.into_iter().map(|(k, v)| Ok((RonValue::Str(k), js_to_value(&v)?))).collect::<Result<_, RonError>>()?;
+    return Ok(RonValue::Map(entries));
+}
+
+fn array(values: &[RonValue]) -> JsValue {
+    return values.iter().map(value_to_js).collect::<Array>().into();
+}
+
+fn object<'k>(entries: impl IntoIterator<Item = (&'k str, JsValue)>) -> JsValue {
+    let object = Object::new();
+    for (k, v) in entries {
+        // Setting a property of a plain object can't fail.
+        let _ = Reflect::set(&object, &JsValue::from_str(k), &v);
+    }
+    return object.into();
+}
+
+fn js_error(value: JsValue) -> RonError {
+    return RonError::new(format!("{value:?}"));
+}
+
+impl From<RonError> for JsValue {
+    fn from(err: RonError) -> Self {
+        return js_sys::Error::new(&err.to_string()).into();
+    }
+}
+
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn to_js_test() {
+        let value = to_js(r#"(name: "a", pos: (1, 2.5), spell: Cast(3), icon: b"\x01", pet: None)"#).unwrap();
+        let get = |key: &str| Reflect::get(&value, &JsValue::from_str(key)).unwrap();
+        assert_eq!(get("name").as_string().as_deref(), Some("a"));
+        assert!(Array::is_array(&get("pos")));
+        assert_eq!(Reflect::get(&get("spell"), &JsValue::from_str("Cast")).unwrap().as_f64(), Some(3.0));
+        assert!(Reflect::get(&to_js("Dog(age: 2)").unwrap(), &JsValue::from_str("Dog")).unwrap().is_object());
+        assert_eq!(get("icon").dyn_into::<Uint8Array>().unwrap().to_vec(), [1]);
+        assert!(get("pet").is_null());
+        assert!(to_js("[1,").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn round_trip_test() {
+        let src = r#"(a: [1, 2.5], b: Rect(1, 2), c: Dog(age: 2), d: {"pirate ship": true}, e: b"\x00")"#;
+        let value: RonValue = crate::from_str(src).unwrap();
+        assert_eq!(js_to_value(&value_to_js(&value)), Ok(value));
+
+        let map = js_sys::Map::new();
+        map.set(&JsValue::from_f64(1.0), &JsValue::from_str("x"));
+        assert_eq!(js_to_value(&map.into()), Ok(RonValue::Map(vec![(RonValue::Int(1), RonValue::Str(String::from("x")))])));
+        assert!(js_to_value(&js_sys::Function::new_no_args("")).is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! Checks that reading small documents doesn't allocate. It's a separate test binary because counting allocations
+//! needs its own global allocator, which the library can't have since it forbids unsafe code.
+
+#![allow(clippy::needless_return)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::borrow::Cow;
+use std::cell::Cell;
+
+use light_ron::deserial::{RonDeserialize, RonDeserializer, RonEvent};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|x| x.set(x.get() + 1));
+        return System.alloc(layout);
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Number of allocations made by the current thread while running `f`.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    return ALLOCATIONS.with(Cell::get) - before;
+}
+
+#[test]
+fn events_test() {
+    let src = r#"Player(name: "a", pos: (1, 2.5), tags: [A, B], pet: Some(Dog(age: 2u8)), stats: {"hp": 3}, icon: b"ab")"#;
+    let count = allocations(|| {
+        let mut de = RonDeserializer::new(src);
+        while de.next_event().unwrap() != RonEvent::Eof {}
+    });
+    assert_eq!(count, 0);
+
+    let deep = format!("{}1{}", "[".repeat(16), "]".repeat(16));
+    let count = allocations(|| {
+        let mut de = RonDeserializer::new(&deep);
+        while de.next_event().unwrap() != RonEvent::Eof {}
+    });
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn decode_test() {
+    let count = allocations(|| {
+        let value = <(&str, [f32; 2], Option<bool>)>::deserialize(&mut RonDeserializer::new(r#"("a", [1.0, 2.5], Some(true))"#));
+        assert_eq!(value, Ok(("a", [1.0, 2.5], Some(true))));
+    });
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn allocating_test() {
+    // Past the inline depth the stack moves to the heap, and so do escaped strings.
+    let deep = format!("{}{}", "[".repeat(17), "]".repeat(17));
+    assert!(allocations(|| _ = RonDeserializer::new(&deep).skip_value()) > 0);
+    assert!(allocations(|| _ = Cow::<str>::deserialize(&mut RonDeserializer::new(r#""a\n""#))) > 0);
+}
@@ -0,0 +1,119 @@
+//! Runs the parser over the documents in `tests/conformance`, comparing what it reads with the expected results next
+//! to them.
+//!
+//! - `valid/name.ron` is read to the end, and its events must match `valid/name.events`, one per line with its span,
+//!   like `0..7 StructStart { name: Some("Player") }`. Errors of recovery mode are written as `Error("message")`.
+//! - `invalid/name.ron` must fail, with the line, column and message of `invalid/name.error`, like
+//!   `2:13 Unexpected character '@'`.
+//!
+//! Lines of the expected files starting with `#` are comments, except `#options:` followed by the names of the
+//! [`DeserializerOptions`] to enable, like `#options: comments, documents`. Running the tests with `LIGHT_RON_BLESS=1`
+//! rewrites the expected files from the current results, keeping their `#` lines, so new documents only need their
+//! `.ron` file, and changed results show up in the diff.
+
+#![allow(clippy::needless_return)]
+
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use light_ron::deserial::{DeserializerOptions, RonDeserializer, RonError, RonEvent, SyntaxOptions};
+
+fn documents(dir: &str) -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/conformance").join(dir);
+    let mut paths: Vec<_> = std::fs::read_dir(dir).unwrap().map(|x| x.unwrap().path()).filter(|x| x.extension().is_some_and(|x| x == "ron")).collect();
+    paths.sort();
+    return paths;
+}
+
+/// The `#` lines of the expected file, if there's one.
+fn header(expected: &Path) -> Vec<String> {
+    let text = std::fs::read_to_string(expected).unwrap_or_default();
+    return text.lines().filter(|x| x.starts_with('#')).map(String::from).collect();
+}
+
+fn options(header: &[String]) -> DeserializerOptions {
+    let mut options = DeserializerOptions::default();
+    for name in header.iter().filter_map(|x| x.strip_prefix("#options:")).flat_map(|x| x.split(',')) {
+        match name.trim() {
+            "comments" => options.comments = true,
+            "documents" => options.documents = true,
+            "recover_errors" => options.recover_errors = true,
+            "reject_duplicate_map_keys" => options.reject_duplicate_map_keys = true,
+            "strict" => options.syntax = SyntaxOptions::strict(),
+            "lenient" => options.syntax = SyntaxOptions::lenient(),
+            x => panic!("Unknown option '{x}'"),
+        }
+    }
+    return options;
+}
+
+fn read_all(src: &str, options: DeserializerOptions) -> Result<String, RonError> {
+    let mut de = RonDeserializer::with_options(src, options);
+    let mut dump = String::new();
+    loop {
+        let event = de.next_event()?;
+        let span = de.last_span();
+        match &event {
+            RonEvent::Error(err) => writeln!(dump, "{}..{} Error({:?})", span.start, span.end, err.message()).unwrap(),
+            x => writeln!(dump, "{}..{} {x:?}", span.start, span.end).unwrap(),
+        }
+        if event == RonEvent::Eof {
+            return Ok(dump);
+        }
+    }
+}
+
+fn describe(err: &RonError) -> String {
+    let (line, column) = err.line_col().unwrap_or_default();
+    return format!("{line}:{column} {}\n", err.message());
+}
+
+/// Compares `result` with the expected file, or rewrites it when blessing. Returns the failure, if any.
+fn check(document: &Path, expected: &Path, header: &[String], result: Result<String, String>) -> Option<String> {
+    let bless = std::env::var_os("LIGHT_RON_BLESS").is_some();
+    let name = document.strip_prefix(env!("CARGO_MANIFEST_DIR")).unwrap_or(document).display();
+    let found = match result {
+        Ok(x) => x,
+        Err(x) => return Some(format!("{name}: {x}")),
+    };
+    if bless {
+        let header: String = header.iter().map(|x| format!("{x}\n")).collect();
+        std::fs::write(expected, header + &found).unwrap();
+        return None;
+    }
+
+    let Ok(text) = std::fs::read_to_string(expected) else {
+        return Some(format!("{name}: There's no {}, run with LIGHT_RON_BLESS=1 to write it", expected.display()));
+    };
+    let wanted: String = text.lines().filter(|x| !x.starts_with('#')).map(|x| format!("{x}\n")).collect();
+    if wanted == found {
+        return None;
+    }
+    return Some(format!("{name}:\n--- expected\n{wanted}--- found\n{found}"));
+}
+
+fn run(dir: &str, extension: &str, read: fn(&str, DeserializerOptions) -> Result<String, String>) {
+    let mut failures = Vec::new();
+    let documents = documents(dir);
+    assert!(!documents.is_empty(), "There are no documents in tests/conformance/{dir}");
+    for document in &documents {
+        let expected = document.with_extension(extension);
+        let header = header(&expected);
+        let src = std::fs::read_to_string(document).unwrap();
+        failures.extend(check(document, &expected, &header, read(&src, options(&header))));
+    }
+    assert!(failures.is_empty(), "{} of {} documents failed:\n\n{}", failures.len(), documents.len(), failures.join("\n"));
+}
+
+#[test]
+fn valid_test() {
+    run("valid", "events", |src, options| read_all(src, options).map_err(|err| format!("Unexpected error: {err}")));
+}
+
+#[test]
+fn invalid_test() {
+    run("invalid", "error", |src, options| match read_all(src, options) {
+        Ok(_) => Err(String::from("Expected an error, but the document was read")),
+        Err(err) => Ok(describe(&err)),
+    });
+}
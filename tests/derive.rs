@@ -0,0 +1,208 @@
+//! Checks the code generated by the derive macros, which can only be used from outside the crate.
+
+#![cfg(feature = "derive")]
+#![allow(clippy::needless_return)]
+
+use std::collections::BTreeMap;
+
+use light_ron::deserial::{ErrorKind, RonDeserialize, RonError};
+use light_ron::serial::RonSerialize;
+
+#[derive(Debug, PartialEq, RonSerialize, RonDeserialize)]
+struct Meters(f32);
+
+#[derive(Debug, PartialEq, RonSerialize, RonDeserialize)]
+struct Marker;
+
+#[derive(Debug, PartialEq, RonSerialize, RonDeserialize)]
+enum Spell {
+    Fire,
+    #[ron(rename = "Water", alias = "Wave")]
+    Tide { depth: i32 },
+    Beam(f32),
+    Bolt(i32, i32),
+}
+
+#[derive(Debug, PartialEq, RonSerialize, RonDeserialize)]
+struct Stats {
+    #[ron(rename = "maxHP", alias = "hp")]
+    max_hp: u32,
+    #[ron(default)]
+    mp: u32,
+}
+
+#[derive(Debug, PartialEq, RonSerialize, RonDeserialize)]
+#[ron(rename = "Player")]
+struct PlayerData<'a> {
+    name: &'a str,
+    r#type: Spell,
+    height: Meters,
+    marker: Marker,
+    #[ron(flatten)]
+    stats: Stats,
+    #[ron(default = "default_level", skip_serializing_if = "is_first_level")]
+    level: u8,
+    #[ron(skip)]
+    cache: Option<String>,
+    tags: BTreeMap<String, i32>,
+}
+
+#[derive(Debug, PartialEq, RonDeserialize)]
+#[ron(deny_unknown_fields)]
+struct Strict {
+    a: i32,
+    b: i32,
+    #[ron(flatten)]
+    stats: Stats,
+}
+
+#[derive(Debug, PartialEq, RonDeserialize)]
+#[ron(deny_unknown_fields)]
+enum StrictShape {
+    Square { side: f32 },
+}
+
+#[derive(Debug, PartialEq, RonSerialize, RonDeserialize)]
+struct Inventory {
+    #[ron(skip_serializing_if = "Vec::is_empty")]
+    items: Vec<String>,
+    #[ron(skip_serializing_if = "Option::is_none")]
+    weapon: Option<String>,
+}
+
+#[derive(Debug, PartialEq, RonSerialize, RonDeserialize)]
+struct Wrapper<T> {
+    inner: Vec<T>,
+}
+
+#[derive(Debug, PartialEq, RonSerialize, RonDeserialize)]
+struct Asset {
+    #[ron(with = "light_ron::codec::base64")]
+    icon: Vec<u8>,
+    #[ron(with = "light_ron::codec::hex", rename = "sha", default)]
+    hash: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, RonSerialize, RonDeserialize)]
+enum Blob {
+    Packed {
+        #[ron(with = "light_ron::codec::hex")]
+        data: Vec<u8>,
+    },
+}
+
+fn default_level() -> u8 {
+    return 1;
+}
+
+fn is_first_level(level: &u8) -> bool {
+    return *level == 1;
+}
+
+#[test]
+fn round_trip_test() {
+    let src = r#"Player(
+    name: "a",
+    type: Water(
+        depth: 3,
+    ),
+    height: Meters(1.5),
+    marker: Marker(),
+    maxHP: 10,
+    mp: 0,
+    tags: {
+        "b": 2,
+    },
+)"#;
+    let player: PlayerData = light_ron::from_str(src).unwrap();
+    let expected = PlayerData {
+        name: "a",
+        r#type: Spell::Tide { depth: 3 },
+        height: Meters(1.5),
+        marker: Marker,
+        stats: Stats { max_hp: 10, mp: 0 },
+        level: 1,
+        cache: None,
+        tags: BTreeMap::from([(String::from("b"), 2)]),
+    };
+    assert_eq!(player, expected);
+    assert_eq!(light_ron::to_string(&player).unwrap(), src);
+
+    let spells: Vec<Spell> = light_ron::from_str("[Fire, Wave(depth: 1), Beam(0.5), Bolt(1, 2)]").unwrap();
+    assert_eq!(spells, [Spell::Fire, Spell::Tide { depth: 1 }, Spell::Beam(0.5), Spell::Bolt(1, 2)]);
+    assert_eq!(light_ron::to_string(&spells[3]).unwrap(), "Bolt(1, 2)");
+    assert_eq!(light_ron::from_str::<Wrapper<u8>>("(inner: [1, 2])"), Ok(Wrapper { inner: vec![1, 2] }));
+}
+
+#[test]
+fn field_attributes_test() {
+    let src = r#"(name: "a", type: Fire, height: Meters(2.0), marker: (), hp: 5, level: 3, unknown: [1], tags: {})"#;
+    let player: PlayerData = light_ron::from_str(src).unwrap();
+    assert_eq!((player.stats, player.level, player.cache), (Stats { max_hp: 5, mp: 0 }, 3, None));
+
+    let mut out = String::new();
+    let mut ser = light_ron::serial::RonSerializer::new(&mut out, light_ron::serial::PrettyConfig::compact());
+    Stats { max_hp: 1, mp: 2 }.serialize(&mut ser).unwrap();
+    assert_eq!(out, "Stats(maxHP:1,mp:2)");
+}
+
+#[test]
+fn skip_serializing_if_test() {
+    let empty = Inventory { items: Vec::new(), weapon: None };
+    let src = light_ron::to_string(&empty).unwrap();
+    assert_eq!(src, "Inventory()");
+    assert_eq!(light_ron::from_str(&src), Ok(empty));
+
+    let full = Inventory { items: vec![String::from("a")], weapon: Some(String::from("b")) };
+    assert_eq!(light_ron::from_str(&light_ron::to_string(&full).unwrap()), Ok(full));
+}
+
+#[test]
+fn with_test() {
+    let asset = Asset { icon: b"hi".to_vec(), hash: vec![0x00, 0xff] };
+    let src = light_ron::to_string(&asset).unwrap();
+    assert_eq!(src, "Asset(\n    icon: \"aGk=\",\n    sha: \"00ff\",\n)");
+    assert_eq!(light_ron::from_str(&src), Ok(asset));
+    assert_eq!(light_ron::from_str(r#"(icon: b"\x01")"#), Ok(Asset { icon: vec![1], hash: Vec::new() }));
+    assert!(light_ron::from_str::<Asset>(r#"(icon: "not base64!")"#).is_err());
+
+    let blob = Blob::Packed { data: vec![0xab] };
+    let src = light_ron::to_string(&blob).unwrap();
+    assert_eq!(src, "Packed(\n    data: \"ab\",\n)");
+    assert_eq!(light_ron::from_str(&src), Ok(blob));
+}
+
+#[test]
+fn comments_test() {
+    let options = light_ron::deserial::DeserializerOptions { comments: true, ..Default::default() };
+    let mut de = light_ron::deserial::RonDeserializer::with_options("// Bag\nInventory( /* a */ items: [\"a\"], // b\n weapon: Some(\"c\") )", options.clone());
+    assert_eq!(Inventory::deserialize(&mut de), Ok(Inventory { items: vec![String::from("a")], weapon: Some(String::from("c")) }));
+    let mut de = light_ron::deserial::RonDeserializer::with_options("[Fire, /* a */ Water(/* b */ depth: 2), Bolt(1, /* c */ 2)]", options);
+    assert_eq!(Vec::<Spell>::deserialize(&mut de), Ok(vec![Spell::Fire, Spell::Tide { depth: 2 }, Spell::Bolt(1, 2)]));
+}
+
+#[test]
+fn error_test() {
+    let err: RonError = light_ron::from_str::<PlayerData>(r#"(name: "a", height: Meters(1.0), tags: {})"#).unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::MissingFields(vec![String::from("type"), String::from("marker"), String::from("maxHP")]));
+    let err = light_ron::from_str::<Spell>("Water(height: 1)").unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::MissingFields(vec![String::from("depth")]));
+    assert!(light_ron::from_str::<Spell>("Ice").is_err());
+    assert!(Meters::deserialize(&mut light_ron::deserial::RonDeserializer::new("(1.0, 2.0)")).is_err());
+}
+
+#[test]
+fn deny_unknown_fields_test() {
+    assert_eq!(light_ron::from_str("(b: 2, hp: 3, a: 1)"), Ok(Strict { a: 1, b: 2, stats: Stats { max_hp: 3, mp: 0 } }));
+
+    let err = light_ron::from_str::<Strict>("(a: 1, c: 3, b: 2, maxHP: 1)").unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::UnknownField(String::from("c")));
+    assert_eq!(err.span(), Some(light_ron::deserial::Span::new(7, 8)));
+    let err = light_ron::from_str::<StrictShape>("Square(side: 1.0, color: Red)").unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::UnknownField(String::from("color")));
+
+    // Every missing field is reported at once, including the ones of flattened structs.
+    let err = light_ron::from_str::<Strict>("(b: 2)").unwrap_err();
+    assert_eq!(err.kind(), &ErrorKind::MissingFields(vec![String::from("a"), String::from("maxHP")]));
+    assert_eq!(err.message(), "Missing fields a, maxHP");
+}